@@ -95,6 +95,17 @@ impl<const D: usize> Matrix<u64, D> {
         }
     }
 
+    #[cfg(feature = "parallel")]
+    fn encrypt_batch(
+        &self,
+        eek: &EncodingEncryptionKey<EncryptionKey<BigInteger>, Code<BigInteger>>,
+    ) -> Matrix<scalar::Ciphertext<BigInteger, u64>, D> {
+        Matrix {
+            inner: Paillier::encrypt_batch(eek, &self.inner),
+            dims: self.dims,
+        }
+    }
+
     fn encrypt_no_dot(
         &self,
         eek: &EncodingEncryptionKey<EncryptionKey<BigInteger>, Code<BigInteger>>,
@@ -120,6 +131,17 @@ impl<const D: usize> Matrix<scalar::Ciphertext<BigInteger, u64>, D> {
             dims: self.dims,
         }
     }
+
+    #[cfg(feature = "parallel")]
+    fn decrypt_batch(
+        &self,
+        ddk: &DecodingDecryptionKey<DecryptionKey<BigInteger>, Code<BigInteger>>,
+    ) -> Matrix<u64, D> {
+        Matrix {
+            inner: Paillier::decrypt_batch(ddk, &self.inner),
+            dims: self.dims,
+        }
+    }
 }
 
 impl<const D: usize> MatrixNoDot<vector::Ciphertext<BigInteger, u64>, D> {
@@ -239,6 +261,19 @@ impl<const D: usize> Matrix<scalar::Ciphertext<BigInteger, u64>, D> {
             dims: self.dims,
         }
     }
+
+    #[cfg(feature = "parallel")]
+    fn add_encrypted_batch(
+        &self,
+        other: &Self,
+        eek: &EncodingEncryptionKey<EncryptionKey<BigInteger>, Code<BigInteger>>,
+    ) -> Self {
+        assert_eq!(self.dims, other.dims);
+        Self {
+            inner: Paillier::add_batch(eek, &self.inner, &other.inner),
+            dims: self.dims,
+        }
+    }
 }
 
 impl<const D: usize> MatrixNoDot<vector::Ciphertext<BigInteger, u64>, D> {
@@ -431,6 +466,64 @@ fn bench_dot_full(c: &mut Criterion) {
     }
 }
 
+#[cfg(feature = "parallel")]
+fn bench_encryption_batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encryption batch");
+    for key_size in [1024, 2048] {
+        group.bench_function(format!("{}", key_size), |b| {
+            let (ek, dk) = Paillier::keypair_with_modulus_size(key_size).keys();
+            let code: Code<BigInteger> = integral::Code::default();
+
+            let eek = ek.with_code(&code);
+            let _ddk = dk.with_code(&code);
+            let p = Matrix::rand(&MATRIX_SIZE);
+            b.iter(|| {
+                let _ = p.encrypt_batch(black_box(&eek));
+            });
+        });
+    }
+}
+
+#[cfg(feature = "parallel")]
+fn bench_decryption_batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decryption batch");
+    for key_size in [1024, 2048] {
+        group.bench_function(format!("{}", key_size), |b| {
+            let (ek, dk) = Paillier::keypair_with_modulus_size(key_size).keys();
+            let code: Code<BigInteger> = integral::Code::default();
+
+            let eek = ek.with_code(&code);
+            let ddk = dk.with_code(&code);
+            let p = Matrix::rand(&MATRIX_SIZE);
+            let c = p.encrypt_batch(black_box(&eek));
+            b.iter(|| {
+                c.decrypt_batch(black_box(&ddk));
+            });
+        });
+    }
+}
+
+#[cfg(feature = "parallel")]
+fn bench_addition_batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("addition batch");
+    for key_size in [1024, 2048] {
+        group.bench_function(format!("{}", key_size), |b| {
+            let (ek, dk) = Paillier::keypair_with_modulus_size(key_size).keys();
+            let code: Code<BigInteger> = integral::Code::default();
+
+            let eek = ek.with_code(&code);
+            let _ddk = dk.with_code(&code);
+            let p1 = Matrix::rand(&MATRIX_SIZE);
+            let p2 = Matrix::rand(&MATRIX_SIZE);
+            let c1 = p1.encrypt_batch(&eek);
+            let c2 = p2.encrypt_batch(&eek);
+            b.iter(|| {
+                c1.add_encrypted_batch(black_box(&c2), black_box(&eek));
+            });
+        });
+    }
+}
+
 criterion_group! {
     name = benches;
     config = Criterion::default().sample_size(10);
@@ -445,4 +538,18 @@ criterion_group! {
     bench_multiplication_full,
     bench_dot_full
 }
+
+#[cfg(feature = "parallel")]
+criterion_group! {
+    name = batch_benches;
+    config = Criterion::default().sample_size(10);
+    targets =
+    bench_encryption_batch,
+    bench_decryption_batch,
+    bench_addition_batch
+}
+
+#[cfg(feature = "parallel")]
+criterion_main!(benches, batch_benches);
+#[cfg(not(feature = "parallel"))]
 criterion_main!(benches);
@@ -81,12 +81,16 @@ impl<const D: usize> Matrix<u64, D> {
         &self,
         eek: &EncodingEncryptionKey<EncryptionKey<BigInteger>, Code<BigInteger>>,
     ) -> Matrix<scalar::Ciphertext<BigInteger, u64>, D> {
+        #[cfg(feature = "parallel")]
+        let inner = Paillier::encrypt_batch(eek, &self.inner);
+        #[cfg(not(feature = "parallel"))]
+        let inner = self
+            .inner
+            .iter()
+            .map(|m| Paillier::encrypt(eek, m))
+            .collect();
         Matrix {
-            inner: self
-                .inner
-                .iter()
-                .map(|m| Paillier::encrypt(eek, m))
-                .collect(),
+            inner,
             dims: self.dims,
         }
     }
@@ -107,12 +111,16 @@ impl<const D: usize> Matrix<scalar::Ciphertext<BigInteger, u64>, D> {
         &self,
         ddk: &DecodingDecryptionKey<DecryptionKey<BigInteger>, Code<BigInteger>>,
     ) -> Matrix<u64, D> {
+        #[cfg(feature = "parallel")]
+        let inner = Paillier::decrypt_batch(ddk, &self.inner);
+        #[cfg(not(feature = "parallel"))]
+        let inner = self
+            .inner
+            .iter()
+            .map(|m| Paillier::decrypt(ddk, m))
+            .collect();
         Matrix {
-            inner: self
-                .inner
-                .iter()
-                .map(|m| Paillier::decrypt(ddk, m))
-                .collect(),
+            inner,
             dims: self.dims,
         }
     }
@@ -0,0 +1,649 @@
+#![cfg(feature = "inclfixed")]
+
+extern crate crypto_bigint;
+
+use self::crypto_bigint::modular::runtime_mod::{DynResidue, DynResidueParams};
+use self::crypto_bigint::subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use self::crypto_bigint::{Limb, NonZero, Random, RandomMod, Reciprocal, Uint, Word};
+use super::traits::*;
+use crate::Error;
+use num_traits::{One, Zero};
+use rand::rngs::OsRng;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Rem, Shl, Shr, Sub};
+use std::str::FromStr;
+
+/// Signed integer of `LIMBS` limbs backed by `crypto_bigint::Uint`.
+///
+/// Values are kept as a sign and a fixed-width magnitude, so nothing is allocated, and the time
+/// taken by addition, subtraction, and multiplication does not depend on the magnitudes; signs,
+/// which are public in the schemes, are branched on. Results that do not fit the width panic, as
+/// would integer overflow in a debug build.
+///
+/// Only `ModPowSec`, used for secret exponents with the `hardened` feature, is constant-time as a
+/// whole. Everything else may leak through its timing:
+///
+/// - `ModPow` only processes the exponent up to its highest set bit, so without `hardened` the
+///   length of secret exponents leaks;
+/// - division and remainder depend on the length of the divisor;
+/// - `EGCD`, `ModInv`, and `TryModInv` depend on the operands;
+/// - `ToBytes`, conversions, parsing, and formatting depend on the length of the value.
+///
+/// The width must hold products of two numbers modulo n², hence the four times larger storage of
+/// `Fixed2048`, `Fixed3072`, and `Fixed4096` for moduli n of 2048, 3072, and 4096 bits.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct FixedInt<const LIMBS: usize> {
+    magnitude: Uint<LIMBS>,
+    negative: bool,
+}
+
+/// Integer for 2048-bit moduli.
+pub type Fixed2048 = FixedInt<{ 8192 / Limb::BITS }>;
+
+/// Integer for 3072-bit moduli.
+pub type Fixed3072 = FixedInt<{ 12288 / Limb::BITS }>;
+
+/// Integer for 4096-bit moduli.
+pub type Fixed4096 = FixedInt<{ 16384 / Limb::BITS }>;
+
+const OVERFLOW: &str = "fixed-width integer overflow";
+
+impl<const LIMBS: usize> FixedInt<LIMBS> {
+    fn new(magnitude: Uint<LIMBS>, negative: bool) -> Self {
+        // zero is never negative, so that equality can be derived
+        let negative = negative && magnitude != Uint::ZERO;
+        FixedInt {
+            magnitude: magnitude,
+            negative: negative,
+        }
+    }
+
+    fn add_signed(&self, magnitude: &Uint<LIMBS>, negative: bool) -> Self {
+        if self.negative == negative {
+            let (sum, carry) = self.magnitude.adc(magnitude, Limb::ZERO);
+            assert!(carry == Limb::ZERO, "{}", OVERFLOW);
+            Self::new(sum, negative)
+        } else {
+            // subtract the smaller magnitude from the larger without branching on which it is
+            let (difference, borrow) = self.magnitude.sbb(magnitude, Limb::ZERO);
+            let swap = Choice::from((borrow.0 & 1) as u8);
+            let magnitude = Uint::conditional_select(&difference, &difference.wrapping_neg(), swap);
+            Self::new(magnitude, self.negative ^ bool::from(swap))
+        }
+    }
+
+    fn mul_signed(&self, rhs: &Self) -> Self {
+        let magnitude = checked_mul(&self.magnitude, &rhs.magnitude).expect(OVERFLOW);
+        Self::new(magnitude, self.negative ^ rhs.negative)
+    }
+
+    /// Division rounding towards zero, with the remainder taking the sign of the dividend.
+    fn div_rem(&self, rhs: &Self) -> (Self, Self) {
+        assert!(rhs.magnitude != Uint::ZERO, "division by zero");
+        let (quotient, remainder) = if rhs.magnitude.bits_vartime() <= Limb::BITS {
+            let divisor = NonZero::new(rhs.magnitude.as_limbs()[0]).unwrap();
+            let (quotient, remainder) = self.magnitude.div_rem_limb(divisor);
+            (quotient, Uint::from_word(remainder.0))
+        } else {
+            div_rem_limbs(&self.magnitude, &rhs.magnitude)
+        };
+        (
+            Self::new(quotient, self.negative ^ rhs.negative),
+            Self::new(remainder, self.negative),
+        )
+    }
+
+    /// Least non-negative residue modulo the magnitude of `modulus`.
+    fn reduce(&self, modulus: &Self) -> Uint<LIMBS> {
+        let remainder = self.div_rem(modulus).1;
+        if remainder.negative {
+            modulus.magnitude.wrapping_sub(&remainder.magnitude)
+        } else {
+            remainder.magnitude
+        }
+    }
+
+    /// Base of a modular exponentiation, reduced and inverted for negative exponents.
+    fn power_base(base: &Self, exponent: &Self, modulus: &Self) -> Uint<LIMBS> {
+        if exponent.negative {
+            Self::modinv(base, modulus).magnitude
+        } else {
+            base.reduce(modulus)
+        }
+    }
+}
+
+fn checked_mul<const LIMBS: usize>(a: &Uint<LIMBS>, b: &Uint<LIMBS>) -> Option<Uint<LIMBS>> {
+    let (low, high) = a.mul_wide(b);
+    if high == Uint::ZERO {
+        Some(low)
+    } else {
+        None
+    }
+}
+
+fn checked_add<const LIMBS: usize>(a: &Uint<LIMBS>, b: &Uint<LIMBS>) -> Option<Uint<LIMBS>> {
+    let (sum, carry) = a.adc(b, Limb::ZERO);
+    if carry == Limb::ZERO {
+        Some(sum)
+    } else {
+        None
+    }
+}
+
+/// Schoolbook division (Knuth's algorithm D) by a divisor of at least two limbs.
+///
+/// Every limb of the quotient is estimated from the leading limbs, which overestimates it by at
+/// most two, and then corrected by twice adding back the divisor if the remainder went negative.
+/// The operations performed thus depend only on the length of the divisor.
+fn div_rem_limbs<const LIMBS: usize>(
+    u: &Uint<LIMBS>,
+    v: &Uint<LIMBS>,
+) -> (Uint<LIMBS>, Uint<LIMBS>) {
+    let bits = v.bits_vartime();
+    let n = bits.div_ceil(Limb::BITS);
+    // estimates are made as if both were shifted to set the top bit of the divisor
+    let shift = n * Limb::BITS - bits;
+    let (reciprocal, _) = Reciprocal::ct_new(v.shl_vartime(shift).as_limbs()[n - 1]);
+    let v = v.as_limbs();
+
+    let mut quotient = Uint::ZERO;
+    let mut remainder = *u;
+    for j in (0..=LIMBS - n).rev() {
+        // the remainder is now below v * b^(j + 1), so limb j + n is its last
+        let r = remainder.as_limbs_mut();
+        let leading = Uint::<2>::from_words([
+            shifted_limb(r, j + n - 1, shift),
+            shifted_limb(r, j + n, shift),
+        ]);
+        let (estimate, _) = leading.ct_div_rem_limb_with_reciprocal(&reciprocal);
+        let [low, high] = estimate.to_limbs();
+        let mut q = Limb::conditional_select(&low, &Limb::MAX, high.ct_ne(&Limb::ZERO));
+
+        let mut top = r.get(j + n).cloned().unwrap_or(Limb::ZERO);
+        let (mut carry, mut borrow) = (Limb::ZERO, Limb::ZERO);
+        for i in 0..n {
+            let (product, product_carry) = Limb::ZERO.mac(v[i], q, carry);
+            let (difference, difference_borrow) = r[i + j].sbb(product, borrow);
+            r[i + j] = difference;
+            carry = product_carry;
+            borrow = difference_borrow;
+        }
+        let (difference, difference_borrow) = top.sbb(carry, borrow);
+        top = difference;
+        borrow = difference_borrow;
+
+        for _ in 0..2 {
+            let negative = borrow.ct_ne(&Limb::ZERO);
+            let mut carry = Limb::ZERO;
+            for i in 0..n {
+                let addend = Limb::conditional_select(&Limb::ZERO, &v[i], negative);
+                let (sum, sum_carry) = r[i + j].adc(addend, carry);
+                r[i + j] = sum;
+                carry = sum_carry;
+            }
+            // a carry out of the top limb takes the remainder back above zero
+            let (sum, sum_carry) = top.adc(Limb::ZERO, carry);
+            top = sum;
+            borrow = Limb::conditional_select(&borrow, &Limb::ZERO, sum_carry.ct_ne(&Limb::ZERO));
+            q = q.wrapping_sub(Limb(negative.unwrap_u8() as Word));
+        }
+        if j + n < LIMBS {
+            r[j + n] = top;
+        }
+        quotient.as_limbs_mut()[j] = q;
+    }
+    (quotient, remainder)
+}
+
+/// Limb `k` of `limbs` shifted left by `shift < Limb::BITS` bits, with limbs beyond the end zero.
+fn shifted_limb(limbs: &[Limb], k: usize, shift: usize) -> Word {
+    let limb = |i: usize| limbs.get(i).map_or(0, |limb| limb.0);
+    if shift == 0 {
+        limb(k)
+    } else {
+        (limb(k) << shift) | (limb(k - 1) >> (Limb::BITS - shift))
+    }
+}
+
+/// `base^exponent mod modulus` computed with `WIDTH` limbs, for `base < modulus` fitting in them.
+///
+/// Only `exponent_bits` bits of the exponent are used, and the time taken depends on no other
+/// property of the exponent.
+fn pow<const LIMBS: usize, const WIDTH: usize>(
+    base: &Uint<LIMBS>,
+    exponent: &Uint<LIMBS>,
+    exponent_bits: usize,
+    modulus: &Uint<LIMBS>,
+) -> Uint<LIMBS> {
+    if modulus.as_words()[0] & 1 == 0 {
+        return pow_even(base, exponent, exponent_bits, modulus);
+    }
+    let params = DynResidueParams::new(&modulus.resize::<WIDTH>());
+    DynResidue::new(&base.resize::<WIDTH>(), params)
+        .pow_bounded_exp(exponent, exponent_bits)
+        .retrieve()
+        .resize()
+}
+
+/// Square-and-multiply for the even moduli that Montgomery multiplication cannot handle.
+///
+/// These never occur in the schemes, so this is not constant-time.
+fn pow_even<const LIMBS: usize>(
+    base: &Uint<LIMBS>,
+    exponent: &Uint<LIMBS>,
+    exponent_bits: usize,
+    modulus: &Uint<LIMBS>,
+) -> Uint<LIMBS> {
+    let mut result = Uint::ONE.const_rem(modulus).0;
+    for i in (0..exponent_bits).rev() {
+        result = Uint::const_rem_wide(result.square_wide(), modulus).0;
+        if exponent.bit_vartime(i) {
+            result = Uint::const_rem_wide(result.mul_wide(base), modulus).0;
+        }
+    }
+    result
+}
+
+macro_rules! impl_modpow {
+    ($limbs:expr; $($width:expr),*) => {
+        impl ModPow for FixedInt<$limbs> {
+            fn modpow(base: &Self, exponent: &Self, modulus: &Self) -> Self {
+                let ref base = Self::power_base(base, exponent, modulus);
+                let ref exponent = exponent.magnitude;
+                let ref modulus = modulus.magnitude;
+                // use the narrowest width that fits the modulus; for n and n² this is a quarter
+                // and half of the storage
+                let bits = exponent.bits_vartime();
+                $(
+                    if modulus.bits_vartime() <= Uint::<$width>::BITS {
+                        return Self::new(pow::<$limbs, $width>(base, exponent, bits, modulus), false);
+                    }
+                )*
+                Self::new(pow::<$limbs, $limbs>(base, exponent, bits, modulus), false)
+            }
+        }
+
+        impl ModPowSec for FixedInt<$limbs> {
+            fn modpow_sec(base: &Self, exponent: &Self, modulus: &Self) -> Self {
+                let ref base = Self::power_base(base, exponent, modulus);
+                let ref exponent = exponent.magnitude;
+                let ref modulus = modulus.magnitude;
+                // every bit of the storage is processed, whatever the length of the exponent
+                let bits = Uint::<$limbs>::BITS;
+                $(
+                    if modulus.bits_vartime() <= Uint::<$width>::BITS {
+                        return Self::new(pow::<$limbs, $width>(base, exponent, bits, modulus), false);
+                    }
+                )*
+                Self::new(pow::<$limbs, $limbs>(base, exponent, bits, modulus), false)
+            }
+        }
+    };
+}
+
+impl_modpow!({ 8192 / Limb::BITS }; { 2048 / Limb::BITS }, { 4096 / Limb::BITS });
+impl_modpow!({ 12288 / Limb::BITS }; { 3072 / Limb::BITS }, { 6144 / Limb::BITS });
+impl_modpow!({ 16384 / Limb::BITS }; { 4096 / Limb::BITS }, { 8192 / Limb::BITS });
+
+impl<const LIMBS: usize> Samplable for FixedInt<LIMBS> {
+    fn sample_below(upper: &Self) -> Self {
+        let upper = NonZero::new(upper.magnitude).expect("empty range");
+        Self::new(Uint::random_mod(&mut OsRng, &upper), false)
+    }
+
+    fn sample(bitsize: usize) -> Self {
+        assert!(bitsize <= Uint::<LIMBS>::BITS, "{}", OVERFLOW);
+        let magnitude = Uint::random(&mut OsRng).shr_vartime(Uint::<LIMBS>::BITS - bitsize);
+        Self::new(magnitude, false)
+    }
+
+    fn sample_range(lower: &Self, upper: &Self) -> Self {
+        lower + &Self::sample_below(&(upper - lower))
+    }
+}
+
+impl<const LIMBS: usize> NumberTests for FixedInt<LIMBS> {
+    fn is_zero(&self) -> bool {
+        self.magnitude == Uint::ZERO
+    }
+    fn is_even(&self) -> bool {
+        self.magnitude.as_words()[0] & 1 == 0
+    }
+    fn is_negative(&self) -> bool {
+        self.negative
+    }
+}
+
+impl<const LIMBS: usize> ModInv for FixedInt<LIMBS> {
+    fn modinv(a: &Self, modulus: &Self) -> Self {
+        Self::try_modinv(a, modulus).unwrap()
+    }
+}
+
+impl<const LIMBS: usize> TryModInv for FixedInt<LIMBS> {
+    fn try_modinv(a: &Self, modulus: &Self) -> Result<Self, Error> {
+        let ref a = a.reduce(modulus);
+        let ref modulus = modulus.magnitude;
+        let (inverse, exists) = if modulus.as_words()[0] & 1 == 1 {
+            // bounding by the length of the modulus rather than the storage is much faster
+            let bits = modulus.bits_vartime();
+            a.inv_odd_mod_bounded(modulus, bits, bits)
+        } else {
+            a.inv_mod(modulus)
+        };
+        if bool::from(exists) {
+            Ok(Self::new(inverse, false))
+        } else {
+            Err(Error::NotInvertible)
+        }
+    }
+}
+
+impl<const LIMBS: usize> EGCD for FixedInt<LIMBS> {
+    fn egcd(a: &Self, b: &Self) -> (Self, Self, Self) {
+        // iterative, since the recursion of the generic version would overflow the stack with
+        // integers this large
+        let (mut d, mut r) = (a.clone(), b.clone());
+        let (mut s, mut s_next) = (Self::one(), Self::zero());
+        let (mut t, mut t_next) = (Self::zero(), Self::one());
+        while !NumberTests::is_zero(&r) {
+            let (ref q, remainder) = d.div_rem(&r);
+            d = ::std::mem::replace(&mut r, remainder);
+            let s_new = &s - &(q * &s_next);
+            s = ::std::mem::replace(&mut s_next, s_new);
+            let t_new = &t - &(q * &t_next);
+            t = ::std::mem::replace(&mut t_next, t_new);
+        }
+        (d, s, t)
+    }
+}
+
+impl<const LIMBS: usize> ConvertFrom<FixedInt<LIMBS>> for u64 {
+    fn _from(x: &FixedInt<LIMBS>) -> u64 {
+        u64::_try_from(x).unwrap()
+    }
+}
+
+impl<const LIMBS: usize> TryConvertFrom<FixedInt<LIMBS>> for u64 {
+    fn _try_from(x: &FixedInt<LIMBS>) -> Result<u64, Error> {
+        if x.negative || x.magnitude.bits_vartime() > 64 {
+            return Err(Error::DecodeOverflow);
+        }
+        Ok(x.to_bytes()
+            .iter()
+            .fold(0, |acc, byte| (acc << 8) | *byte as u64))
+    }
+}
+
+impl<const LIMBS: usize> BitManipulation for FixedInt<LIMBS> {
+    fn set_bit(self: &mut Self, bit: usize, bit_val: bool) {
+        let mask: Word = 1 << (bit % Limb::BITS);
+        let word = &mut self.magnitude.as_words_mut()[bit / Limb::BITS];
+        if bit_val {
+            *word |= mask;
+        } else {
+            *word &= !mask;
+        }
+        self.negative = self.negative && self.magnitude != Uint::ZERO;
+    }
+}
+
+impl<const LIMBS: usize> ToBytes for FixedInt<LIMBS> {
+    fn to_bytes(&self) -> Vec<u8> {
+        let bytes: Vec<u8> = self
+            .magnitude
+            .as_words()
+            .iter()
+            .rev()
+            .flat_map(|word| word.to_be_bytes())
+            .skip_while(|byte| *byte == 0)
+            .collect();
+        bytes
+    }
+}
+
+impl<const LIMBS: usize> FromBytes for FixedInt<LIMBS> {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let start = bytes
+            .iter()
+            .position(|byte| *byte != 0)
+            .unwrap_or(bytes.len());
+        let bytes = &bytes[start..];
+        assert!(bytes.len() <= Uint::<LIMBS>::BYTES, "{}", OVERFLOW);
+        let mut padded = vec![0; Uint::<LIMBS>::BYTES];
+        padded[Uint::<LIMBS>::BYTES - bytes.len()..].copy_from_slice(bytes);
+        Self::new(Uint::from_be_slice(&padded), false)
+    }
+}
+
+macro_rules! impl_binop {
+    ($imp:ident, $method:ident, |$a:ident, $b:ident| $body:expr) => {
+        impl<const LIMBS: usize> $imp<FixedInt<LIMBS>> for FixedInt<LIMBS> {
+            type Output = FixedInt<LIMBS>;
+            fn $method(self, rhs: FixedInt<LIMBS>) -> FixedInt<LIMBS> {
+                (&self).$method(&rhs)
+            }
+        }
+
+        impl<'b, const LIMBS: usize> $imp<&'b FixedInt<LIMBS>> for FixedInt<LIMBS> {
+            type Output = FixedInt<LIMBS>;
+            fn $method(self, rhs: &'b FixedInt<LIMBS>) -> FixedInt<LIMBS> {
+                (&self).$method(rhs)
+            }
+        }
+
+        impl<'a, const LIMBS: usize> $imp<FixedInt<LIMBS>> for &'a FixedInt<LIMBS> {
+            type Output = FixedInt<LIMBS>;
+            fn $method(self, rhs: FixedInt<LIMBS>) -> FixedInt<LIMBS> {
+                self.$method(&rhs)
+            }
+        }
+
+        impl<'a, 'b, const LIMBS: usize> $imp<&'b FixedInt<LIMBS>> for &'a FixedInt<LIMBS> {
+            type Output = FixedInt<LIMBS>;
+            fn $method(self, rhs: &'b FixedInt<LIMBS>) -> FixedInt<LIMBS> {
+                let ($a, $b) = (self, rhs);
+                $body
+            }
+        }
+    };
+}
+
+impl_binop!(Add, add, |a, b| a.add_signed(&b.magnitude, b.negative));
+impl_binop!(Sub, sub, |a, b| a.add_signed(&b.magnitude, !b.negative));
+impl_binop!(Mul, mul, |a, b| a.mul_signed(b));
+impl_binop!(Div, div, |a, b| a.div_rem(b).0);
+impl_binop!(Rem, rem, |a, b| a.div_rem(b).1);
+
+impl<const LIMBS: usize> Neg for FixedInt<LIMBS> {
+    type Output = FixedInt<LIMBS>;
+    fn neg(self) -> FixedInt<LIMBS> {
+        FixedInt::new(self.magnitude, !self.negative)
+    }
+}
+
+impl<const LIMBS: usize> Neg for &FixedInt<LIMBS> {
+    type Output = FixedInt<LIMBS>;
+    fn neg(self) -> FixedInt<LIMBS> {
+        FixedInt::new(self.magnitude, !self.negative)
+    }
+}
+
+impl<const LIMBS: usize> Shl<usize> for FixedInt<LIMBS> {
+    type Output = FixedInt<LIMBS>;
+    fn shl(self, bits: usize) -> FixedInt<LIMBS> {
+        &self << bits
+    }
+}
+
+impl<const LIMBS: usize> Shl<usize> for &FixedInt<LIMBS> {
+    type Output = FixedInt<LIMBS>;
+    fn shl(self, bits: usize) -> FixedInt<LIMBS> {
+        let magnitude = self.magnitude.shl_vartime(bits);
+        assert!(
+            bits < Uint::<LIMBS>::BITS && magnitude.shr_vartime(bits) == self.magnitude,
+            "{}",
+            OVERFLOW
+        );
+        FixedInt::new(magnitude, self.negative)
+    }
+}
+
+impl<const LIMBS: usize> Shr<usize> for FixedInt<LIMBS> {
+    type Output = FixedInt<LIMBS>;
+    fn shr(self, bits: usize) -> FixedInt<LIMBS> {
+        &self >> bits
+    }
+}
+
+impl<const LIMBS: usize> Shr<usize> for &FixedInt<LIMBS> {
+    type Output = FixedInt<LIMBS>;
+    fn shr(self, bits: usize) -> FixedInt<LIMBS> {
+        let magnitude = self.magnitude.shr_vartime(bits);
+        // negative numbers are rounded towards minus infinity, as by the other libraries
+        let truncated =
+            bits >= Uint::<LIMBS>::BITS || magnitude.shl_vartime(bits) != self.magnitude;
+        if self.negative && truncated {
+            FixedInt::new(magnitude.wrapping_add(&Uint::ONE), true)
+        } else {
+            FixedInt::new(magnitude, self.negative)
+        }
+    }
+}
+
+impl<const LIMBS: usize> Ord for FixedInt<LIMBS> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, false) => self.magnitude.cmp(&other.magnitude),
+            (true, true) => other.magnitude.cmp(&self.magnitude),
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+        }
+    }
+}
+
+impl<const LIMBS: usize> PartialOrd for FixedInt<LIMBS> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const LIMBS: usize> Zero for FixedInt<LIMBS> {
+    fn zero() -> Self {
+        FixedInt::new(Uint::ZERO, false)
+    }
+    fn is_zero(&self) -> bool {
+        self.magnitude == Uint::ZERO
+    }
+}
+
+impl<const LIMBS: usize> One for FixedInt<LIMBS> {
+    fn one() -> Self {
+        FixedInt::new(Uint::ONE, false)
+    }
+}
+
+macro_rules! impl_from {
+    ($($t:ty),*) => {
+        $(
+            impl<const LIMBS: usize> From<$t> for FixedInt<LIMBS> {
+                fn from(x: $t) -> FixedInt<LIMBS> {
+                    FixedInt::new(Uint::from_u64(x.unsigned_abs() as u64), x < 0)
+                }
+            }
+        )*
+    };
+}
+
+impl_from!(i32, i64);
+
+macro_rules! impl_from_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl<const LIMBS: usize> From<$t> for FixedInt<LIMBS> {
+                fn from(x: $t) -> FixedInt<LIMBS> {
+                    FixedInt::new(Uint::from_u64(x as u64), false)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_unsigned!(u32, u64, usize);
+
+// largest power of ten that fits in a limb on every platform
+const DECIMAL_CHUNK: u32 = 1_000_000_000;
+const DECIMAL_CHUNK_DIGITS: usize = 9;
+
+/// Error parsing a `FixedInt` from its decimal representation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseFixedIntError {
+    InvalidDigit,
+    Overflow,
+}
+
+impl fmt::Display for ParseFixedIntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseFixedIntError::InvalidDigit => write!(f, "invalid digit found in string"),
+            ParseFixedIntError::Overflow => write!(f, "number too large for fixed width"),
+        }
+    }
+}
+
+impl ::std::error::Error for ParseFixedIntError {}
+
+impl<const LIMBS: usize> FromStr for FixedInt<LIMBS> {
+    type Err = ParseFixedIntError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(ParseFixedIntError::InvalidDigit);
+        }
+        let mut magnitude = Uint::ZERO;
+        for chunk in digits.as_bytes().chunks(DECIMAL_CHUNK_DIGITS) {
+            let scale = 10u32.pow(chunk.len() as u32);
+            let value = chunk
+                .iter()
+                .fold(0, |acc, byte| acc * 10 + (byte - b'0') as u32);
+            magnitude = checked_mul(&magnitude, &Uint::from_u32(scale))
+                .and_then(|scaled| checked_add(&scaled, &Uint::from_u32(value)))
+                .ok_or(ParseFixedIntError::Overflow)?;
+        }
+        Ok(FixedInt::new(magnitude, negative))
+    }
+}
+
+impl<const LIMBS: usize> fmt::Display for FixedInt<LIMBS> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let chunk = NonZero::new(Limb(DECIMAL_CHUNK as Word)).unwrap();
+        let mut chunks = vec![];
+        let mut magnitude = self.magnitude;
+        loop {
+            let (quotient, remainder) = magnitude.div_rem_limb(chunk);
+            chunks.push(remainder.0);
+            magnitude = quotient;
+            if magnitude == Uint::ZERO {
+                break;
+            }
+        }
+        let mut digits = chunks.pop().unwrap().to_string();
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{:0width$}", chunk, width = DECIMAL_CHUNK_DIGITS));
+        }
+        f.pad_integral(!self.negative, "", &digits)
+    }
+}
+
+impl<const LIMBS: usize> fmt::Debug for FixedInt<LIMBS> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+pub type BigInteger = Fixed2048;
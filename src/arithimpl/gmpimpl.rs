@@ -4,6 +4,7 @@ extern crate gmp;
 
 use self::gmp::mpz::Mpz;
 use super::traits::*;
+use crate::Error;
 use rand::rngs::OsRng;
 use rand::RngCore;
 
@@ -19,6 +20,9 @@ impl Samplable for Mpz {
     }
 
     fn sample(bitsize: usize) -> Self {
+        if bitsize == 0 {
+            return Mpz::zero();
+        }
         let mut rng = OsRng::default();
         let bytes = (bitsize - 1) / 8 + 1;
         let mut buf: Vec<u8> = vec![0; bytes];
@@ -51,12 +55,33 @@ impl ModPow for Mpz {
     }
 }
 
+impl ModPowSec for Mpz {
+    fn modpow_sec(base: &Self, exponent: &Self, modulus: &Self) -> Self {
+        // mpz_powm_sec requires a positive exponent and an odd modulus; rather than falling back
+        // to the variable-time powm, only the zero exponent is handled here
+        assert!(
+            !NumberTests::is_negative(exponent) && !NumberTests::is_even(modulus),
+            "modpow_sec needs a non-negative exponent and an odd modulus"
+        );
+        if NumberTests::is_zero(exponent) {
+            return &Mpz::one() % modulus;
+        }
+        base.powm_sec(exponent, modulus)
+    }
+}
+
 impl ModInv for Mpz {
     fn modinv(a: &Self, modulus: &Self) -> Self {
         a.invert(modulus).unwrap()
     }
 }
 
+impl TryModInv for Mpz {
+    fn try_modinv(a: &Self, modulus: &Self) -> Result<Self, Error> {
+        a.invert(modulus).ok_or(Error::NotInvertible)
+    }
+}
+
 impl EGCD for Mpz {
     fn egcd(a: &Self, b: &Self) -> (Self, Self, Self) {
         a.gcdext(b)
@@ -70,6 +95,13 @@ impl ConvertFrom<Mpz> for u64 {
     }
 }
 
+impl TryConvertFrom<Mpz> for u64 {
+    fn _try_from(x: &Mpz) -> Result<u64, Error> {
+        let foo: Option<u64> = x.into();
+        foo.ok_or(Error::DecodeOverflow)
+    }
+}
+
 impl BitManipulation for Mpz {
     fn set_bit(self: &mut Self, bit: usize, bit_val: bool) {
         if bit_val {
@@ -81,3 +113,19 @@ impl BitManipulation for Mpz {
 }
 
 pub type BigInteger = Mpz;
+
+impl ToBytes for Mpz {
+    fn to_bytes(&self) -> Vec<u8> {
+        let ref magnitude = self.abs();
+        let bytes: Vec<u8> = magnitude.into();
+        // strip any leading zeros, including the single byte used for zero
+        let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+        bytes[start..].to_vec()
+    }
+}
+
+impl FromBytes for Mpz {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        Self::from(bytes)
+    }
+}
@@ -1,7 +1,10 @@
 //! Supported underlying libraries for arbitrary precision arithmetic.
 
+pub mod fixedimpl;
 pub mod gmpimpl;
+pub mod montgomery;
 pub mod numimpl;
 pub mod primes;
 pub mod rampimpl;
+pub mod rugimpl;
 pub mod traits;
@@ -0,0 +1,352 @@
+//! Montgomery multiplication and sliding-window exponentiation for the generic `ModPow`.
+//!
+//! Montgomery multiplication replaces the division after every multiplication by shifts and two
+//! further multiplications, for the price of a context computed once per modulus. Contexts are
+//! kept in a small per-thread cache, so that the repeated exponentiations modulo n and n² made
+//! by the schemes share them. Moduli that may be secret, such as the primes used for decryption
+//! and the candidates tested during key generation, are kept out of the cache by `uncached`.
+
+use super::traits::*;
+use num_traits::{One, Zero};
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::ops::{Add, Mul, Rem, Shl, Shr, Sub};
+use std::rc::Rc;
+
+// Number of contexts kept per thread, enough for the public moduli of a few keys; secret ones,
+// including every prime candidate tried during key generation, never enter the cache.
+const CACHE_SIZE: usize = 8;
+
+thread_local! {
+    static CONTEXTS: RefCell<Vec<Rc<dyn Any>>> = const { RefCell::new(Vec::new()) };
+    static BYPASS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Precomputed values for multiplication modulo an odd modulus n in Montgomery form, where x is
+/// represented by xR mod n for R a power of two above n.
+#[derive(Debug, Clone)]
+pub struct Montgomery<I> {
+    modulus: I,
+    bits: usize,  // R = 2^bits
+    inverse: I,   // -n^-1 mod R
+    one: I,       // R mod n
+    r_squared: I, // R^2 mod n
+}
+
+impl<I> Montgomery<I>
+where
+    I: Clone + PartialOrd,
+    I: Zero + One + NumberTests + ToBytes,
+    I: Shl<usize, Output = I> + Shr<usize, Output = I>,
+    I: Add<I, Output = I>,
+    I: Sub<I, Output = I>,
+    for<'b> I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+{
+    /// Context for the odd and positive `modulus`.
+    pub fn new(modulus: &I) -> Montgomery<I> {
+        assert!(
+            !NumberTests::is_even(modulus) && !NumberTests::is_negative(modulus),
+            "Montgomery multiplication needs an odd modulus"
+        );
+        // whole bytes, so that R is found without a bit length
+        let bits = modulus.to_bytes().len() * 8;
+
+        // n^-1 mod 2^k by Newton iteration, doubling k from 1, where the inverse is 1
+        let mut inverse = I::one();
+        let mut precision = 1;
+        while precision < bits {
+            precision = ::std::cmp::min(2 * precision, bits);
+            // x (2 - n x), with 2 - n x taken modulo 2^k to stay non-negative
+            let ref product = low_bits(&(modulus * &inverse), precision);
+            let ref correction = (I::one() << precision) + (I::one() + I::one()) - product;
+            inverse = low_bits(&(&inverse * correction), precision);
+        }
+        let inverse = low_bits(&((I::one() << bits) - inverse), bits);
+
+        let ref r = &(I::one() << bits) % modulus;
+        Montgomery {
+            modulus: modulus.clone(),
+            bits: bits,
+            inverse: inverse,
+            one: r.clone(),
+            r_squared: &(r * r) % modulus,
+        }
+    }
+
+    pub fn modulus(&self) -> &I {
+        &self.modulus
+    }
+
+    /// Montgomery form of `x`, which may be any integer.
+    pub fn to_montgomery(&self, x: &I) -> I {
+        let mut x = x % &self.modulus;
+        if NumberTests::is_negative(&x) {
+            x = x + self.modulus.clone();
+        }
+        self.reduce(&x * &self.r_squared)
+    }
+
+    /// The integer in `[0, n)` with Montgomery form `x`.
+    pub fn from_montgomery(&self, x: &I) -> I {
+        self.reduce(x.clone())
+    }
+
+    /// Montgomery form of one.
+    pub fn one(&self) -> I {
+        self.one.clone()
+    }
+
+    /// Product of numbers in Montgomery form.
+    pub fn mul(&self, a: &I, b: &I) -> I {
+        self.reduce(a * b)
+    }
+
+    /// `base^exponent mod n` for a non-negative `exponent`.
+    pub fn pow(&self, base: &I, exponent: &I) -> I {
+        let ref base = self.to_montgomery(base);
+        let power = window_pow(base, exponent, self.one(), |a, b| self.mul(a, b));
+        self.from_montgomery(&power)
+    }
+
+    // Montgomery reduction of `0 <= t < nR` to tR^-1 mod n
+    fn reduce(&self, t: I) -> I {
+        let ref m = low_bits(&(&low_bits(&t, self.bits) * &self.inverse), self.bits);
+        let reduced = (t + m * &self.modulus) >> self.bits;
+        if reduced >= self.modulus {
+            reduced - &self.modulus
+        } else {
+            reduced
+        }
+    }
+}
+
+/// Context for `modulus` from the cache of the current thread, created if missing.
+pub fn context<I>(modulus: &I) -> Rc<Montgomery<I>>
+where
+    I: 'static,
+    I: Clone + PartialOrd,
+    I: Zero + One + NumberTests + ToBytes,
+    I: Shl<usize, Output = I> + Shr<usize, Output = I>,
+    I: Add<I, Output = I>,
+    I: Sub<I, Output = I>,
+    for<'b> I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+{
+    if BYPASS.with(Cell::get) {
+        return Rc::new(Montgomery::new(modulus));
+    }
+    CONTEXTS.with(|contexts| {
+        let mut contexts = contexts.borrow_mut();
+        let position = contexts.iter().position(|context| {
+            context
+                .downcast_ref::<Montgomery<I>>()
+                .is_some_and(|context| &context.modulus == modulus)
+        });
+        // most recently used first
+        let context = match position {
+            Some(position) => contexts.remove(position),
+            None => Rc::new(Montgomery::new(modulus)),
+        };
+        contexts.insert(0, context.clone());
+        contexts.truncate(CACHE_SIZE);
+        context.downcast::<Montgomery<I>>().ok().unwrap()
+    })
+}
+
+// Moduli currently in the cache of this thread, most recently used first.
+#[cfg(test)]
+pub(crate) fn cached_moduli<I>() -> Vec<I>
+where
+    I: 'static + Clone,
+{
+    CONTEXTS.with(|contexts| {
+        contexts
+            .borrow()
+            .iter()
+            .filter_map(|context| context.downcast_ref::<Montgomery<I>>())
+            .map(|context| context.modulus.clone())
+            .collect()
+    })
+}
+
+/// Run `f` with contexts created afresh and dropped after use, neither looked up in nor added to
+/// the cache of the current thread.
+pub fn uncached<T, F>(f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    // restores the previous state even if `f` panics
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            BYPASS.with(|bypass| bypass.set(self.0));
+        }
+    }
+    let _restore = Restore(BYPASS.with(|bypass| bypass.replace(true)));
+    f()
+}
+
+/// `base^exponent` for a non-negative `exponent` by left-to-right sliding-window
+/// exponentiation, with `mul` the multiplication and `one` its identity.
+///
+/// Only odd powers of `base` up to the window size are precomputed, and windows start and end
+/// at set bits, which saves a multiplication on most windows compared with the k-ary method.
+pub fn window_pow<I, F>(base: &I, exponent: &I, one: I, mul: F) -> I
+where
+    I: Clone,
+    I: ToBytes,
+    F: Fn(&I, &I) -> I,
+{
+    let bytes = exponent.to_bytes();
+    let bits = bytes.len() * 8;
+    let bit = |i: usize| (bytes[bytes.len() - 1 - i / 8] >> (i % 8)) & 1 == 1;
+    let width = match bits {
+        0..=64 => 3,
+        65..=256 => 4,
+        257..=1024 => 5,
+        _ => 6,
+    };
+
+    // base, base^3, base^5, ..., base^(2^width - 1)
+    let ref square = mul(base, base);
+    let mut powers = vec![base.clone()];
+    for i in 1..1 << (width - 1) {
+        let next = mul(&powers[i - 1], square);
+        powers.push(next);
+    }
+
+    let mut result: Option<I> = None;
+    let mut i = bits;
+    while i > 0 {
+        if !bit(i - 1) {
+            result = result.map(|result| mul(&result, &result));
+            i -= 1;
+            continue;
+        }
+        // the longest window of at most `width` bits from bit i - 1 down to a set bit
+        let mut low = i.saturating_sub(width);
+        while !bit(low) {
+            low += 1;
+        }
+        let value = (low..i)
+            .rev()
+            .fold(0, |value, j| (value << 1) | bit(j) as usize);
+        result = Some(match result {
+            None => powers[value >> 1].clone(),
+            Some(mut result) => {
+                for _ in low..i {
+                    result = mul(&result, &result);
+                }
+                mul(&result, &powers[value >> 1])
+            }
+        });
+        i = low;
+    }
+    result.unwrap_or(one)
+}
+
+// x mod 2^bits for non-negative x
+fn low_bits<I>(x: &I, bits: usize) -> I
+where
+    I: Clone,
+    I: Shl<usize, Output = I> + Shr<usize, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+{
+    x - &((x.clone() >> bits) << bits)
+}
+
+bigint!(
+    I,
+    #[cfg(test)]
+    mod tests {
+
+        use super::I;
+        use crate::arithimpl::montgomery::*;
+        use num_traits::One;
+        use std::rc::Rc;
+
+        fn reference_pow(base: &I, exponent: &I, modulus: &I) -> I {
+            let mut result = I::one();
+            let mut exponent = exponent.clone();
+            while !NumberTests::is_zero(&exponent) {
+                result = (&result * base) % modulus;
+                exponent = &exponent - &I::one();
+            }
+            result
+        }
+
+        #[test]
+        fn test_window_pow() {
+            let ref modulus = I::from(1000003);
+            let ref base = I::from(12345);
+            for e in (0..300).chain(vec![1023, 1024, 65535, 65537]) {
+                let ref exponent = I::from(e as u64);
+                let power = window_pow(base, exponent, I::one(), |a, b| (a * b) % modulus);
+                assert_eq!(power, reference_pow(base, exponent, modulus));
+            }
+        }
+
+        #[test]
+        fn test_montgomery() {
+            let ref modulus: I = str::parse("148677972634832330983979593310074301486537017973460461278300587514468301043894574906886127642530475786889672304776052879927627556769456140664043088700743909632312483413393134504352834240399191134336344285483935856491230340093391784574980688823380828143810804684752914935441384845195613674104960646037368551517").unwrap();
+            let ref a: I = str::parse("98765432109876543210987654321").unwrap();
+            let ref b = -I::from(123456789);
+            let context = Montgomery::new(modulus);
+
+            let product = context.mul(&context.to_montgomery(a), &context.to_montgomery(b));
+            assert_eq!(
+                context.from_montgomery(&product),
+                (a * b % modulus + modulus) % modulus
+            );
+            assert_eq!(context.from_montgomery(&context.one()), I::one());
+
+            // Fermat's little theorem for the prime modulus
+            let ref exponent = modulus - &I::one();
+            assert_eq!(context.pow(a, exponent), I::one());
+            assert_eq!(context.pow(a, &I::from(0)), I::one());
+            assert_eq!(context.pow(a, &I::from(1)), a % modulus);
+            assert_eq!(
+                context.pow(a, &I::from(3)),
+                reference_pow(a, &I::from(3), modulus)
+            );
+        }
+
+        #[test]
+        fn test_context_cache() {
+            let ref n = I::from(1000003);
+            let ref m = I::from(1000033);
+            let cached = context(n);
+            assert!(Rc::ptr_eq(&cached, &context(n)));
+            assert!(!Rc::ptr_eq(&cached, &context(m)));
+            assert_eq!(cached.modulus(), n);
+
+            // contexts created without the cache are not shared
+            let ref p = I::from(1000037);
+            let secret = uncached(|| context(p));
+            assert_eq!(Rc::strong_count(&secret), 1);
+            assert!(!Rc::ptr_eq(&cached, &uncached(|| context(n))));
+            assert!(Rc::ptr_eq(&cached, &context(n)));
+        }
+
+        #[test]
+        fn test_modpow() {
+            // odd moduli go through Montgomery multiplication, even ones do not
+            for modulus in [I::from(1000003), I::from(1000000)] {
+                let ref modulus = modulus;
+                let ref base = I::from(987654);
+                let ref exponent = I::from(4321);
+                assert_eq!(
+                    I::modpow(base, exponent, modulus),
+                    reference_pow(base, exponent, modulus)
+                );
+            }
+        }
+    }
+);
@@ -7,6 +7,7 @@ use rand;
 // use self::num;
 
 use super::traits::*;
+use crate::Error;
 
 impl Samplable for num::bigint::BigInt {
     fn sample_below(upper: &Self) -> Self {
@@ -58,6 +59,12 @@ impl ConvertFrom<num::bigint::BigInt> for u64 {
     }
 }
 
+impl TryConvertFrom<num::bigint::BigInt> for u64 {
+    fn _try_from(x: &num::bigint::BigInt) -> Result<u64, Error> {
+        x.to_u64().ok_or(Error::DecodeOverflow)
+    }
+}
+
 impl BitManipulation for num::bigint::BigInt {
     fn set_bit(self: &mut Self, bit: usize, bit_val: bool) {
         self.set_bit(bit as u64, bit_val);
@@ -65,3 +72,18 @@ impl BitManipulation for num::bigint::BigInt {
 }
 
 pub type BigInteger = num::bigint::BigInt;
+
+impl ToBytes for num::bigint::BigInt {
+    fn to_bytes(&self) -> Vec<u8> {
+        if Zero::is_zero(self) {
+            return vec![];
+        }
+        self.magnitude().to_bytes_be()
+    }
+}
+
+impl FromBytes for num::bigint::BigInt {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        Self::from_bytes_be(num::bigint::Sign::Plus, bytes)
+    }
+}
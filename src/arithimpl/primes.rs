@@ -1,3 +1,4 @@
+use crate::arithimpl::montgomery;
 use crate::arithimpl::traits::*;
 use num_traits::{One, Zero};
 use std::marker::Sized;
@@ -9,6 +10,7 @@ pub trait PrimeSampable {
 
 impl<I> PrimeSampable for I
 where
+    I: ModPow + ModPowSec,
     I: Samplable,
     I: BitManipulation,
     I: Clone + Sized,
@@ -36,7 +38,8 @@ where
         let one = I::one();
         let two = &one + &one;
 
-        loop {
+        // the candidates, the last of which is returned, must not reach the Montgomery cache
+        montgomery::uncached(|| loop {
             let mut candidate = Self::sample(bitsize);
             // We flip the LSB to make sure tue candidate is odd.
             candidate.set_bit(0, true);
@@ -53,7 +56,7 @@ where
                 }
                 candidate = candidate + &two;
             }
-        }
+        })
     }
 }
 
@@ -208,6 +211,7 @@ static SMALL_PRIMES: [u32; 2048] = [
 // 3. Run five rounds of the Miller-Rabin test on the candidate.
 pub fn is_prime<I>(candidate: &I) -> bool
 where
+    I: ModPow + ModPowSec,
     I: Clone + Sized,
     I: Samplable,
     I: Eq,
@@ -227,14 +231,8 @@ where
     I: BitManipulation,
 {
     // First, simple trial divide
-    for p in SMALL_PRIMES.iter() {
-        let prime = I::from(*p);
-        let r = candidate % &prime;
-        if !NumberTests::is_zero(&r) {
-            continue;
-        } else {
-            return false;
-        }
+    if has_small_factor(candidate) {
+        return false;
     }
     // Second, do a little Fermat test on the candidate
     if !fermat(candidate) {
@@ -249,9 +247,66 @@ where
     true
 }
 
+/// Trial divide `candidate` by the first 2048 primes.
+pub fn has_small_factor<I>(candidate: &I) -> bool
+where
+    I: From<u32>,
+    I: NumberTests,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+{
+    SMALL_PRIMES
+        .iter()
+        .any(|p| NumberTests::is_zero(&(candidate % &I::from(*p))))
+}
+
+/// Jacobi symbol `(a/n)` for odd positive `n`.
+pub fn jacobi<I>(a: &I, n: &I) -> i8
+where
+    I: Clone,
+    I: From<u32>,
+    I: Zero + One + NumberTests,
+    I: PartialEq,
+    I: Shr<usize, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+{
+    let ref three = I::from(3);
+    let ref four = I::from(4);
+    let ref five = I::from(5);
+    let ref eight = I::from(8);
+
+    let mut a = a % n;
+    if NumberTests::is_negative(&a) {
+        a = &a + n;
+    }
+    let mut n = n.clone();
+    let mut result = 1;
+    while !NumberTests::is_zero(&a) {
+        while NumberTests::is_even(&a) {
+            a = a >> 1;
+            let r = &n % eight;
+            if &r == three || &r == five {
+                result = -result;
+            }
+        }
+        // quadratic reciprocity
+        ::std::mem::swap(&mut a, &mut n);
+        if &(&a % four) == three && &(&n % four) == three {
+            result = -result;
+        }
+        a = &a % &n;
+    }
+    if n == I::one() {
+        result
+    } else {
+        0
+    }
+}
+
 fn fermat<I>(candidate: &I) -> bool
 where
     I: ModPow,
+    I: ModPowSec,
     I: Clone + Sized,
     I: Samplable,
     I: Eq,
@@ -270,7 +325,7 @@ where
     // Perform Fermat's little theorem
     // This might be perform more than once. Handbook of Applied Cryptography [Algorithm 4.9 p136]
     let random = I::sample_below(candidate);
-    let result = I::modpow(&random, &(candidate - &I::one()), candidate);
+    let result = modpow_secret(&random, &(candidate - &I::one()), candidate);
 
     result == I::one()
 }
@@ -282,6 +337,7 @@ where
 fn miller_rabin<I>(candidate: &I, limit: usize) -> bool
 where
     I: ModPow,
+    I: ModPowSec,
     I: Clone + Sized,
     I: Samplable,
     I: Eq,
@@ -305,14 +361,15 @@ where
 
     for _ in 0..limit {
         let basis = I::sample_range(&two, &(candidate - &two));
-        let mut y = I::modpow(&basis, &d, candidate);
+        // the candidate becomes a secret prime during key generation
+        let mut y = modpow_secret(&basis, &d, candidate);
 
         if y == one || y == (candidate - &one) {
             continue;
         } else {
             let mut counter = I::one();
             while counter < (&s - &one) {
-                y = I::modpow(&y, &two, candidate);
+                y = (&y * &y) % candidate;
                 if y == one {
                     return false;
                 } else if y == candidate - &one {
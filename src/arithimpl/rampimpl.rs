@@ -1,6 +1,7 @@
 #![cfg(feature = "inclramp")]
 extern crate ramp;
 use super::traits::*;
+use crate::Error;
 use rand::rngs::OsRng;
 
 impl Samplable for ramp::Int {
@@ -12,6 +13,9 @@ impl Samplable for ramp::Int {
 
     fn sample(bitsize: usize) -> Self {
         use self::ramp::RandomInt;
+        if bitsize == 0 {
+            return ramp::Int::from(0);
+        }
         let mut rng = OsRng::default();
         rng.gen_uint(bitsize)
     }
@@ -71,6 +75,15 @@ impl ConvertFrom<ramp::Int> for u64 {
     }
 }
 
+impl TryConvertFrom<ramp::Int> for u64 {
+    fn _try_from(x: &ramp::Int) -> Result<u64, Error> {
+        if x < &0 || x.bit_length() > 64 {
+            return Err(Error::DecodeOverflow);
+        }
+        Ok(u64::from(x))
+    }
+}
+
 impl BitManipulation for ramp::Int {
     fn set_bit(self: &mut Self, bit: usize, bit_val: bool) {
         self.set_bit(bit as u32, bit_val);
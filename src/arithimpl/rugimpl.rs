@@ -0,0 +1,273 @@
+#![cfg(feature = "inclrug")]
+
+extern crate rug;
+
+use self::rug::integer::{Order, ParseIntegerError};
+use self::rug::Integer;
+use super::traits::*;
+use crate::Error;
+use num_traits::{One, Zero};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Rem, Shl, Shr, Sub};
+use std::str::FromStr;
+
+/// Integer backed by `rug::Integer`.
+///
+/// Arithmetic on references to `rug::Integer` yields incomplete computations rather than
+/// integers, so the wrapper evaluates these to satisfy the operator bounds used by the schemes.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RugInteger(pub Integer);
+
+impl Samplable for RugInteger {
+    fn sample_below(upper: &Self) -> Self {
+        let bits = upper.0.significant_bits() as usize;
+        loop {
+            let n = Self::sample(bits);
+            if n < *upper {
+                return n;
+            }
+        }
+    }
+
+    fn sample(bitsize: usize) -> Self {
+        if bitsize == 0 {
+            return Self::zero();
+        }
+        let mut rng = OsRng::default();
+        let bytes = (bitsize - 1) / 8 + 1;
+        let mut buf: Vec<u8> = vec![0; bytes];
+        rng.fill_bytes(&mut buf);
+        Self::from_bytes(&buf) >> (bytes * 8 - bitsize)
+    }
+
+    fn sample_range(lower: &Self, upper: &Self) -> Self {
+        lower + &Self::sample_below(&(upper - lower))
+    }
+}
+
+impl NumberTests for RugInteger {
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+    fn is_even(&self) -> bool {
+        self.0.is_even()
+    }
+    fn is_negative(&self) -> bool {
+        self.0 < 0
+    }
+}
+
+impl ModPow for RugInteger {
+    fn modpow(base: &Self, exponent: &Self, modulus: &Self) -> Self {
+        RugInteger(Integer::from(
+            base.0.pow_mod_ref(&exponent.0, &modulus.0).unwrap(),
+        ))
+    }
+}
+
+impl ModPowSec for RugInteger {
+    fn modpow_sec(base: &Self, exponent: &Self, modulus: &Self) -> Self {
+        // mpz_powm_sec requires a positive exponent and an odd modulus; rather than falling back
+        // to the variable-time pow_mod, only the zero exponent is handled here
+        assert!(
+            exponent.0 >= 0 && modulus.0.is_odd(),
+            "modpow_sec needs a non-negative exponent and an odd modulus"
+        );
+        if exponent.0 == 0 {
+            return RugInteger(Integer::from(1) % &modulus.0);
+        }
+        RugInteger(Integer::from(
+            base.0.secure_pow_mod_ref(&exponent.0, &modulus.0),
+        ))
+    }
+}
+
+impl ModInv for RugInteger {
+    fn modinv(a: &Self, modulus: &Self) -> Self {
+        Self::try_modinv(a, modulus).unwrap()
+    }
+}
+
+impl TryModInv for RugInteger {
+    fn try_modinv(a: &Self, modulus: &Self) -> Result<Self, Error> {
+        a.0.invert_ref(&modulus.0)
+            .map(|inverse| RugInteger(Integer::from(inverse)))
+            .ok_or(Error::NotInvertible)
+    }
+}
+
+impl EGCD for RugInteger {
+    fn egcd(a: &Self, b: &Self) -> (Self, Self, Self) {
+        let (d, s, t) = <(Integer, Integer, Integer)>::from(a.0.gcd_cofactors_ref(&b.0));
+        (RugInteger(d), RugInteger(s), RugInteger(t))
+    }
+}
+
+impl ConvertFrom<RugInteger> for u64 {
+    fn _from(x: &RugInteger) -> u64 {
+        x.0.to_u64().unwrap()
+    }
+}
+
+impl TryConvertFrom<RugInteger> for u64 {
+    fn _try_from(x: &RugInteger) -> Result<u64, Error> {
+        x.0.to_u64().ok_or(Error::DecodeOverflow)
+    }
+}
+
+impl BitManipulation for RugInteger {
+    fn set_bit(self: &mut Self, bit: usize, bit_val: bool) {
+        self.0.set_bit(bit as u32, bit_val);
+    }
+}
+
+impl ToBytes for RugInteger {
+    fn to_bytes(&self) -> Vec<u8> {
+        // digits are those of the absolute value, with none for zero
+        self.0.to_digits::<u8>(Order::Msf)
+    }
+}
+
+impl FromBytes for RugInteger {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        RugInteger(Integer::from_digits::<u8>(bytes, Order::Msf))
+    }
+}
+
+macro_rules! impl_binop {
+    ($imp:ident, $method:ident, $op:tt) => {
+        impl $imp<RugInteger> for RugInteger {
+            type Output = RugInteger;
+            fn $method(self, rhs: RugInteger) -> RugInteger {
+                RugInteger(self.0 $op rhs.0)
+            }
+        }
+
+        impl<'b> $imp<&'b RugInteger> for RugInteger {
+            type Output = RugInteger;
+            fn $method(self, rhs: &'b RugInteger) -> RugInteger {
+                RugInteger(self.0 $op &rhs.0)
+            }
+        }
+
+        impl<'a> $imp<RugInteger> for &'a RugInteger {
+            type Output = RugInteger;
+            fn $method(self, rhs: RugInteger) -> RugInteger {
+                RugInteger(&self.0 $op rhs.0)
+            }
+        }
+
+        impl<'a, 'b> $imp<&'b RugInteger> for &'a RugInteger {
+            type Output = RugInteger;
+            fn $method(self, rhs: &'b RugInteger) -> RugInteger {
+                RugInteger(Integer::from(&self.0 $op &rhs.0))
+            }
+        }
+    };
+}
+
+impl_binop!(Add, add, +);
+impl_binop!(Sub, sub, -);
+impl_binop!(Mul, mul, *);
+impl_binop!(Div, div, /);
+impl_binop!(Rem, rem, %);
+
+impl Neg for RugInteger {
+    type Output = RugInteger;
+    fn neg(self) -> RugInteger {
+        RugInteger(-self.0)
+    }
+}
+
+impl<'a> Neg for &'a RugInteger {
+    type Output = RugInteger;
+    fn neg(self) -> RugInteger {
+        RugInteger(Integer::from(-&self.0))
+    }
+}
+
+impl Shl<usize> for RugInteger {
+    type Output = RugInteger;
+    fn shl(self, bits: usize) -> RugInteger {
+        RugInteger(self.0 << bits as u32)
+    }
+}
+
+impl<'a> Shl<usize> for &'a RugInteger {
+    type Output = RugInteger;
+    fn shl(self, bits: usize) -> RugInteger {
+        RugInteger(Integer::from(&self.0 << bits as u32))
+    }
+}
+
+impl Shr<usize> for RugInteger {
+    type Output = RugInteger;
+    fn shr(self, bits: usize) -> RugInteger {
+        RugInteger(self.0 >> bits as u32)
+    }
+}
+
+impl<'a> Shr<usize> for &'a RugInteger {
+    type Output = RugInteger;
+    fn shr(self, bits: usize) -> RugInteger {
+        RugInteger(Integer::from(&self.0 >> bits as u32))
+    }
+}
+
+impl Zero for RugInteger {
+    fn zero() -> Self {
+        RugInteger(Integer::new())
+    }
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl One for RugInteger {
+    fn one() -> Self {
+        RugInteger(Integer::from(1))
+    }
+}
+
+macro_rules! impl_from {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for RugInteger {
+                fn from(x: $t) -> RugInteger {
+                    RugInteger(Integer::from(x))
+                }
+            }
+        )*
+    };
+}
+
+impl_from!(i32, i64, u32, u64, usize);
+
+impl From<Integer> for RugInteger {
+    fn from(x: Integer) -> RugInteger {
+        RugInteger(x)
+    }
+}
+
+impl FromStr for RugInteger {
+    type Err = ParseIntegerError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Integer::from_str(s).map(RugInteger)
+    }
+}
+
+impl fmt::Display for RugInteger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Debug for RugInteger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+pub type BigInteger = RugInteger;
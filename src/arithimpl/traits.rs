@@ -1,4 +1,7 @@
+use crate::Error;
+use num_traits::One;
 use std::marker::Sized;
+use std::ops::Neg;
 
 pub trait NumberTests {
     fn is_zero(&self) -> bool;
@@ -10,6 +13,17 @@ pub trait ModPow {
     fn modpow(base: &Self, exponent: &Self, modulus: &Self) -> Self;
 }
 
+/// Modular exponentiation for secret exponents.
+///
+/// Implementations do not branch on the bits of the exponent, but only those on `mpz_powm_sec`
+/// and crypto-bigint are constant-time. The generic Montgomery ladder merely performs the same
+/// sequence of operations for every bit: its swaps multiply by a bit of zero or one and its
+/// reductions run in the variable time of the underlying library, both of which may still leak.
+/// The exponent must not be negative, except with the crypto-bigint backend.
+pub trait ModPowSec {
+    fn modpow_sec(base: &Self, exponent: &Self, modulus: &Self) -> Self;
+}
+
 pub trait EGCD
 where
     Self: Sized,
@@ -17,10 +31,31 @@ where
     fn egcd(a: &Self, b: &Self) -> (Self, Self, Self);
 }
 
-pub trait ModInv {
+pub trait ModInv
+where
+    Self: Sized,
+{
     fn modinv(a: &Self, prime: &Self) -> Self;
 }
 
+/// Modular inversion reporting elements without an inverse.
+pub trait TryModInv
+where
+    Self: ModInv + EGCD + One + PartialEq + Neg<Output = Self>,
+{
+    /// Inverse of `a` modulo `modulus`, or `NotInvertible` if there is none.
+    ///
+    /// By default the gcd is checked with `EGCD` before the inverse is computed by `modinv`.
+    fn try_modinv(a: &Self, modulus: &Self) -> Result<Self, Error> {
+        // the gcd is only determined up to its sign
+        let (d, _, _) = Self::egcd(a, modulus);
+        if d != Self::one() && d != -Self::one() {
+            return Err(Error::NotInvertible);
+        }
+        Ok(Self::modinv(a, modulus))
+    }
+}
+
 pub trait Samplable {
     fn sample_below(upper: &Self) -> Self;
     fn sample_range(lower: &Self, upper: &Self) -> Self;
@@ -35,14 +70,62 @@ pub trait ConvertFrom<T> {
     fn _from(_: &T) -> Self;
 }
 
-use num_traits::{One, Zero};
-use std::ops::{Add, Div, Mul, Neg, Rem, Shr, Sub};
+pub trait TryConvertFrom<T>
+where
+    Self: Sized,
+{
+    fn _try_from(_: &T) -> Result<Self, Error>;
+}
+
+/// Big-endian bytes of the absolute value, without leading zeros.
+///
+/// The encoding is the same for all arithmetic libraries, with zero encoded as no bytes.
+pub trait ToBytes {
+    fn to_bytes(&self) -> Vec<u8>;
+}
+
+/// Non-negative number from its big-endian bytes.
+pub trait FromBytes {
+    fn from_bytes(bytes: &[u8]) -> Self;
+}
+
+use super::montgomery::{self, window_pow};
+use num_traits::Zero;
+use std::ops::{Add, Div, Mul, Rem, Shl, Shr, Sub};
 
 impl<I> ModPow for I
 where
     // TODO clean up
+    I: 'static,
+    I: Clone + Sized + PartialOrd + ToBytes,
+    I: Zero + One + Neg<Output = I> + NumberTests,
+    for<'a> &'a I: Mul<I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Div<&'b I, Output = I>,
+    for<'a> I: Rem<&'a I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    I: Add<I, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    I: Sub<I, Output = I>,
+    for<'b> I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    I: Shl<usize, Output = I> + Shr<usize, Output = I>,
+{
+    default fn modpow(base: &Self, exponent: &Self, modulus: &Self) -> Self {
+        if NumberTests::is_even(modulus) {
+            // Montgomery multiplication needs an odd modulus
+            let ref base = base % modulus;
+            return window_pow(base, exponent, Self::one(), |a, b| (a * b) % modulus);
+        }
+        montgomery::context(modulus).pow(base, exponent)
+    }
+}
+
+impl<I> ModPowSec for I
+where
     I: Clone + Sized,
     I: Zero + One + Neg<Output = I> + NumberTests,
+    I: From<u64> + ToBytes,
     for<'a> &'a I: Mul<I, Output = I>,
     for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
     for<'a, 'b> &'a I: Div<&'b I, Output = I>,
@@ -54,19 +137,62 @@ where
     for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
     I: Shr<usize, Output = I>,
 {
-    default fn modpow(base: &Self, exponent: &Self, modulus: &Self) -> Self {
-        let mut base = base.clone();
-        let mut exponent = exponent.clone();
-        let mut result = Self::one();
+    default fn modpow_sec(base: &Self, exponent: &Self, modulus: &Self) -> Self {
+        // the bytes below drop the sign, which would silently compute the power of |exponent|
+        assert!(
+            !NumberTests::is_negative(exponent),
+            "modpow_sec needs a non-negative exponent"
+        );
+        // read the exponent bits from its bytes, padded to the length of the modulus so that
+        // the number of steps does not reveal the length of the exponent
+        let exponent = exponent.to_bytes();
+        let width = ::std::cmp::max(exponent.len(), modulus.to_bytes().len());
+        let mut bytes = vec![0_u8; width - exponent.len()];
+        bytes.extend(exponent);
 
-        while !NumberTests::is_zero(&exponent) {
-            if !NumberTests::is_even(&exponent) {
-                result = (&result * &base) % modulus;
+        // Montgomery ladder: one multiplication and one squaring for every bit
+        let mut r0 = Self::one() % modulus;
+        let mut r1 = base % modulus;
+        for byte in bytes {
+            for i in (0..8).rev() {
+                let ref bit = Self::from(u64::from((byte >> i) & 1));
+                cswap(bit, &mut r0, &mut r1);
+                r1 = (&r0 * &r1) % modulus;
+                r0 = (&r0 * &r0) % modulus;
+                cswap(bit, &mut r0, &mut r1);
             }
-            base = (&base * &base) % modulus; // waste one of these by having it here but code is simpler (tiny bit)
-            exponent = exponent >> 1;
         }
-        result
+        r0
+    }
+}
+
+// Swap `a` and `b` if `bit` is one and leave them if it is zero, computing the same arithmetic
+// in both cases; the underlying libraries offer no constant-time select, and backends with a
+// dedicated routine override `modpow_sec` instead
+fn cswap<I>(bit: &I, a: &mut I, b: &mut I)
+where
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+{
+    let ref delta = &(&*b - &*a) * bit;
+    *a = &*a + delta;
+    *b = &*b - delta;
+}
+
+/// Modular exponentiation with an exponent that must be kept secret.
+///
+/// With the `hardened` feature this uses `ModPowSec`, and otherwise falls back to the faster
+/// `ModPow`. Either way the modulus, which may be secret too, is kept out of the cache of
+/// Montgomery contexts.
+pub fn modpow_secret<I>(base: &I, exponent: &I, modulus: &I) -> I
+where
+    I: ModPow + ModPowSec,
+{
+    if cfg!(feature = "hardened") {
+        I::modpow_sec(base, exponent, modulus)
+    } else {
+        montgomery::uncached(|| I::modpow(base, exponent, modulus))
     }
 }
 
@@ -129,3 +255,73 @@ where
         (prime + d) % prime
     }
 }
+
+impl<I> TryModInv for I
+where
+    I: EGCD,
+    I: Clone + Sized + PartialEq,
+    I: Zero + One + Neg<Output = I> + NumberTests,
+    for<'a> &'a I: Mul<I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Div<&'b I, Output = I>,
+    for<'a> I: Rem<&'a I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    I: Sub<I, Output = I>,
+    for<'b> I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    I: Shr<usize, Output = I>,
+{
+    default fn try_modinv(a: &Self, modulus: &Self) -> Result<Self, Error> {
+        let mut r = a % modulus;
+        if NumberTests::is_negative(&r) {
+            r = &r + modulus;
+        }
+        let (d, _, t) = Self::egcd(modulus, &r);
+        // an inverse exists only when the gcd is one
+        if !NumberTests::is_zero(&(d - Self::one())) {
+            return Err(Error::NotInvertible);
+        }
+        Ok((modulus + &t) % modulus)
+    }
+}
+
+impl<I> ToBytes for I
+where
+    I: Clone,
+    I: Neg<Output = I> + NumberTests,
+    I: From<u64>,
+    u64: ConvertFrom<I>,
+    I: Shr<usize, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+{
+    default fn to_bytes(&self) -> Vec<u8> {
+        let mut x = if NumberTests::is_negative(self) {
+            -self.clone()
+        } else {
+            self.clone()
+        };
+        let ref base = I::from(256);
+        let mut bytes = vec![];
+        while !NumberTests::is_zero(&x) {
+            bytes.push(u64::_from(&(&x % base)) as u8);
+            x = x >> 8;
+        }
+        bytes.reverse();
+        bytes
+    }
+}
+
+impl<I> FromBytes for I
+where
+    I: From<u64>,
+    I: Add<I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+{
+    default fn from_bytes(bytes: &[u8]) -> Self {
+        let ref base = I::from(256);
+        bytes
+            .iter()
+            .fold(I::from(0), |acc, byte| acc * base + I::from(*byte as u64))
+    }
+}
@@ -0,0 +1,101 @@
+//! Parallel batch operations (requires the `parallel` feature).
+//!
+//! Work is spread across the global `rayon` thread pool; results are always returned in the
+//! same order as the inputs.
+
+use crate::traits::*;
+
+use rayon::prelude::*;
+
+impl<EK, PT, CT, S> BatchEncryption<EK, PT, CT> for S
+where
+    S: Encryption<EK, PT, CT>,
+    EK: Sync,
+    PT: Sync,
+    CT: Send,
+{
+    fn encrypt_batch(ek: &EK, ms: &[PT]) -> Vec<CT> {
+        ms.par_iter().map(|m| S::encrypt(ek, m)).collect()
+    }
+}
+
+impl<DK, CT, PT, S> BatchDecryption<DK, CT, PT> for S
+where
+    S: Decryption<DK, CT, PT>,
+    DK: Sync,
+    CT: Sync,
+    PT: Send,
+{
+    fn decrypt_batch(dk: &DK, cs: &[CT]) -> Vec<PT> {
+        cs.par_iter().map(|c| S::decrypt(dk, c)).collect()
+    }
+}
+
+impl<EK, CT, S> BatchAddition<EK, CT> for S
+where
+    S: Addition<EK, CT, CT, CT>,
+    EK: Sync,
+    CT: Clone + Send + Sync,
+{
+    fn add_batch(ek: &EK, c1s: &[CT], c2s: &[CT]) -> Vec<CT> {
+        assert_eq!(c1s.len(), c2s.len(), "batches must be of equal length");
+        c1s.par_iter()
+            .zip(c2s.par_iter())
+            .map(|(c1, c2)| S::add(ek, c1, c2))
+            .collect()
+    }
+
+    fn sum_batch(ek: &EK, cs: &[CT]) -> Option<CT> {
+        cs.par_iter()
+            .cloned()
+            .reduce_with(|c1, c2| S::add(ek, &c1, &c2))
+    }
+}
+
+bigint!(
+    I,
+    #[cfg(test)]
+    mod tests {
+
+        use super::I;
+        use crate::core::*;
+        use crate::integral::{scalar, Code};
+        use crate::*;
+
+        fn test_keypair() -> Keypair<I> {
+            let p = str::parse("148677972634832330983979593310074301486537017973460461278300587514468301043894574906886127642530475786889672304776052879927627556769456140664043088700743909632312483413393134504352834240399191134336344285483935856491230340093391784574980688823380828143810804684752914935441384845195613674104960646037368551517").unwrap();
+            let q = str::parse("158741574437007245654463598139927898730476924736461654463975966787719309357536545869203069369466212089132653564188443272208127277664424448947476335413293018778018615899291704693105620242763173357203898195318179150836424196645745308205164116144020613415407736216097185962171301808761138424668335445923774195463").unwrap();
+            Keypair { p: p, q: q }
+        }
+
+        #[test]
+        fn test_batch_encryption_decryption() {
+            let (ek, dk) = test_keypair().keys();
+            let code = Code::default();
+            let eek = ek.with_code(&code);
+            let ddk = dk.with_code(&code);
+
+            let ms: Vec<u64> = (0..20).collect();
+            let cs: Vec<scalar::Ciphertext<I, u64>> = AbstractPaillier::encrypt_batch(&eek, &ms);
+            let recovered_ms: Vec<u64> = AbstractPaillier::decrypt_batch(&ddk, &cs);
+            assert_eq!(recovered_ms, ms);
+        }
+
+        #[test]
+        fn test_batch_addition() {
+            let (ek, dk) = test_keypair().keys();
+
+            let ms: Vec<Plaintext<I>> = (0..20_u64).map(Plaintext::from).collect();
+            let cs: Vec<Ciphertext<I>> = AbstractPaillier::encrypt_batch(&ek, &ms);
+
+            let doubled = AbstractPaillier::add_batch(&ek, &cs, &cs);
+            let recovered_ms: Vec<Plaintext<I>> = AbstractPaillier::decrypt_batch(&dk, &doubled);
+            let expected: Vec<Plaintext<I>> = (0..20_u64).map(|m| Plaintext::from(2 * m)).collect();
+            assert_eq!(recovered_ms, expected);
+
+            let sum = AbstractPaillier::sum_batch(&ek, &cs).unwrap();
+            assert_eq!(AbstractPaillier::decrypt(&dk, &sum), Plaintext::from(190));
+            assert!(AbstractPaillier::sum_batch(&ek, &cs[..0]).is_none());
+        }
+    }
+);
@@ -0,0 +1,735 @@
+//! Floating-point code for `f64` values, similar to `EncodedNumber` in python-paillier.
+//!
+//! A value is represented by a mantissa in `Z_n` together with a public exponent, such that the
+//! encoded value is `mantissa * BASE^exponent`. As for signed integral scalars, `Z_n` is split in
+//! two halves with the upper half representing negative mantissas.
+//!
+//! Homomorphic operations align exponents automatically: before adding, the operand with the
+//! larger exponent is multiplied by a suitable power of `BASE`. Since this grows the mantissa,
+//! adding values of very different magnitude may exceed the plaintext space and silently wrap
+//! modulo n. Every number therefore carries a bound on the bit length of its mantissa, which
+//! `try_add` and `try_mul` under the encryption key check against n before operating; the
+//! infallible operations do not check. The bound of a plaintext is checked against its mantissa
+//! there too, whereas that of a ciphertext received from elsewhere can only be trusted.
+//!
+//! Decoding rounds to the nearest `f64` when the value is not exactly representable, while
+//! `try_decode` reports `PrecisionLoss` instead.
+
+use super::*;
+
+use crate::arithimpl::traits::{ToBytes, TryConvertFrom};
+use num_traits::One;
+use std::ops::{Add, Rem, Shl, Shr, Sub};
+
+#[cfg(feature = "serde")]
+use crate::serialize::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "serde")]
+use std::fmt::Display;
+#[cfg(feature = "serde")]
+use std::str::FromStr;
+
+/// Base in which exponents are expressed.
+pub const BASE: u64 = 16;
+
+const LOG2_BASE: i32 = 4;
+
+/// Floating-point code for plaintext space `Z_n`.
+pub struct Code<I> {
+    /// Modulus `n` of the plaintext space.
+    pub modulus: I,
+}
+
+/// Representation of unencrypted floating-point number.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncodedNumber<I> {
+    pub data: core::Plaintext<I>,
+    pub exponent: i32,
+    /// Upper bound on the bit length of the absolute value of the mantissa.
+    pub mantissa_bits: usize,
+}
+
+/// Representation of encrypted floating-point number.
+#[derive(Debug, Clone)]
+pub struct EncryptedNumber<I> {
+    pub data: core::Ciphertext<I>,
+    pub exponent: i32,
+    /// Upper bound on the bit length of the absolute value of the mantissa.
+    pub mantissa_bits: usize,
+}
+
+impl<I> Code<I> {
+    pub fn new(n: &I) -> Code<I>
+    where
+        I: Clone,
+    {
+        Code { modulus: n.clone() }
+    }
+
+    /// Encode `x` using the given `exponent`, failing if `x` cannot be represented exactly.
+    pub fn try_encode_with_exponent(&self, x: f64, exponent: i32) -> Result<EncodedNumber<I>, Error>
+    where
+        I: From<u64>,
+        I: PartialOrd,
+        I: Shl<usize, Output = I>,
+        for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+        for<'a> &'a I: Sub<I, Output = I>,
+    {
+        let (negative, mantissa, natural_exponent) = decompose(x)?;
+        let shift = if mantissa == 0 {
+            0
+        } else if natural_exponent < exponent {
+            return Err(Error::PrecisionLoss);
+        } else {
+            (natural_exponent - exponent) as usize * LOG2_BASE as usize
+        };
+
+        let ref n = self.modulus;
+        let m = I::from(mantissa) << shift;
+        if &(&m + &m) >= n {
+            return Err(Error::PlaintextOutOfRange);
+        }
+        Ok(EncodedNumber {
+            data: core::Plaintext(if negative { n - m } else { m }),
+            exponent: exponent,
+            mantissa_bits: (64 - mantissa.leading_zeros() as usize) + shift,
+        })
+    }
+
+    fn encode_f64(&self, x: f64) -> Result<EncodedNumber<I>, Error>
+    where
+        I: From<u64>,
+        I: PartialOrd,
+        I: Shl<usize, Output = I>,
+        for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+        for<'a> &'a I: Sub<I, Output = I>,
+    {
+        let (_, _, natural_exponent) = decompose(x)?;
+        self.try_encode_with_exponent(x, natural_exponent)
+    }
+
+    // Decode `x`, failing with `PrecisionLoss` rather than rounding if `exact` is set.
+    fn decode_f64(&self, x: &EncodedNumber<I>, exact: bool) -> Result<f64, Error>
+    where
+        u64: TryConvertFrom<I>,
+        I: Clone,
+        I: From<u64>,
+        I: PartialOrd,
+        I: Shl<usize, Output = I>,
+        for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+        for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+        for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+        for<'a> &'a I: Shr<usize, Output = I>,
+    {
+        let ref n = self.modulus;
+        let ref y = x.data.0;
+        let negative = &(y + y) > n;
+        let ref magnitude = if negative { n - y } else { y.clone() };
+
+        let (approximation, shift) = approximate(magnitude)?;
+        let value = scale(
+            approximation,
+            shift as i64 + i64::from(x.exponent) * i64::from(LOG2_BASE),
+        );
+        if value.is_infinite() {
+            return Err(Error::DecodeOverflow);
+        }
+        if exact && !represents(value, x.exponent, magnitude) {
+            return Err(Error::PrecisionLoss);
+        }
+        Ok(if negative { -value } else { value })
+    }
+}
+
+// Check that finite, non-negative `value` equals `magnitude * BASE^exponent`.
+fn represents<I>(value: f64, exponent: i32, magnitude: &I) -> bool
+where
+    I: From<u64>,
+    I: PartialOrd,
+    I: Shl<usize, Output = I>,
+{
+    match decompose(value) {
+        Ok((_, 0, _)) => *magnitude == I::from(0),
+        Ok((_, mantissa, natural_exponent)) if natural_exponent >= exponent => {
+            let shift = (natural_exponent - exponent) as usize * LOG2_BASE as usize;
+            *magnitude == I::from(mantissa) << shift
+        }
+        _ => false,
+    }
+}
+
+// Split finite `x` into sign, mantissa, and exponent such that `x = ±mantissa * BASE^exponent`,
+// choosing the largest exponent for which this is exact.
+fn decompose(x: f64) -> Result<(bool, u64, i32), Error> {
+    if !x.is_finite() {
+        return Err(Error::PlaintextOutOfRange);
+    }
+    let bits = x.to_bits();
+    let biased_exponent = ((bits >> 52) & 0x7ff) as i32;
+    let fraction = bits & ((1 << 52) - 1);
+    let (mut mantissa, binary_exponent) = if biased_exponent == 0 {
+        (fraction, -1074)
+    } else {
+        (fraction | (1 << 52), biased_exponent - 1075)
+    };
+    if mantissa == 0 {
+        return Ok((false, 0, 0));
+    }
+
+    mantissa <<= binary_exponent.rem_euclid(LOG2_BASE);
+    let mut exponent = binary_exponent.div_euclid(LOG2_BASE);
+    while mantissa % BASE == 0 {
+        mantissa /= BASE;
+        exponent += 1;
+    }
+    Ok((x.is_sign_negative(), mantissa, exponent))
+}
+
+// Approximate non-negative `x` by `f * 2^shift`, keeping at least 64 significant bits in `f`
+// before rounding to the nearest float.
+fn approximate<I>(x: &I) -> Result<(f64, usize), Error>
+where
+    u64: TryConvertFrom<I>,
+    I: From<u64>,
+    I: PartialOrd,
+    I: Shl<usize, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'a> &'a I: Shr<usize, Output = I>,
+{
+    let ref bound = I::from(1) << 128;
+    let mut shift = 0;
+    while &(x >> shift) >= bound {
+        shift += 64;
+    }
+    let ref top = x >> shift;
+    let ref mask = I::from(1) << 64;
+    let low = u64::_try_from(&(top % mask))?;
+    let high = u64::_try_from(&(top >> 64))?;
+    Ok((((u128::from(high) << 64) | u128::from(low)) as f64, shift))
+}
+
+// Compute `x * 2^e`, stepping through large `e` to avoid overflow in the power of two.
+fn scale(mut x: f64, mut e: i64) -> f64 {
+    let step = 2_f64.powi(1000);
+    while e > 1000 && x.is_finite() {
+        x *= step;
+        e -= 1000;
+    }
+    while e < -1000 && x != 0.0 {
+        x /= step;
+        e += 1000;
+    }
+    x * 2_f64.powi(e.clamp(-1100, 1100) as i32)
+}
+
+// Bit length of the mantissa of `c` once its exponent is lowered to `exponent`.
+fn aligned_bits<I>(c: &EncryptedNumber<I>, exponent: i32) -> usize {
+    let shift = (i64::from(c.exponent) - i64::from(exponent)) as usize * LOG2_BASE as usize;
+    c.mantissa_bits.saturating_add(shift)
+}
+
+// Bit length of non-negative `x`.
+fn bit_length<I>(x: &I) -> usize
+where
+    I: ToBytes,
+{
+    let bytes = x.to_bytes();
+    bytes.len() * 8 - bytes.first().map_or(0, |b| b.leading_zeros() as usize)
+}
+
+// Check that mantissas of `bits` bits stay in the half of `Z_n` used for their sign.
+fn check_mantissa_bits<I>(bits: usize, n: &I) -> Result<(), Error>
+where
+    I: ToBytes,
+{
+    // |m| < 2^bits <= 2^(n_bits - 2) ensures 2|m| < n
+    if bits.saturating_add(2) > bit_length(n) {
+        return Err(Error::PlaintextOutOfRange);
+    }
+    Ok(())
+}
+
+// Check that the bound on the mantissa of `m` holds, as it may come from elsewhere.
+fn check_bound<I>(m: &EncodedNumber<I>, n: &I) -> Result<(), Error>
+where
+    I: Clone,
+    I: PartialOrd,
+    I: ToBytes,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+{
+    let ref y = m.data.0;
+    let magnitude = if &(y + y) > n { n - y } else { y.clone() };
+    if bit_length(&magnitude) > m.mantissa_bits {
+        return Err(Error::PlaintextOutOfRange);
+    }
+    check_mantissa_bits(m.mantissa_bits, n)
+}
+
+// Lower the exponent of `c` to `exponent` by homomorphically multiplying with a power of `BASE`.
+fn decrease_exponent<I, S, EK>(
+    ek: &EK,
+    c: &EncryptedNumber<I>,
+    exponent: i32,
+) -> core::Ciphertext<I>
+where
+    S: Multiplication<EK, core::Ciphertext<I>, core::Plaintext<I>, core::Ciphertext<I>>,
+    I: Clone,
+    I: One,
+    I: Shl<usize, Output = I>,
+{
+    if c.exponent == exponent {
+        c.data.clone()
+    } else {
+        let shift = (c.exponent - exponent) as usize * LOG2_BASE as usize;
+        S::mul(ek, &c.data, &core::Plaintext(I::one() << shift))
+    }
+}
+
+impl<I> Encoder<f64> for Code<I>
+where
+    I: From<u64>,
+    I: PartialOrd,
+    I: Shl<usize, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a> &'a I: Sub<I, Output = I>,
+{
+    type Target = EncodedNumber<I>;
+    fn encode(&self, x: &f64) -> EncodedNumber<I> {
+        self.encode_f64(*x)
+            .expect("value cannot be encoded in the plaintext space")
+    }
+}
+
+impl<I> TryEncoder<f64> for Code<I>
+where
+    I: From<u64>,
+    I: PartialOrd,
+    I: Shl<usize, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a> &'a I: Sub<I, Output = I>,
+{
+    fn try_encode(&self, x: &f64) -> Result<EncodedNumber<I>, Error> {
+        self.encode_f64(*x)
+    }
+}
+
+impl<I> Decoder<f64> for Code<I>
+where
+    u64: TryConvertFrom<I>,
+    I: Clone,
+    I: From<u64>,
+    I: PartialOrd,
+    I: Shl<usize, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'a> &'a I: Shr<usize, Output = I>,
+{
+    type Source = EncodedNumber<I>;
+    fn decode(&self, x: &EncodedNumber<I>) -> f64 {
+        self.decode_f64(x, false).expect("overflow while decoding")
+    }
+}
+
+impl<I> TryDecoder<f64> for Code<I>
+where
+    u64: TryConvertFrom<I>,
+    I: Clone,
+    I: From<u64>,
+    I: PartialOrd,
+    I: Shl<usize, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'a> &'a I: Shr<usize, Output = I>,
+{
+    fn try_decode(&self, x: &EncodedNumber<I>) -> Result<f64, Error> {
+        self.decode_f64(x, true)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<I> Serialize for EncodedNumber<I>
+where
+    I: Display,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        EncodedNumberRepr {
+            data: to_repr(&self.data.0),
+            exponent: self.exponent,
+            mantissa_bits: self.mantissa_bits,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, I> Deserialize<'de> for EncodedNumber<I>
+where
+    I: FromStr,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = EncodedNumberRepr::deserialize(deserializer)?;
+        // n is unknown here, so the bound is checked against the mantissa by try_add and try_mul
+        Ok(EncodedNumber {
+            data: core::Plaintext(from_repr(&repr.data)?),
+            exponent: repr.exponent,
+            mantissa_bits: repr.mantissa_bits,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<I> Serialize for EncryptedNumber<I>
+where
+    I: Display,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        EncodedNumberRepr {
+            data: to_repr(&self.data.0),
+            exponent: self.exponent,
+            mantissa_bits: self.mantissa_bits,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, I> Deserialize<'de> for EncryptedNumber<I>
+where
+    I: FromStr,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = EncodedNumberRepr::deserialize(deserializer)?;
+        // the bound of a ciphertext cannot be checked, but try_add and try_mul bound it by n
+        Ok(EncryptedNumber {
+            data: core::Ciphertext(from_repr(&repr.data)?),
+            exponent: repr.exponent,
+            mantissa_bits: repr.mantissa_bits,
+        })
+    }
+}
+
+impl<I, S, EK> Encryption<EK, EncodedNumber<I>, EncryptedNumber<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Encryption<EK, core::Plaintext<I>, core::Ciphertext<I>>,
+{
+    fn encrypt(ek: &EK, m: &EncodedNumber<I>) -> EncryptedNumber<I> {
+        EncryptedNumber {
+            data: S::encrypt(&ek, &m.data),
+            exponent: m.exponent,
+            mantissa_bits: m.mantissa_bits,
+        }
+    }
+}
+
+impl<I, S, DK> Decryption<DK, EncryptedNumber<I>, EncodedNumber<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Decryption<DK, core::Ciphertext<I>, core::Plaintext<I>>,
+{
+    fn decrypt(dk: &DK, c: &EncryptedNumber<I>) -> EncodedNumber<I> {
+        EncodedNumber {
+            data: S::decrypt(dk, &c.data),
+            exponent: c.exponent,
+            mantissa_bits: c.mantissa_bits,
+        }
+    }
+}
+
+impl<I, S, EK> Rerandomisation<EK, EncryptedNumber<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Rerandomisation<EK, core::Ciphertext<I>>,
+{
+    fn rerandomise(ek: &EK, c: &EncryptedNumber<I>) -> EncryptedNumber<I> {
+        EncryptedNumber {
+            data: S::rerandomise(&ek, &c.data),
+            exponent: c.exponent,
+            mantissa_bits: c.mantissa_bits,
+        }
+    }
+}
+
+impl<I, S, EK> Addition<EK, EncryptedNumber<I>, EncryptedNumber<I>, EncryptedNumber<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Addition<EK, core::Ciphertext<I>, core::Ciphertext<I>, core::Ciphertext<I>>,
+    S: Multiplication<EK, core::Ciphertext<I>, core::Plaintext<I>, core::Ciphertext<I>>,
+    I: Clone,
+    I: One,
+    I: Shl<usize, Output = I>,
+{
+    fn add(ek: &EK, c1: &EncryptedNumber<I>, c2: &EncryptedNumber<I>) -> EncryptedNumber<I> {
+        let exponent = c1.exponent.min(c2.exponent);
+        let ref d1 = decrease_exponent::<I, S, EK>(ek, c1, exponent);
+        let ref d2 = decrease_exponent::<I, S, EK>(ek, c2, exponent);
+        EncryptedNumber {
+            data: S::add(&ek, d1, d2),
+            exponent: exponent,
+            mantissa_bits: aligned_bits(c1, exponent).max(aligned_bits(c2, exponent)) + 1,
+        }
+    }
+}
+
+impl<I, S> TryAddition<EncryptionKey<I>, EncryptedNumber<I>, EncryptedNumber<I>, EncryptedNumber<I>>
+    for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Addition<EncryptionKey<I>, EncryptedNumber<I>, EncryptedNumber<I>, EncryptedNumber<I>>,
+    I: ToBytes,
+{
+    fn try_add(
+        ek: &EncryptionKey<I>,
+        c1: &EncryptedNumber<I>,
+        c2: &EncryptedNumber<I>,
+    ) -> Result<EncryptedNumber<I>, Error> {
+        let exponent = c1.exponent.min(c2.exponent);
+        let bits = aligned_bits(c1, exponent).max(aligned_bits(c2, exponent));
+        check_mantissa_bits(bits.saturating_add(1), &ek.n)?;
+        Ok(S::add(ek, c1, c2))
+    }
+}
+
+impl<I, S, EK> Addition<EK, EncryptedNumber<I>, EncodedNumber<I>, EncryptedNumber<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Encryption<EK, EncodedNumber<I>, EncryptedNumber<I>>,
+    S: Addition<EK, EncryptedNumber<I>, EncryptedNumber<I>, EncryptedNumber<I>>,
+{
+    fn add(ek: &EK, c1: &EncryptedNumber<I>, m2: &EncodedNumber<I>) -> EncryptedNumber<I> {
+        let ref c2 = S::encrypt(ek, m2);
+        S::add(ek, c1, c2)
+    }
+}
+
+impl<I, S> TryAddition<EncryptionKey<I>, EncryptedNumber<I>, EncodedNumber<I>, EncryptedNumber<I>>
+    for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Encryption<EncryptionKey<I>, EncodedNumber<I>, EncryptedNumber<I>>,
+    S: TryAddition<EncryptionKey<I>, EncryptedNumber<I>, EncryptedNumber<I>, EncryptedNumber<I>>,
+    I: Clone,
+    I: PartialOrd,
+    I: ToBytes,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+{
+    fn try_add(
+        ek: &EncryptionKey<I>,
+        c1: &EncryptedNumber<I>,
+        m2: &EncodedNumber<I>,
+    ) -> Result<EncryptedNumber<I>, Error> {
+        check_bound(m2, &ek.n)?;
+        let ref c2 = S::encrypt(ek, m2);
+        S::try_add(ek, c1, c2)
+    }
+}
+
+impl<I, S, EK> Multiplication<EK, EncryptedNumber<I>, EncodedNumber<I>, EncryptedNumber<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Multiplication<EK, core::Ciphertext<I>, core::Plaintext<I>, core::Ciphertext<I>>,
+{
+    fn mul(ek: &EK, c1: &EncryptedNumber<I>, m2: &EncodedNumber<I>) -> EncryptedNumber<I> {
+        EncryptedNumber {
+            data: S::mul(&ek, &c1.data, &m2.data),
+            exponent: c1.exponent + m2.exponent,
+            mantissa_bits: c1.mantissa_bits + m2.mantissa_bits,
+        }
+    }
+}
+
+impl<I, S>
+    TryMultiplication<EncryptionKey<I>, EncryptedNumber<I>, EncodedNumber<I>, EncryptedNumber<I>>
+    for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Multiplication<EncryptionKey<I>, EncryptedNumber<I>, EncodedNumber<I>, EncryptedNumber<I>>,
+    I: Clone,
+    I: PartialOrd,
+    I: ToBytes,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+{
+    fn try_mul(
+        ek: &EncryptionKey<I>,
+        c1: &EncryptedNumber<I>,
+        m2: &EncodedNumber<I>,
+    ) -> Result<EncryptedNumber<I>, Error> {
+        check_bound(m2, &ek.n)?;
+        check_mantissa_bits(c1.mantissa_bits.saturating_add(m2.mantissa_bits), &ek.n)?;
+        c1.exponent
+            .checked_add(m2.exponent)
+            .ok_or(Error::PlaintextOutOfRange)?;
+        Ok(S::mul(ek, c1, m2))
+    }
+}
+
+bigint!(
+    I,
+    #[cfg(test)]
+    mod tests {
+
+        use super::I;
+        use crate::core::*;
+        use crate::float::*;
+        use crate::AbstractPaillier;
+
+        fn test_keypair() -> Keypair<I> {
+            let p = str::parse("148677972634832330983979593310074301486537017973460461278300587514468301043894574906886127642530475786889672304776052879927627556769456140664043088700743909632312483413393134504352834240399191134336344285483935856491230340093391784574980688823380828143810804684752914935441384845195613674104960646037368551517").unwrap();
+            let q = str::parse("158741574437007245654463598139927898730476924736461654463975966787719309357536545869203069369466212089132653564188443272208127277664424448947476335413293018778018615899291704693105620242763173357203898195318179150836424196645745308205164116144020613415407736216097185962171301808761138424668335445923774195463").unwrap();
+            Keypair { p: p, q: q }
+        }
+
+        #[test]
+        fn test_encoding_roundtrip() {
+            let (ek, _) = test_keypair().keys();
+            let code = Code::new(&ek.n);
+
+            for x in &[0.0, 1.0, -1.0, 1.5, -2.25, 0.1, -1e-300, 1e300, f64::MAX] {
+                let m: EncodedNumber<I> = code.encode(x);
+                let y: f64 = code.decode(&m);
+                assert_eq!(y, *x);
+            }
+
+            let m: EncodedNumber<I> = code.encode(&3.0);
+            assert_eq!(m.exponent, 0);
+            let m: EncodedNumber<I> = code.encode(&0.0625);
+            assert_eq!(m.exponent, -1);
+        }
+
+        #[test]
+        fn test_correct_addition() {
+            let (ek, dk) = test_keypair().keys();
+            let code = Code::new(&ek.n);
+
+            let c1 = AbstractPaillier::encrypt(&ek, &code.encode(&3.0));
+            let c2 = AbstractPaillier::encrypt(&ek, &code.encode(&-0.0625));
+            let c = AbstractPaillier::add(&ek, &c1, &c2);
+            let y: f64 = code.decode(&AbstractPaillier::decrypt(&dk, &c));
+            assert_eq!(y, 2.9375);
+
+            let c = AbstractPaillier::add(&ek, &c, &code.encode(&0.5));
+            let y: f64 = code.decode(&AbstractPaillier::decrypt(&dk, &c));
+            assert_eq!(y, 3.4375);
+        }
+
+        #[test]
+        fn test_correct_multiplication() {
+            let (ek, dk) = test_keypair().keys();
+            let code = Code::new(&ek.n);
+            let eek = ek.with_code(&code);
+            let ddk = dk.with_code(&code);
+
+            let c: EncryptedNumber<I> = AbstractPaillier::encrypt(&eek, &1.5);
+            let c = AbstractPaillier::mul(&eek, &c, &-0.25);
+            let y: f64 = AbstractPaillier::decrypt(&ddk, &c);
+            assert_eq!(y, -0.375);
+
+            let c: EncryptedNumber<I> = AbstractPaillier::encrypt(&eek, &0.1);
+            let c = AbstractPaillier::mul(&eek, &c, &0.1);
+            let y: f64 = AbstractPaillier::decrypt(&ddk, &c);
+            assert_eq!(y, 0.1 * 0.1);
+        }
+
+        #[test]
+        fn test_precision_loss() {
+            let (ek, _) = test_keypair().keys();
+            let code = Code::new(&ek.n);
+
+            let m = code.try_encode_with_exponent(0.5, -2).unwrap();
+            assert_eq!(m.exponent, -2);
+            let y: f64 = code.decode(&m);
+            assert_eq!(y, 0.5);
+
+            let res = code.try_encode_with_exponent(0.1, -2);
+            assert_eq!(res.err(), Some(Error::PrecisionLoss));
+
+            // the product of two mantissas of 0.1 needs more bits than a float has
+            let (ek, dk) = test_keypair().keys();
+            let c = AbstractPaillier::encrypt(&ek, &code.encode(&0.1));
+            let c = AbstractPaillier::mul(&ek, &c, &code.encode(&0.1));
+            let m = AbstractPaillier::decrypt(&dk, &c);
+            let res: Result<f64, _> = code.try_decode(&m);
+            assert_eq!(res.err(), Some(Error::PrecisionLoss));
+            let y: f64 = code.decode(&m);
+            assert_eq!(y, 0.1 * 0.1);
+
+            let m = code.encode(&-0.375);
+            assert_eq!(code.try_decode(&m), Ok(-0.375));
+        }
+
+        #[test]
+        fn test_overflow() {
+            let (ek, _) = test_keypair().keys();
+            let code = Code::new(&ek.n);
+
+            let res: Result<EncodedNumber<I>, _> = code.try_encode(&f64::INFINITY);
+            assert_eq!(res.err(), Some(Error::PlaintextOutOfRange));
+            let res = code.try_encode_with_exponent(1.0, -600);
+            assert_eq!(res.err(), Some(Error::PlaintextOutOfRange));
+
+            let m = EncodedNumber {
+                data: Plaintext::from(1),
+                exponent: 300,
+                mantissa_bits: 1,
+            };
+            let res: Result<f64, _> = code.try_decode(&m);
+            assert_eq!(res.err(), Some(Error::DecodeOverflow));
+        }
+
+        #[test]
+        fn test_alignment_overflow() {
+            let (ek, dk) = test_keypair().keys();
+            let code = Code::new(&ek.n);
+
+            // aligning the exponents would need a mantissa of more than 2000 bits
+            let c1 = AbstractPaillier::encrypt(&ek, &code.encode(&1e300));
+            let c2 = AbstractPaillier::encrypt(&ek, &code.encode(&1e-300));
+            let res = AbstractPaillier::try_add(&ek, &c1, &c2);
+            assert_eq!(res.err(), Some(Error::PlaintextOutOfRange));
+            let res = AbstractPaillier::try_add(&ek, &c1, &code.encode(&1e-300));
+            assert_eq!(res.err(), Some(Error::PlaintextOutOfRange));
+
+            // while moderately different magnitudes are fine
+            let c2 = AbstractPaillier::encrypt(&ek, &code.encode(&1e-30));
+            let c = AbstractPaillier::try_add(&ek, &c2, &code.encode(&1e30)).unwrap();
+            let y: f64 = code.decode(&AbstractPaillier::decrypt(&dk, &c));
+            assert_eq!(y, 1e30 + 1e-30);
+
+            // as would multiplying two mantissas of 1200 bits
+            let m = code.try_encode_with_exponent(1.0, -300).unwrap();
+            let c3 = AbstractPaillier::encrypt(&ek, &m);
+            let res = AbstractPaillier::try_mul(&ek, &c3, &m);
+            assert_eq!(res.err(), Some(Error::PlaintextOutOfRange));
+            let c = AbstractPaillier::try_mul(&ek, &c2, &code.encode(&-2.0)).unwrap();
+            let y: f64 = code.decode(&AbstractPaillier::decrypt(&dk, &c));
+            assert_eq!(y, -2e-30);
+        }
+
+        #[test]
+        fn test_forged_mantissa_bits() {
+            let (ek, _) = test_keypair().keys();
+            let code = Code::new(&ek.n);
+            let c = AbstractPaillier::encrypt(&ek, &code.encode(&1.0));
+
+            // a plaintext understating its mantissa, as may arrive from elsewhere
+            let mut m = code.try_encode_with_exponent(-1.0, -300).unwrap();
+            m.mantissa_bits = 1;
+            let res = AbstractPaillier::try_add(&ek, &c, &m);
+            assert_eq!(res.err(), Some(Error::PlaintextOutOfRange));
+            let res = AbstractPaillier::try_mul(&ek, &c, &m);
+            assert_eq!(res.err(), Some(Error::PlaintextOutOfRange));
+
+            // and bounds too large for n
+            let mut c = c;
+            c.mantissa_bits = usize::MAX;
+            let res = AbstractPaillier::try_add(&ek, &c, &c);
+            assert_eq!(res.err(), Some(Error::PlaintextOutOfRange));
+        }
+    }
+);
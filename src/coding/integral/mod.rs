@@ -5,10 +5,11 @@ use super::*;
 pub mod scalar;
 pub mod vector;
 
-use crate::arithimpl::traits::ConvertFrom;
+use crate::arithimpl::traits::{ConvertFrom, NumberTests, TryConvertFrom};
 use num_traits::One;
+use std::convert::TryFrom;
 use std::marker::PhantomData;
-use std::ops::{Add, Rem, Shl, Shr};
+use std::ops::{Add, Rem, Shl, Shr, Sub};
 
 /// Integral code for scalars and vectors.
 pub struct Code<I> {
@@ -19,6 +20,11 @@ pub struct Code<I> {
     pub _phantom: PhantomData<I>,
 }
 
+/// Integral code for signed scalars in plaintext space `Z_n`.
+pub struct SignedCode<I> {
+    modulus: I,
+}
+
 impl<I> Code<I> {
     pub fn default() -> Code<I> {
         Self::new(10, 64)
@@ -33,6 +39,15 @@ impl<I> Code<I> {
     }
 }
 
+impl<I> SignedCode<I> {
+    pub fn new(n: &I) -> SignedCode<I>
+    where
+        I: Clone,
+    {
+        SignedCode { modulus: n.clone() }
+    }
+}
+
 // impl<I> Encoder<usize> for Code<I>
 // where
 //     I: From<usize>,
@@ -101,6 +116,15 @@ where
     }
 }
 
+impl<I> TryEncoder<u64> for Code<I>
+where
+    I: From<u64>,
+{
+    fn try_encode(&self, x: &u64) -> Result<Self::Target, Error> {
+        Ok(self.encode(x))
+    }
+}
+
 impl<I> Encoder<Vec<u64>> for Code<I>
 where
     I: One,
@@ -122,6 +146,29 @@ where
     }
 }
 
+impl<I> TryEncoder<Vec<u64>> for Code<I>
+where
+    I: One,
+    I: Clone,
+    I: From<u64>,
+    I: Shl<usize, Output = I>,
+    I: Add<I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'a> &'a I: Shr<usize, Output = I>,
+{
+    /// Fails unless there are exactly `component_count` components, each fitting in
+    /// `component_size` bits.
+    fn try_encode(&self, x: &Vec<u64>) -> Result<Self::Target, Error> {
+        if x.len() != self.component_count {
+            return Err(Error::PlaintextOutOfRange);
+        }
+        if self.component_size < 64 && x.iter().any(|c| c >> self.component_size != 0) {
+            return Err(Error::PlaintextOutOfRange);
+        }
+        Ok(self.encode(x))
+    }
+}
+
 // impl<I> Decoder<usize> for Code<I>
 // where
 //     usize: ConvertFrom<I>,
@@ -172,6 +219,16 @@ where
     }
 }
 
+impl<I> TryDecoder<u64> for Code<I>
+where
+    u64: ConvertFrom<I>,
+    u64: TryConvertFrom<I>,
+{
+    fn try_decode(&self, x: &scalar::Plaintext<I, u64>) -> Result<u64, Error> {
+        u64::_try_from(&x.data.0)
+    }
+}
+
 impl<I> Decoder<Vec<u64>> for Code<I>
 where
     u64: ConvertFrom<I>,
@@ -189,3 +246,153 @@ where
         unpack(x.data.0.clone(), self.component_count, self.component_size)
     }
 }
+
+impl<I> TryDecoder<Vec<u64>> for Code<I>
+where
+    u64: ConvertFrom<I>,
+    u64: TryConvertFrom<I>,
+    I: One,
+    I: Clone,
+    I: From<u64>,
+    I: NumberTests,
+    I: Shl<usize, Output = I>,
+    I: Add<I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'a> &'a I: Shr<usize, Output = I>,
+{
+    /// Only a carry out of the first component is detected. A carry out of any other component
+    /// silently spills into its neighbour, so `component_size` must leave enough gap space above
+    /// the largest value a component can reach after the intended homomorphic operations.
+    fn try_decode(&self, x: &vector::Plaintext<I, u64>) -> Result<Vec<u64>, Error> {
+        try_unpack(x.data.0.clone(), self.component_count, self.component_size)
+    }
+}
+
+// Signed scalars split `Z_n` in two: values in `[0, n/2]` represent non-negative numbers and
+// values in `(n/2, n)` represent negative numbers, with `x < 0` encoded as `n - |x|`. Results
+// whose representative does not fit in the target type fall in the overflow band between the
+// two halves and are reported as such when decoding.
+
+fn encode_signed<I>(x: i128, n: &I) -> Result<I, Error>
+where
+    I: From<u64>,
+    I: PartialOrd,
+    I: Shl<usize, Output = I>,
+    I: Add<I, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a> &'a I: Sub<I, Output = I>,
+{
+    let magnitude = x.unsigned_abs();
+    let m = (I::from((magnitude >> 64) as u64) << 64) + I::from(magnitude as u64);
+    if &(&m + &m) >= n {
+        return Err(Error::PlaintextOutOfRange);
+    }
+    if x < 0 {
+        Ok(n - m)
+    } else {
+        Ok(m)
+    }
+}
+
+fn decode_signed<I>(y: &I, n: &I) -> Result<i128, Error>
+where
+    u64: TryConvertFrom<I>,
+    I: From<u64>,
+    I: PartialOrd,
+    I: Shl<usize, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'a> &'a I: Shr<usize, Output = I>,
+{
+    let negative = &(y + y) > n;
+    let ref magnitude = if negative { n - y } else { y - &I::from(0) };
+    let ref mask = I::from(1) << 64;
+    let low = u64::_try_from(&(magnitude % mask))?;
+    let high = u64::_try_from(&(magnitude >> 64))?;
+    let magnitude = (u128::from(high) << 64) | u128::from(low);
+    if negative {
+        0_i128
+            .checked_sub_unsigned(magnitude)
+            .ok_or(Error::DecodeOverflow)
+    } else {
+        i128::try_from(magnitude).map_err(|_| Error::DecodeOverflow)
+    }
+}
+
+macro_rules! signed_code {
+    ( $t:ty ) => {
+        impl<I> Encoder<$t> for SignedCode<I>
+        where
+            I: From<u64>,
+            I: PartialOrd,
+            I: Shl<usize, Output = I>,
+            I: Add<I, Output = I>,
+            for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+            for<'a> &'a I: Sub<I, Output = I>,
+        {
+            type Target = scalar::Plaintext<I, $t>;
+            fn encode(&self, x: &$t) -> Self::Target {
+                self.try_encode(x)
+                    .expect("value too large for the plaintext space")
+            }
+        }
+
+        impl<I> TryEncoder<$t> for SignedCode<I>
+        where
+            I: From<u64>,
+            I: PartialOrd,
+            I: Shl<usize, Output = I>,
+            I: Add<I, Output = I>,
+            for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+            for<'a> &'a I: Sub<I, Output = I>,
+        {
+            fn try_encode(&self, x: &$t) -> Result<Self::Target, Error> {
+                Ok(scalar::Plaintext {
+                    data: core::Plaintext(encode_signed(i128::from(*x), &self.modulus)?),
+                    _phantom: PhantomData,
+                })
+            }
+        }
+
+        impl<I> Decoder<$t> for SignedCode<I>
+        where
+            u64: TryConvertFrom<I>,
+            I: From<u64>,
+            I: PartialOrd,
+            I: Shl<usize, Output = I>,
+            for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+            for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+            for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+            for<'a> &'a I: Shr<usize, Output = I>,
+        {
+            type Source = scalar::Plaintext<I, $t>;
+            fn decode(&self, x: &scalar::Plaintext<I, $t>) -> $t {
+                decode_signed(&x.data.0, &self.modulus)
+                    .and_then(|y| <$t>::try_from(y).map_err(|_| Error::DecodeOverflow))
+                    .expect("overflow while decoding")
+            }
+        }
+
+        impl<I> TryDecoder<$t> for SignedCode<I>
+        where
+            u64: TryConvertFrom<I>,
+            I: From<u64>,
+            I: PartialOrd,
+            I: Shl<usize, Output = I>,
+            for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+            for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+            for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+            for<'a> &'a I: Shr<usize, Output = I>,
+        {
+            fn try_decode(&self, x: &scalar::Plaintext<I, $t>) -> Result<$t, Error> {
+                let y = decode_signed(&x.data.0, &self.modulus)?;
+                <$t>::try_from(y).map_err(|_| Error::DecodeOverflow)
+            }
+        }
+    };
+}
+
+signed_code!(i32);
+signed_code!(i64);
+signed_code!(i128);
@@ -1,9 +1,12 @@
-//! Integral scalars such as `u64`.
+//! Integral scalars such as `u64` and `i64`.
 
 use super::*;
 
 use std::marker::PhantomData;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 /// Representation of unencrypted integral scalar.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Plaintext<I, T> {
@@ -12,10 +15,77 @@ pub struct Plaintext<I, T> {
 }
 
 /// Representation of encrypted integral scalar.
+///
+/// The underlying ciphertext defaults to `core::Ciphertext` but may be required to be a
+/// `core::ValidatedCiphertext` instead; see `ValidatedCiphertext`.
 #[derive(Debug, Clone)]
-pub struct Ciphertext<I, T> {
-    pub data: core::Ciphertext<I>,
-    pub _phantom: PhantomData<T>,
+pub struct Ciphertext<I, T, C = core::Ciphertext<I>> {
+    pub data: C,
+    pub _phantom: PhantomData<(I, T)>,
+}
+
+/// Representation of encrypted integral scalar known to be well-formed.
+pub type ValidatedCiphertext<I, T> = Ciphertext<I, T, core::ValidatedCiphertext<I>>;
+
+impl<I, T> AsRef<core::Plaintext<I>> for Plaintext<I, T> {
+    fn as_ref(&self) -> &core::Plaintext<I> {
+        &self.data
+    }
+}
+
+impl<I, T, C> AsRef<core::Ciphertext<I>> for Ciphertext<I, T, C>
+where
+    C: AsRef<core::Ciphertext<I>>,
+{
+    fn as_ref(&self) -> &core::Ciphertext<I> {
+        self.data.as_ref()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<I, T> Serialize for Plaintext<I, T>
+where
+    core::Plaintext<I>: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.data.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, I, T> Deserialize<'de> for Plaintext<I, T>
+where
+    core::Plaintext<I>: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Plaintext {
+            data: core::Plaintext::deserialize(deserializer)?,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<I, T> Serialize for Ciphertext<I, T>
+where
+    core::Ciphertext<I>: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.data.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, I, T> Deserialize<'de> for Ciphertext<I, T>
+where
+    core::Ciphertext<I>: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Ciphertext {
+            data: core::Ciphertext::deserialize(deserializer)?,
+            _phantom: PhantomData,
+        })
+    }
 }
 
 impl<I, T, S, EK> Encryption<EK, Plaintext<I, T>, Ciphertext<I, T>> for S
@@ -31,12 +101,12 @@ where
     }
 }
 
-impl<I, T, S, DK> Decryption<DK, Ciphertext<I, T>, Plaintext<I, T>> for S
+impl<I, T, C, S, DK> Decryption<DK, Ciphertext<I, T, C>, Plaintext<I, T>> for S
 where
     S: AbstractScheme<BigInteger = I>,
-    S: Decryption<DK, core::Ciphertext<I>, core::Plaintext<I>>,
+    S: Decryption<DK, C, core::Plaintext<I>>,
 {
-    fn decrypt(dk: &DK, c: &Ciphertext<I, T>) -> Plaintext<I, T> {
+    fn decrypt(dk: &DK, c: &Ciphertext<I, T, C>) -> Plaintext<I, T> {
         Plaintext {
             data: S::decrypt(dk, &c.data),
             _phantom: PhantomData,
@@ -57,12 +127,13 @@ where
     }
 }
 
-impl<I, T, S, EK> Addition<EK, Ciphertext<I, T>, Ciphertext<I, T>, Ciphertext<I, T>> for S
+impl<I, T, C, S, EK> Addition<EK, Ciphertext<I, T, C>, Ciphertext<I, T, C>, Ciphertext<I, T, C>>
+    for S
 where
     S: AbstractScheme<BigInteger = I>,
-    S: Addition<EK, core::Ciphertext<I>, core::Ciphertext<I>, core::Ciphertext<I>>,
+    S: Addition<EK, C, C, C>,
 {
-    fn add(ek: &EK, c1: &Ciphertext<I, T>, c2: &Ciphertext<I, T>) -> Ciphertext<I, T> {
+    fn add(ek: &EK, c1: &Ciphertext<I, T, C>, c2: &Ciphertext<I, T, C>) -> Ciphertext<I, T, C> {
         Ciphertext {
             data: S::add(&ek, &c1.data, &c2.data),
             _phantom: PhantomData,
@@ -70,12 +141,40 @@ where
     }
 }
 
-impl<I, T, S, EK> Multiplication<EK, Ciphertext<I, T>, Plaintext<I, T>, Ciphertext<I, T>> for S
+impl<I, T, C, S, EK> Negation<EK, Ciphertext<I, T, C>, Ciphertext<I, T, C>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Negation<EK, C, C>,
+{
+    fn neg(ek: &EK, c: &Ciphertext<I, T, C>) -> Ciphertext<I, T, C> {
+        Ciphertext {
+            data: S::neg(&ek, &c.data),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<I, T, C, S, EK> Subtraction<EK, Ciphertext<I, T, C>, Ciphertext<I, T, C>, Ciphertext<I, T, C>>
+    for S
 where
     S: AbstractScheme<BigInteger = I>,
-    S: Multiplication<EK, core::Ciphertext<I>, core::Plaintext<I>, core::Ciphertext<I>>,
+    S: Subtraction<EK, C, C, C>,
 {
-    fn mul(ek: &EK, c1: &Ciphertext<I, T>, m2: &Plaintext<I, T>) -> Ciphertext<I, T> {
+    fn sub(ek: &EK, c1: &Ciphertext<I, T, C>, c2: &Ciphertext<I, T, C>) -> Ciphertext<I, T, C> {
+        Ciphertext {
+            data: S::sub(&ek, &c1.data, &c2.data),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<I, T, C, S, EK> Multiplication<EK, Ciphertext<I, T, C>, Plaintext<I, T>, Ciphertext<I, T, C>>
+    for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Multiplication<EK, C, core::Plaintext<I>, C>,
+{
+    fn mul(ek: &EK, c1: &Ciphertext<I, T, C>, m2: &Plaintext<I, T>) -> Ciphertext<I, T, C> {
         Ciphertext {
             data: S::mul(&ek, &c1.data, &m2.data),
             _phantom: PhantomData,
@@ -83,6 +182,79 @@ where
     }
 }
 
+impl<I, T, S, EK> Validation<EK, Ciphertext<I, T>, ValidatedCiphertext<I, T>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Validation<EK, core::Ciphertext<I>, core::ValidatedCiphertext<I>>,
+{
+    fn validate(ek: &EK, c: &Ciphertext<I, T>) -> Result<ValidatedCiphertext<I, T>, Error> {
+        Ok(Ciphertext {
+            data: S::validate(ek, &c.data)?,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<I, T, S, EK> TryEncryption<EK, Plaintext<I, T>, Ciphertext<I, T>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: TryEncryption<EK, core::Plaintext<I>, core::Ciphertext<I>>,
+{
+    fn try_encrypt(ek: &EK, m: &Plaintext<I, T>) -> Result<Ciphertext<I, T>, Error> {
+        Ok(Ciphertext {
+            data: S::try_encrypt(&ek, &m.data)?,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<I, T, S, DK> TryDecryption<DK, Ciphertext<I, T>, Plaintext<I, T>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: TryDecryption<DK, core::Ciphertext<I>, core::Plaintext<I>>,
+{
+    fn try_decrypt(dk: &DK, c: &Ciphertext<I, T>) -> Result<Plaintext<I, T>, Error> {
+        Ok(Plaintext {
+            data: S::try_decrypt(dk, &c.data)?,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<I, T, S, EK> TryAddition<EK, Ciphertext<I, T>, Ciphertext<I, T>, Ciphertext<I, T>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: TryAddition<EK, core::Ciphertext<I>, core::Ciphertext<I>, core::Ciphertext<I>>,
+{
+    fn try_add(
+        ek: &EK,
+        c1: &Ciphertext<I, T>,
+        c2: &Ciphertext<I, T>,
+    ) -> Result<Ciphertext<I, T>, Error> {
+        Ok(Ciphertext {
+            data: S::try_add(&ek, &c1.data, &c2.data)?,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<I, T, S, EK> TryMultiplication<EK, Ciphertext<I, T>, Plaintext<I, T>, Ciphertext<I, T>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: TryMultiplication<EK, core::Ciphertext<I>, core::Plaintext<I>, core::Ciphertext<I>>,
+{
+    fn try_mul(
+        ek: &EK,
+        c1: &Ciphertext<I, T>,
+        m2: &Plaintext<I, T>,
+    ) -> Result<Ciphertext<I, T>, Error> {
+        Ok(Ciphertext {
+            data: S::try_mul(&ek, &c1.data, &m2.data)?,
+            _phantom: PhantomData,
+        })
+    }
+}
+
 impl<I, T> From<T> for Plaintext<I, T>
 where
     T: Copy, // marker to avoid infinite loop by excluding Plaintext
@@ -151,5 +323,157 @@ bigint!(
             let m = AbstractPaillier::decrypt(&dk, &c);
             assert_eq!(m, code.encode(&200_u64));
         }
+
+        #[test]
+        fn test_correct_subtraction() {
+            let (ek, dk) = test_keypair().keys();
+            let code = Code::default();
+            let eek = ek.with_code(&code);
+
+            let c1: Ciphertext<I, u64> = AbstractPaillier::encrypt(&eek, &30);
+            let c2: Ciphertext<I, u64> = AbstractPaillier::encrypt(&eek, &10);
+
+            let c = AbstractPaillier::sub(&ek, &c1, &c2);
+            let m = AbstractPaillier::decrypt(&dk, &c);
+            assert_eq!(m, code.encode(&20_u64));
+
+            let c = AbstractPaillier::sub(&eek, &c1, &5);
+            let m = AbstractPaillier::decrypt(&dk, &c);
+            assert_eq!(m, code.encode(&25_u64));
+
+            let c = AbstractPaillier::sub(&eek, &50, &c1);
+            let m = AbstractPaillier::decrypt(&dk, &c);
+            assert_eq!(m, code.encode(&20_u64));
+        }
+
+        #[test]
+        fn test_signed_negation() {
+            let (ek, dk) = test_keypair().keys();
+            let code = SignedCode::new(&ek.n);
+
+            let c1 = AbstractPaillier::encrypt(&ek, &code.encode(&10_i64));
+            let c = AbstractPaillier::neg(&ek, &c1);
+            let m: i64 = code.decode(&AbstractPaillier::decrypt(&dk, &c));
+            assert_eq!(m, -10);
+
+            let c2 = AbstractPaillier::encrypt(&ek, &code.encode(&25_i64));
+            let c = AbstractPaillier::sub(&ek, &c1, &c2);
+            let m: i64 = code.decode(&AbstractPaillier::decrypt(&dk, &c));
+            assert_eq!(m, -15);
+        }
+
+        #[test]
+        fn test_validated_operations() {
+            let (ek, dk) = test_keypair().keys();
+            let code = Code::default();
+
+            let c1 = AbstractPaillier::encrypt(&ek, &code.encode(&10_u64));
+            let c1: ValidatedCiphertext<_, _> = AbstractPaillier::validate(&ek, &c1).unwrap();
+            let c2 = AbstractPaillier::encrypt(&ek, &code.encode(&20_u64));
+            let c2: ValidatedCiphertext<_, _> = AbstractPaillier::validate(&ek, &c2).unwrap();
+
+            let c = AbstractPaillier::add(&ek, &c1, &c2);
+            let c = AbstractPaillier::mul(&ek, &c, &code.encode(&2_u64));
+            let m = AbstractPaillier::decrypt(&dk, &c);
+            assert_eq!(m, code.encode(&60_u64));
+
+            let malformed: Ciphertext<_, u64> = Ciphertext {
+                data: core::Ciphertext(test_keypair().q),
+                _phantom: PhantomData,
+            };
+            let res: Result<ValidatedCiphertext<_, _>, _> =
+                AbstractPaillier::validate(&ek, &malformed);
+            assert_eq!(res.err(), Some(Error::InvalidCiphertext));
+        }
+
+        #[test]
+        fn test_decode_overflow() {
+            let (ek, dk) = test_keypair().keys();
+            let code = Code::default();
+
+            let m1 = code.encode(&u64::MAX);
+            let c1 = AbstractPaillier::encrypt(&ek, &m1);
+            let m2 = code.encode(&2_u64);
+
+            let c = AbstractPaillier::try_mul(&ek, &c1, &m2).unwrap();
+            let m = AbstractPaillier::try_decrypt(&dk, &c).unwrap();
+            let decoded: Result<u64, _> = code.try_decode(&m);
+            assert_eq!(decoded, Err(Error::DecodeOverflow));
+        }
+
+        #[test]
+        fn test_signed_operations() {
+            let (ek, dk) = test_keypair().keys();
+            let code = SignedCode::new(&ek.n);
+
+            let c1 = AbstractPaillier::encrypt(&ek, &code.encode(&-10_i64));
+            let c2 = AbstractPaillier::encrypt(&ek, &code.encode(&4_i64));
+
+            let c = AbstractPaillier::add(&ek, &c1, &c2);
+            let m: i64 = code.decode(&AbstractPaillier::decrypt(&dk, &c));
+            assert_eq!(m, -6);
+
+            let c = AbstractPaillier::mul(&ek, &c1, &code.encode(&-3_i64));
+            let m: i64 = code.decode(&AbstractPaillier::decrypt(&dk, &c));
+            assert_eq!(m, 30);
+
+            let m = code.encode(&i128::MIN);
+            let c = AbstractPaillier::encrypt(&ek, &m);
+            let m: i128 = code.decode(&AbstractPaillier::decrypt(&dk, &c));
+            assert_eq!(m, i128::MIN);
+        }
+
+        #[test]
+        fn test_signed_encoding_key() {
+            let (ek, dk) = test_keypair().keys();
+            let code = SignedCode::new(&ek.n);
+            let eek = ek.with_code(&code);
+
+            let c1: Ciphertext<I, i64> = AbstractPaillier::encrypt(&eek, &-10_i64);
+            let c = AbstractPaillier::add(&eek, &c1, &4_i64);
+            let m: i64 = code.decode(&AbstractPaillier::decrypt(&dk, &c));
+            assert_eq!(m, -6);
+
+            let c = AbstractPaillier::sub(&eek, &3_i64, &c1);
+            let m: i64 = code.decode(&AbstractPaillier::decrypt(&dk, &c));
+            assert_eq!(m, 13);
+
+            // values outside of the plaintext space are rejected rather than wrapped
+            let small = SignedCode::new(&I::from(1000_u64));
+            let res: Result<Plaintext<I, i64>, _> = small.try_encode(&500_i64);
+            assert_eq!(res.err(), Some(Error::PlaintextOutOfRange));
+            let res: Result<Plaintext<I, i64>, _> = small.try_encode(&-499_i64);
+            assert!(res.is_ok());
+
+            // also when encoding through the key, which reports them instead of panicking
+            let small_eek = ek.with_code(&small);
+            let res: Result<Ciphertext<I, i64>, _> =
+                AbstractPaillier::try_encrypt(&small_eek, &500_i64);
+            assert_eq!(res.err(), Some(Error::PlaintextOutOfRange));
+            let res: Result<Ciphertext<I, i64>, _> =
+                AbstractPaillier::try_add(&small_eek, &c1, &500_i64);
+            assert_eq!(res.err(), Some(Error::PlaintextOutOfRange));
+            let res: Result<Ciphertext<I, i64>, _> =
+                AbstractPaillier::try_mul(&small_eek, &c1, &500_i64);
+            assert_eq!(res.err(), Some(Error::PlaintextOutOfRange));
+        }
+
+        #[test]
+        fn test_signed_decode_overflow() {
+            let (ek, dk) = test_keypair().keys();
+            let code = SignedCode::new(&ek.n);
+
+            let c1 = AbstractPaillier::encrypt(&ek, &code.encode(&i32::MIN));
+            let c = AbstractPaillier::add(&ek, &c1, &c1);
+            let m = AbstractPaillier::decrypt(&dk, &c);
+            let decoded: Result<i32, _> = code.try_decode(&m);
+            assert_eq!(decoded, Err(Error::DecodeOverflow));
+
+            let m: scalar::Plaintext<_, i64> = code.encode(&i64::MAX);
+            let c1 = AbstractPaillier::encrypt(&ek, &m);
+            let c = AbstractPaillier::mul(&ek, &c1, &code.encode(&2_i64));
+            let decoded: Result<i64, _> = code.try_decode(&AbstractPaillier::decrypt(&dk, &c));
+            assert_eq!(decoded, Err(Error::DecodeOverflow));
+        }
     }
 );
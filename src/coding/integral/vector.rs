@@ -7,6 +7,15 @@ use super::*;
 
 use std::marker::PhantomData;
 
+#[cfg(feature = "serde")]
+use crate::serialize::*;
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "serde")]
+use std::fmt::Display;
+#[cfg(feature = "serde")]
+use std::str::FromStr;
+
 /// Representation of unencrypted integral vector.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Plaintext<I, T> {
@@ -17,12 +26,108 @@ pub struct Plaintext<I, T> {
 }
 
 /// Representation of encrypted integral vector.
+///
+/// As for scalars, the underlying ciphertext may be required to be a `core::ValidatedCiphertext`.
 #[derive(Debug, Clone)]
-pub struct Ciphertext<I, T> {
-    pub data: core::Ciphertext<I>,
+pub struct Ciphertext<I, T, C = core::Ciphertext<I>> {
+    pub data: C,
     pub component_count: usize,
     pub component_size: usize, // in bits
-    pub _phantom: PhantomData<T>,
+    pub _phantom: PhantomData<(I, T)>,
+}
+
+/// Representation of encrypted integral vector known to be well-formed.
+pub type ValidatedCiphertext<I, T> = Ciphertext<I, T, core::ValidatedCiphertext<I>>;
+
+// Check that `c1` and `c2` pack the same number of components of the same size, as needed to
+// combine them component-wise.
+fn check_layout<I, T, C>(c1: &Ciphertext<I, T, C>, c2: &Ciphertext<I, T, C>) -> Result<(), Error> {
+    if c1.component_count != c2.component_count || c1.component_size != c2.component_size {
+        return Err(Error::InvalidCiphertext);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+impl<I, T> Serialize for Plaintext<I, T>
+where
+    I: Display,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        VectorRepr {
+            data: to_repr(&self.data.0),
+            component_count: self.component_count,
+            component_size: self.component_size,
+        }
+        .serialize(serializer)
+    }
+}
+
+// Number of bits taken by the components of a serialized vector, if the layout is usable.
+#[cfg(feature = "serde")]
+fn layout_bits<E: de::Error>(repr: &VectorRepr) -> Result<usize, E> {
+    if repr.component_count == 0 || repr.component_size == 0 {
+        return Err(E::custom("invalid vector layout"));
+    }
+    repr.component_count
+        .checked_mul(repr.component_size)
+        .ok_or_else(|| E::custom("invalid vector layout"))
+}
+
+#[cfg(feature = "serde")]
+impl<'de, I, T> Deserialize<'de> for Plaintext<I, T>
+where
+    I: FromStr,
+    I: NumberTests,
+    for<'a> &'a I: Shr<usize, Output = I>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = VectorRepr::deserialize(deserializer)?;
+        let bits = layout_bits(&repr)?;
+        let data: I = from_repr(&repr.data)?;
+        // the packed components must fit in the layout
+        if NumberTests::is_negative(&data) || !NumberTests::is_zero(&(&data >> bits)) {
+            return Err(de::Error::custom("vector does not fit its layout"));
+        }
+        Ok(Plaintext {
+            data: core::Plaintext(data),
+            component_count: repr.component_count,
+            component_size: repr.component_size,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<I, T> Serialize for Ciphertext<I, T>
+where
+    I: Display,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        VectorRepr {
+            data: to_repr(&self.data.0),
+            component_count: self.component_count,
+            component_size: self.component_size,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, I, T> Deserialize<'de> for Ciphertext<I, T>
+where
+    I: FromStr,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = VectorRepr::deserialize(deserializer)?;
+        layout_bits(&repr)?;
+        Ok(Ciphertext {
+            data: core::Ciphertext(from_repr(&repr.data)?),
+            component_count: repr.component_count,
+            component_size: repr.component_size,
+            _phantom: PhantomData,
+        })
+    }
 }
 
 impl<I, T, S, EK> Encryption<EK, Plaintext<I, T>, Ciphertext<I, T>> for S
@@ -40,12 +145,12 @@ where
     }
 }
 
-impl<I, T, S, DK> Decryption<DK, Ciphertext<I, T>, Plaintext<I, T>> for S
+impl<I, T, C, S, DK> Decryption<DK, Ciphertext<I, T, C>, Plaintext<I, T>> for S
 where
     S: AbstractScheme<BigInteger = I>,
-    S: Decryption<DK, core::Ciphertext<I>, core::Plaintext<I>>,
+    S: Decryption<DK, C, core::Plaintext<I>>,
 {
-    fn decrypt(dk: &DK, c: &Ciphertext<I, T>) -> Plaintext<I, T> {
+    fn decrypt(dk: &DK, c: &Ciphertext<I, T, C>) -> Plaintext<I, T> {
         Plaintext {
             data: S::decrypt(dk, &c.data),
             component_count: c.component_count,
@@ -70,38 +175,185 @@ where
     }
 }
 
-impl<I, T, S, EK> Addition<EK, Ciphertext<I, T>, Ciphertext<I, T>, Ciphertext<I, T>> for S
+impl<I, T, C, S, EK> Addition<EK, Ciphertext<I, T, C>, Ciphertext<I, T, C>, Ciphertext<I, T, C>>
+    for S
 where
     S: AbstractScheme<BigInteger = I>,
-    S: Addition<EK, core::Ciphertext<I>, core::Ciphertext<I>, core::Ciphertext<I>>,
+    S: Addition<EK, C, C, C>,
 {
-    fn add(ek: &EK, c1: &Ciphertext<I, T>, c2: &Ciphertext<I, T>) -> Ciphertext<I, T> {
+    /// Panics if `c1` and `c2` have different layouts.
+    fn add(ek: &EK, c1: &Ciphertext<I, T, C>, c2: &Ciphertext<I, T, C>) -> Ciphertext<I, T, C> {
+        check_layout(c1, c2).expect("vectors of different layouts");
         let c = S::add(&ek, &c1.data, &c2.data);
         Ciphertext {
             data: c,
             component_count: c1.component_count,
-            component_size: c1.component_size, // TODO equality
+            component_size: c1.component_size,
             _phantom: PhantomData,
         }
     }
 }
 
-impl<I, T, S, EK> Multiplication<EK, Ciphertext<I, T>, scalar::Plaintext<I, T>, Ciphertext<I, T>>
+// Negation applies to the packed value as a whole and is hence only meaningful as part of a
+// larger computation; subtraction decodes correctly as long as no component underflows.
+
+impl<I, T, C, S, EK> Negation<EK, Ciphertext<I, T, C>, Ciphertext<I, T, C>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Negation<EK, C, C>,
+{
+    fn neg(ek: &EK, c: &Ciphertext<I, T, C>) -> Ciphertext<I, T, C> {
+        Ciphertext {
+            data: S::neg(&ek, &c.data),
+            component_count: c.component_count,
+            component_size: c.component_size,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<I, T, C, S, EK> Subtraction<EK, Ciphertext<I, T, C>, Ciphertext<I, T, C>, Ciphertext<I, T, C>>
     for S
 where
     S: AbstractScheme<BigInteger = I>,
-    S: Multiplication<EK, core::Ciphertext<I>, core::Plaintext<I>, core::Ciphertext<I>>,
+    S: Subtraction<EK, C, C, C>,
+{
+    /// Panics if `c1` and `c2` have different layouts.
+    fn sub(ek: &EK, c1: &Ciphertext<I, T, C>, c2: &Ciphertext<I, T, C>) -> Ciphertext<I, T, C> {
+        check_layout(c1, c2).expect("vectors of different layouts");
+        let c = S::sub(&ek, &c1.data, &c2.data);
+        Ciphertext {
+            data: c,
+            component_count: c1.component_count,
+            component_size: c1.component_size,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<I, T, C, S, EK>
+    Multiplication<EK, Ciphertext<I, T, C>, scalar::Plaintext<I, T>, Ciphertext<I, T, C>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Multiplication<EK, C, core::Plaintext<I>, C>,
 {
-    fn mul(ek: &EK, c1: &Ciphertext<I, T>, m2: &scalar::Plaintext<I, T>) -> Ciphertext<I, T> {
+    fn mul(ek: &EK, c1: &Ciphertext<I, T, C>, m2: &scalar::Plaintext<I, T>) -> Ciphertext<I, T, C> {
         Ciphertext {
             data: S::mul(&ek, &c1.data, &m2.data),
-            component_count: c1.component_count, // TODO equality
+            component_count: c1.component_count,
             component_size: c1.component_size,
             _phantom: PhantomData,
         }
     }
 }
 
+impl<I, T, S, EK> Validation<EK, Ciphertext<I, T>, ValidatedCiphertext<I, T>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Validation<EK, core::Ciphertext<I>, core::ValidatedCiphertext<I>>,
+{
+    fn validate(ek: &EK, c: &Ciphertext<I, T>) -> Result<ValidatedCiphertext<I, T>, Error> {
+        Ok(Ciphertext {
+            data: S::validate(ek, &c.data)?,
+            component_count: c.component_count,
+            component_size: c.component_size,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<I, T, S, EK> TryEncryption<EK, Plaintext<I, T>, Ciphertext<I, T>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: TryEncryption<EK, core::Plaintext<I>, core::Ciphertext<I>>,
+{
+    fn try_encrypt(ek: &EK, m: &Plaintext<I, T>) -> Result<Ciphertext<I, T>, Error> {
+        Ok(Ciphertext {
+            data: S::try_encrypt(&ek, &m.data)?,
+            component_count: m.component_count,
+            component_size: m.component_size,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<I, T, S, DK> TryDecryption<DK, Ciphertext<I, T>, Plaintext<I, T>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: TryDecryption<DK, core::Ciphertext<I>, core::Plaintext<I>>,
+{
+    fn try_decrypt(dk: &DK, c: &Ciphertext<I, T>) -> Result<Plaintext<I, T>, Error> {
+        Ok(Plaintext {
+            data: S::try_decrypt(dk, &c.data)?,
+            component_count: c.component_count,
+            component_size: c.component_size,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<I, T, S, EK> TryAddition<EK, Ciphertext<I, T>, Ciphertext<I, T>, Ciphertext<I, T>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: TryAddition<EK, core::Ciphertext<I>, core::Ciphertext<I>, core::Ciphertext<I>>,
+{
+    /// Fails with `InvalidCiphertext` if `c1` and `c2` have different layouts.
+    fn try_add(
+        ek: &EK,
+        c1: &Ciphertext<I, T>,
+        c2: &Ciphertext<I, T>,
+    ) -> Result<Ciphertext<I, T>, Error> {
+        check_layout(c1, c2)?;
+        Ok(Ciphertext {
+            data: S::try_add(&ek, &c1.data, &c2.data)?,
+            component_count: c1.component_count,
+            component_size: c1.component_size,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<I, T, S, EK> TrySubtraction<EK, Ciphertext<I, T>, Ciphertext<I, T>, Ciphertext<I, T>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: TrySubtraction<EK, core::Ciphertext<I>, core::Ciphertext<I>, core::Ciphertext<I>>,
+{
+    /// Fails with `InvalidCiphertext` if `c1` and `c2` have different layouts.
+    fn try_sub(
+        ek: &EK,
+        c1: &Ciphertext<I, T>,
+        c2: &Ciphertext<I, T>,
+    ) -> Result<Ciphertext<I, T>, Error> {
+        check_layout(c1, c2)?;
+        Ok(Ciphertext {
+            data: S::try_sub(&ek, &c1.data, &c2.data)?,
+            component_count: c1.component_count,
+            component_size: c1.component_size,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<I, T, S, EK> TryMultiplication<EK, Ciphertext<I, T>, scalar::Plaintext<I, T>, Ciphertext<I, T>>
+    for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: TryMultiplication<EK, core::Ciphertext<I>, core::Plaintext<I>, core::Ciphertext<I>>,
+{
+    fn try_mul(
+        ek: &EK,
+        c1: &Ciphertext<I, T>,
+        m2: &scalar::Plaintext<I, T>,
+    ) -> Result<Ciphertext<I, T>, Error> {
+        Ok(Ciphertext {
+            data: S::try_mul(&ek, &c1.data, &m2.data)?,
+            component_count: c1.component_count,
+            component_size: c1.component_size,
+            _phantom: PhantomData,
+        })
+    }
+}
+
 bigint!(
     I,
     #[cfg(test)]
@@ -164,5 +416,86 @@ bigint!(
             let m: Vec<_> = code.decode(&AbstractPaillier::decrypt(&dk, &c));
             assert_eq!(m, vec![4, 8, 12]);
         }
+
+        #[test]
+        fn test_correct_subtraction() {
+            let (ek, dk) = test_keypair().keys();
+
+            let code = Code::new(3, 16);
+            let eek = ek.with_code(&code);
+
+            let c1 = AbstractPaillier::encrypt(&ek, &code.encode(&vec![5, 6, 7]));
+            let c2 = AbstractPaillier::encrypt(&ek, &code.encode(&vec![1, 2, 3]));
+
+            let c = AbstractPaillier::sub(&ek, &c1, &c2);
+            let m: Vec<_> = code.decode(&AbstractPaillier::decrypt(&dk, &c));
+            assert_eq!(m, vec![4, 4, 4]);
+
+            let c = AbstractPaillier::sub(&eek, &vec![10_u64, 10, 10], &c2);
+            let m: Vec<_> = code.decode(&AbstractPaillier::decrypt(&dk, &c));
+            assert_eq!(m, vec![9, 8, 7]);
+
+            let c = AbstractPaillier::try_sub(&ek, &c1, &c2).unwrap();
+            let m: Vec<_> = code.decode(&AbstractPaillier::decrypt(&dk, &c));
+            assert_eq!(m, vec![4, 4, 4]);
+        }
+
+        #[test]
+        fn test_layout_mismatch() {
+            let (ek, _) = test_keypair().keys();
+
+            let c1 = AbstractPaillier::encrypt(&ek, &Code::new(3, 16).encode(&vec![1, 2, 3]));
+            let c2 = AbstractPaillier::encrypt(&ek, &Code::new(2, 24).encode(&vec![1, 2]));
+            let c3 = AbstractPaillier::encrypt(&ek, &Code::new(3, 24).encode(&vec![1, 2, 3]));
+            for c in [&c2, &c3] {
+                let res = AbstractPaillier::try_add(&ek, &c1, c);
+                assert_eq!(res.err(), Some(Error::InvalidCiphertext));
+                let res = AbstractPaillier::try_sub(&ek, &c1, c);
+                assert_eq!(res.err(), Some(Error::InvalidCiphertext));
+            }
+        }
+
+        #[test]
+        #[should_panic(expected = "vectors of different layouts")]
+        fn test_layout_mismatch_add() {
+            let (ek, _) = test_keypair().keys();
+
+            let c1 = AbstractPaillier::encrypt(&ek, &Code::new(3, 16).encode(&vec![1, 2, 3]));
+            let c2 = AbstractPaillier::encrypt(&ek, &Code::new(3, 24).encode(&vec![1, 2, 3]));
+            AbstractPaillier::add(&ek, &c1, &c2);
+        }
+
+        #[test]
+        fn test_validated_addition() {
+            let (ek, dk) = test_keypair().keys();
+
+            let code = Code::new(3, 16);
+
+            let c1 = AbstractPaillier::encrypt(&ek, &code.encode(&vec![1, 2, 3]));
+            let c1: ValidatedCiphertext<_, _> = AbstractPaillier::validate(&ek, &c1).unwrap();
+            let c2 = AbstractPaillier::encrypt(&ek, &code.encode(&vec![1, 2, 3]));
+            let c2: ValidatedCiphertext<_, _> = AbstractPaillier::validate(&ek, &c2).unwrap();
+
+            let c = AbstractPaillier::add(&ek, &c1, &c2);
+            let m: Vec<_> = code.decode(&AbstractPaillier::decrypt(&dk, &c));
+            assert_eq!(m, vec![2, 4, 6]);
+        }
+
+        #[test]
+        fn test_decode_overflow() {
+            let (ek, dk) = test_keypair().keys();
+
+            let code = Code::new(3, 16);
+
+            // a carry out of the first component is detected; carries between components are
+            // not, see `try_decode`
+            let m1 = code.encode(&vec![0xffff, 0, 0]);
+            let c1 = AbstractPaillier::encrypt(&ek, &m1);
+            let m2 = code.encode(&vec![1, 0, 0]);
+            let c2 = AbstractPaillier::encrypt(&ek, &m2);
+            let c = AbstractPaillier::add(&ek, &c1, &c2);
+            let m: Result<Vec<u64>, _> = code.try_decode(&AbstractPaillier::decrypt(&dk, &c));
+            assert_eq!(m, Err(Error::DecodeOverflow));
+        }
     }
 );
@@ -1,5 +1,6 @@
 //! Variout coding schemes to be used in conjuction with the core Paillier encryption scheme.
 
+pub mod float;
 pub mod integral;
 mod packing;
 
@@ -80,6 +81,30 @@ where
     }
 }
 
+impl<'a, 'b, E: 'b, M, CT, S, EK: 'a> TryEncryption<EncodingEncryptionKey<'a, 'b, EK, E>, M, CT>
+    for S
+where
+    M: EncodableType,
+    E: TryEncoder<M>,
+    S: TryEncryption<EK, E::Target, CT>,
+{
+    fn try_encrypt(ek: &EncodingEncryptionKey<EK, E>, m: &M) -> Result<CT, Error> {
+        S::try_encrypt(ek.key, &ek.encoder.try_encode(m)?)
+    }
+}
+
+impl<'a, 'b, D: 'b, M, CT, S, DK: 'a> TryDecryption<DecodingDecryptionKey<'a, 'b, DK, D>, CT, M>
+    for S
+where
+    M: EncodableType,
+    D: TryDecoder<M>,
+    S: TryDecryption<DK, CT, D::Source>,
+{
+    fn try_decrypt(dk: &DecodingDecryptionKey<DK, D>, c: &CT) -> Result<M, Error> {
+        dk.decoder.try_decode(&S::try_decrypt(dk.key, c)?)
+    }
+}
+
 impl<'a, 'b, E, EK: 'a, I, S>
     Addition<
         EncodingEncryptionKey<'a, 'b, EK, E>,
@@ -113,6 +138,143 @@ where
     }
 }
 
+impl<'a, 'b, E, EK: 'a, I, S>
+    TryAddition<
+        EncodingEncryptionKey<'a, 'b, EK, E>,
+        core::Ciphertext<I>,
+        core::Ciphertext<I>,
+        core::Ciphertext<I>,
+    > for S
+where
+    S: TryAddition<EK, core::Ciphertext<I>, core::Ciphertext<I>, core::Ciphertext<I>>,
+{
+    fn try_add(
+        ek: &EncodingEncryptionKey<EK, E>,
+        c1: &core::Ciphertext<I>,
+        c2: &core::Ciphertext<I>,
+    ) -> Result<core::Ciphertext<I>, Error> {
+        S::try_add(ek.key, c1, c2)
+    }
+}
+
+impl<'a, 'b, E, M, EK: 'a, CT, S> TryAddition<EncodingEncryptionKey<'a, 'b, EK, E>, CT, M, CT> for S
+where
+    M: EncodableType,
+    E: TryEncoder<M>,
+    S: TryEncryption<EK, E::Target, CT>,
+    S: TryAddition<EK, CT, CT, CT>,
+{
+    fn try_add(ek: &EncodingEncryptionKey<'a, 'b, EK, E>, c1: &CT, m2: &M) -> Result<CT, Error> {
+        let ref p2 = ek.encoder.try_encode(m2)?;
+        let ref c2 = S::try_encrypt(&ek.key, p2)?;
+        S::try_add(ek.key, c1, c2)
+    }
+}
+
+impl<'a, 'b, E, EK: 'a, I, S>
+    Negation<EncodingEncryptionKey<'a, 'b, EK, E>, core::Ciphertext<I>, core::Ciphertext<I>> for S
+where
+    S: Negation<EK, core::Ciphertext<I>, core::Ciphertext<I>>,
+{
+    fn neg(ek: &EncodingEncryptionKey<EK, E>, c: &core::Ciphertext<I>) -> core::Ciphertext<I> {
+        S::neg(ek.key, c)
+    }
+}
+
+impl<'a, 'b, E, EK: 'a, I, S>
+    Subtraction<
+        EncodingEncryptionKey<'a, 'b, EK, E>,
+        core::Ciphertext<I>,
+        core::Ciphertext<I>,
+        core::Ciphertext<I>,
+    > for S
+where
+    S: Subtraction<EK, core::Ciphertext<I>, core::Ciphertext<I>, core::Ciphertext<I>>,
+{
+    fn sub(
+        ek: &EncodingEncryptionKey<EK, E>,
+        c1: &core::Ciphertext<I>,
+        c2: &core::Ciphertext<I>,
+    ) -> core::Ciphertext<I> {
+        S::sub(ek.key, c1, c2)
+    }
+}
+
+impl<'a, 'b, E, M, EK: 'a, CT, S> Subtraction<EncodingEncryptionKey<'a, 'b, EK, E>, CT, M, CT> for S
+where
+    M: EncodableType,
+    E: Encoder<M>,
+    S: Encryption<EK, E::Target, CT>,
+    S: Subtraction<EK, CT, CT, CT>,
+{
+    fn sub(ek: &EncodingEncryptionKey<'a, 'b, EK, E>, c1: &CT, m2: &M) -> CT {
+        let ref p2 = ek.encoder.encode(m2);
+        let ref c2 = S::encrypt(&ek.key, p2);
+        S::sub(ek.key, c1, c2)
+    }
+}
+
+// subtracting a ciphertext from a plaintext is only supported for concrete ciphertext types, to
+// avoid conflicting with the implementation above
+
+impl<'a, 'b, E, M, EK: 'a, I, T, S>
+    Subtraction<
+        EncodingEncryptionKey<'a, 'b, EK, E>,
+        M,
+        integral::scalar::Ciphertext<I, T>,
+        integral::scalar::Ciphertext<I, T>,
+    > for S
+where
+    M: EncodableType,
+    E: Encoder<M>,
+    S: Encryption<EK, E::Target, integral::scalar::Ciphertext<I, T>>,
+    S: Subtraction<
+        EK,
+        integral::scalar::Ciphertext<I, T>,
+        integral::scalar::Ciphertext<I, T>,
+        integral::scalar::Ciphertext<I, T>,
+    >,
+{
+    fn sub(
+        ek: &EncodingEncryptionKey<'a, 'b, EK, E>,
+        m1: &M,
+        c2: &integral::scalar::Ciphertext<I, T>,
+    ) -> integral::scalar::Ciphertext<I, T> {
+        let ref p1 = ek.encoder.encode(m1);
+        let ref c1 = S::encrypt(&ek.key, p1);
+        S::sub(ek.key, c1, c2)
+    }
+}
+
+impl<'a, 'b, E, M, EK: 'a, I, T, S>
+    Subtraction<
+        EncodingEncryptionKey<'a, 'b, EK, E>,
+        M,
+        integral::vector::Ciphertext<I, T>,
+        integral::vector::Ciphertext<I, T>,
+    > for S
+where
+    M: EncodableType,
+    E: Encoder<M>,
+    S: Encryption<EK, E::Target, integral::vector::Ciphertext<I, T>>,
+    S: Subtraction<
+        EK,
+        integral::vector::Ciphertext<I, T>,
+        integral::vector::Ciphertext<I, T>,
+        integral::vector::Ciphertext<I, T>,
+    >,
+{
+    fn sub(
+        ek: &EncodingEncryptionKey<'a, 'b, EK, E>,
+        m1: &M,
+        c2: &integral::vector::Ciphertext<I, T>,
+    ) -> integral::vector::Ciphertext<I, T> {
+        let ref p1 = ek.encoder.encode(m1);
+        let ref c1 = S::encrypt(&ek.key, p1);
+        S::sub(ek.key, c1, c2)
+    }
+}
+
 // maybe this could work if we didn't parameterise over S but stuck to a concrete type instead
 // impl<'a, 'b, E, M, EK: 'a, CT, S> Addition<EncodingEncryptionKey<'a, 'b, EK, E>, M, CT, CT> for S
 // where
@@ -140,3 +302,16 @@ where
         S::mul(ek.key, c1, p2)
     }
 }
+
+impl<'a, 'b, E, M: 'b, CT, S, EK: 'a>
+    TryMultiplication<EncodingEncryptionKey<'a, 'b, EK, E>, CT, M, CT> for S
+where
+    M: EncodableType,
+    E: TryEncoder<M>,
+    S: TryMultiplication<EK, CT, E::Target, CT>,
+{
+    fn try_mul(ek: &EncodingEncryptionKey<EK, E>, c1: &CT, m2: &M) -> Result<CT, Error> {
+        let ref p2 = ek.encoder.try_encode(m2)?;
+        S::try_mul(ek.key, c1, p2)
+    }
+}
@@ -1,4 +1,5 @@
-use crate::arithimpl::traits::ConvertFrom;
+use crate::arithimpl::traits::{ConvertFrom, NumberTests, TryConvertFrom};
+use crate::Error;
 use num_traits::One;
 use std::ops::{Add, Rem, Shl, Shr};
 
@@ -42,3 +43,32 @@ where
     components.reverse();
     components
 }
+
+pub fn try_unpack<I, T>(
+    mut packed_components: I,
+    component_count: usize,
+    component_size: usize,
+) -> Result<Vec<T>, Error>
+where
+    T: TryConvertFrom<I>,
+    I: One,
+    I: NumberTests,
+    I: Shl<usize, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'a> &'a I: Shr<usize, Output = I>,
+{
+    let mask = I::one() << component_size;
+    let mut components: Vec<T> = vec![];
+    for _ in 0..component_count {
+        let raw_component = &packed_components % &mask;
+        let component = T::_try_from(&raw_component)?;
+        components.push(component);
+        packed_components = &packed_components >> component_size;
+    }
+    // anything left over means the packed value no longer fits the layout
+    if !NumberTests::is_zero(&packed_components) {
+        return Err(Error::DecodeOverflow);
+    }
+    components.reverse();
+    Ok(components)
+}
@@ -8,13 +8,19 @@ pub struct DecryptionKey<I> {
     p: I, // first prime
     q: I, // second prime
     n: I, // the modulus (also in public key)
+    nn: I,
     pp: I,
     pminusone: I,
     qq: I,
     qminusone: I,
     pinvq: I,
+    ppinvqq: I,
     hp: I,
     hq: I,
+    dp: I, // n^-1 mod p-1, for taking n-th roots modulo p
+    dq: I, // n^-1 mod q-1
+    ep: I, // n mod p(p-1), the order of Z*_{p^2}, for computing r^n modulo p^2
+    eq: I, // n mod q(q-1)
 }
 
 impl<I> crate::traits::DecryptionKey for DecryptionKey<I> {}
@@ -23,7 +29,7 @@ impl<'kp, I> From<&'kp Keypair<I>> for DecryptionKey<I>
 where
     I: Clone,
     I: One,
-    I: ModInv,
+    I: TryModInv,
     for<'a> &'a I: Sub<I, Output = I>,
     for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
     for<'b> I: Sub<&'b I, Output = I>,
@@ -31,12 +37,30 @@ where
     for<'b> I: Div<&'b I, Output = I>,
 {
     fn from(keypair: &'kp Keypair<I>) -> DecryptionKey<I> {
+        DecryptionKey::try_from_keypair(keypair).expect("invalid keypair")
+    }
+}
+
+impl<I> DecryptionKey<I>
+where
+    I: Clone,
+    I: One,
+    I: TryModInv,
+    for<'a> &'a I: Sub<I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'b> I: Sub<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+    for<'b> I: Div<&'b I, Output = I>,
+{
+    /// Derive the decryption key, failing with `NotInvertible` if `keypair` does not hold
+    /// suitable primes.
+    pub fn try_from_keypair(keypair: &Keypair<I>) -> Result<DecryptionKey<I>, Error> {
         let ref p = keypair.p;
         let ref q = keypair.q;
         let ref pp = p * p;
         let ref qq = q * q;
         let ref n = p * q;
-        DecryptionKey {
+        Ok(DecryptionKey {
             p: p.clone(), // TODO store ref to keypair instead
             q: q.clone(),
 
@@ -46,12 +70,123 @@ where
             qq: qq.clone(),
             qminusone: q - I::one(),
 
-            pinvq: I::modinv(p, q),
-            hp: h(p, pp, n),
-            hq: h(q, qq, n),
+            pinvq: I::try_modinv(p, q)?,
+            ppinvqq: I::try_modinv(pp, qq)?,
+            hp: h(p, pp, n)?,
+            hq: h(q, qq, n)?,
+
+            dp: I::try_modinv(n, &(p - I::one()))?,
+            dq: I::try_modinv(n, &(q - I::one()))?,
+
+            ep: n.clone() % &(p * &(p - I::one())),
+            eq: n.clone() % &(q * &(q - I::one())),
 
             n: n.clone(),
+            nn: pp * qq,
+        })
+    }
+}
+
+impl<'dk, I> From<&'dk DecryptionKey<I>> for Keypair<I>
+where
+    I: Clone,
+{
+    fn from(dk: &'dk DecryptionKey<I>) -> Keypair<I> {
+        Keypair {
+            p: dk.p.clone(),
+            q: dk.q.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<I> Serialize for DecryptionKey<I>
+where
+    I: fmt::Display,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        KeypairRepr {
+            p: to_repr(&self.p),
+            q: to_repr(&self.q),
         }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, I> Deserialize<'de> for DecryptionKey<I>
+where
+    Keypair<I>: Deserialize<'de>,
+    I: Clone,
+    I: One,
+    I: TryModInv,
+    for<'a> &'a I: Sub<I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'b> I: Sub<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+    for<'b> I: Div<&'b I, Output = I>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // only the primes are stored; everything else is recomputed
+        let keypair = Keypair::deserialize(deserializer)?;
+        DecryptionKey::try_from_keypair(&keypair).map_err(de::Error::custom)
+    }
+}
+
+impl<I> DecryptionKey<I>
+where
+    I: One,
+    I: Samplable,
+    I: ModPow,
+    I: ModPowSec,
+    I: NumberTests,
+    for<'a> &'a I: Add<I, Output = I>,
+    for<'b> I: Add<&'b I, Output = I>,
+    for<'a> &'a I: Sub<I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a> &'a I: Mul<I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'b> I: Div<&'b I, Output = I>,
+    for<'a> I: Rem<&'a I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+{
+    fn decrypt_crt(&self, c: &I) -> I {
+        // in hardened mode the ciphertext is first blinded by a fresh encryption of zero, so
+        // that the exponentiations below never operate on a value chosen by an adversary
+        let blinded;
+        let c = if cfg!(feature = "hardened") {
+            blinded = (c * self.sample_rn()) % &self.nn;
+            &blinded
+        } else {
+            c
+        };
+        // process using p
+        let cp = modpow_secret(c, &self.pminusone, &self.pp);
+        let lp = l(&cp, &self.p);
+        let mp = (&lp * &self.hp) % &self.p;
+        // process using q
+        let cq = modpow_secret(c, &self.qminusone, &self.qq);
+        let lq = l(&cq, &self.q);
+        let mq = (&lq * &self.hq) % &self.q;
+        // perform CRT
+        crt(&mp, &mq, self)
+    }
+
+    /// Recover both the plaintext and the randomness used to produce ciphertext `c`.
+    ///
+    /// Since `c = (1+n)^m * r^n mod n^2` we have `c = r^n mod n`, from which `r` is found by
+    /// taking `n`-th roots modulo `p` and `q` separately. The ciphertext is assumed to be
+    /// well-formed; see `standard::EncryptionKey::validate_ciphertext`.
+    pub fn open(&self, c: &Ciphertext<I>) -> (Plaintext<I>, Randomness<I>) {
+        let m = self.decrypt_crt(&c.0);
+        // process using p
+        let rp = modpow_secret(&(&c.0 % &self.p), &self.dp, &self.p);
+        // process using q
+        let rq = modpow_secret(&(&c.0 % &self.q), &self.dq, &self.q);
+        // perform CRT
+        let r = crt(&rp, &rq, self);
+        (Plaintext(m), Randomness(r))
     }
 }
 
@@ -59,35 +194,124 @@ impl<I, S> Decryption<DecryptionKey<I>, Ciphertext<I>, Plaintext<I>> for S
 where
     S: AbstractScheme<BigInteger = I>,
     I: One,
+    I: Samplable,
     I: ModPow,
+    I: ModPowSec,
     I: NumberTests,
     for<'a> &'a I: Add<I, Output = I>,
     for<'b> I: Add<&'b I, Output = I>,
     for<'a> &'a I: Sub<I, Output = I>,
     for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a> &'a I: Mul<I, Output = I>,
     for<'b> I: Mul<&'b I, Output = I>,
     for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
     for<'b> I: Div<&'b I, Output = I>,
     for<'a> I: Rem<&'a I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
 {
     fn decrypt(dk: &DecryptionKey<I>, c: &Ciphertext<I>) -> Plaintext<I> {
-        // process using p
-        let cp = I::modpow(&c.0, &dk.pminusone, &dk.pp);
-        let lp = l(&cp, &dk.p);
-        let mp = (&lp * &dk.hp) % &dk.p;
+        Plaintext(dk.decrypt_crt(&c.0))
+    }
+}
+
+impl<I, S> Decryption<DecryptionKey<I>, ValidatedCiphertext<I>, Plaintext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Decryption<DecryptionKey<I>, Ciphertext<I>, Plaintext<I>>,
+{
+    fn decrypt(dk: &DecryptionKey<I>, c: &ValidatedCiphertext<I>) -> Plaintext<I> {
+        S::decrypt(dk, &c.0)
+    }
+}
+
+impl<I> DecryptionKey<I>
+where
+    I: Samplable,
+    I: ModPow,
+    I: ModPowSec,
+    I: One,
+    I: NumberTests,
+    for<'a> &'a I: Sub<I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'b> I: Add<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    /// Sample `r^n mod n^2` for a fresh `r`.
+    fn sample_rn(&self) -> I {
+        // same distribution as for the public key
+        let r = I::sample_below(&self.n);
+        // process using p, with the exponent reduced modulo the order of Z*_{p^2}
+        let rp = modpow_secret(&(&r % &self.pp), &self.ep, &self.pp);
         // process using q
-        let cq = I::modpow(&c.0, &dk.qminusone, &dk.qq);
-        let lq = l(&cq, &dk.q);
-        let mq = (&lq * &dk.hq) % &dk.q;
-        // perform CRT
-        Plaintext(crt(&mp, &mq, &dk))
+        let rq = modpow_secret(&(&r % &self.qq), &self.eq, &self.qq);
+        // perform CRT to obtain r^n mod n^2
+        crt_squared(&rp, &rq, self)
+    }
+}
+
+impl<I, S> Rerandomisation<DecryptionKey<I>, Ciphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    I: Samplable,
+    I: ModPow,
+    I: ModPowSec,
+    I: One,
+    I: NumberTests,
+    for<'a> &'a I: Sub<I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'b> I: Add<&'b I, Output = I>,
+    for<'a> &'a I: Mul<I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    fn rerandomise(dk: &DecryptionKey<I>, c: &Ciphertext<I>) -> Ciphertext<I> {
+        let rn = dk.sample_rn();
+        Ciphertext((&c.0 * rn) % &dk.nn)
+    }
+}
+
+impl<I, S> Encryption<DecryptionKey<I>, Plaintext<I>, Ciphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Rerandomisation<DecryptionKey<I>, Ciphertext<I>>,
+    I: One,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    fn encrypt(dk: &DecryptionKey<I>, m: &Plaintext<I>) -> Ciphertext<I> {
+        // here we assume that g = n+1
+        let nm = &m.0 * &dk.n;
+        let gx = (&nm + &I::one()) % &dk.nn;
+        Self::rerandomise(dk, &Ciphertext(gx))
+    }
+}
+
+impl<I, S> TryDecryption<DecryptionKey<I>, Ciphertext<I>, Plaintext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Decryption<DecryptionKey<I>, Ciphertext<I>, Plaintext<I>>,
+    I: One,
+    I: PartialOrd,
+    I: NumberTests,
+    I: EGCD,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+{
+    fn try_decrypt(dk: &DecryptionKey<I>, c: &Ciphertext<I>) -> Result<Plaintext<I>, Error> {
+        check_ciphertext(&c.0, &dk.n, &dk.nn)?;
+        Ok(S::decrypt(dk, c))
     }
 }
 
-fn h<I>(p: &I, pp: &I, n: &I) -> I
+fn h<I>(p: &I, pp: &I, n: &I) -> Result<I, Error>
 where
     I: One,
-    I: ModInv,
+    I: TryModInv,
     for<'a> &'a I: Sub<I, Output = I>,
     for<'b> I: Sub<&'b I, Output = I>,
     for<'b> I: Rem<&'b I, Output = I>,
@@ -103,8 +327,7 @@ where
     // compute L_p(.)
     let lp = l(&gp, p);
     // compute L_p(.)^{-1}
-    let hp = I::modinv(&lp, p);
-    hp
+    I::try_modinv(&lp, p)
 }
 
 fn crt<I>(mp: &I, mq: &I, dk: &DecryptionKey<I>) -> I
@@ -125,3 +348,20 @@ where
     let m = mp + (&u * &dk.p);
     m % &dk.n
 }
+
+fn crt_squared<I>(xp: &I, xq: &I, dk: &DecryptionKey<I>) -> I
+where
+    I: NumberTests,
+    for<'b> I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    let mut xq_minus_xp = (xq - xp) % &dk.qq;
+    if NumberTests::is_negative(&xq_minus_xp) {
+        xq_minus_xp = xq_minus_xp + &dk.qq;
+    }
+    let u = (xq_minus_xp * &dk.ppinvqq) % &dk.qq;
+    (&u * &dk.pp) + xp
+}
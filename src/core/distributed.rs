@@ -0,0 +1,549 @@
+//! Distributed generation of a Paillier modulus without a trusted dealer.
+//!
+//! Follows Boneh and Franklin: every party holds additive shares `p_i` and `q_i` of the primes,
+//! the modulus `n = (sum p_i)(sum q_i)` is computed using the BGW protocol over integer Shamir
+//! shares, and candidates are filtered using trial division followed by a distributed
+//! biprimality test. No party ever learns `p` or `q`. The resulting decryption exponent is
+//! additively shared, so all parties are needed to decrypt.
+//!
+//! The protocol is secure against honest-but-curious parties as long as a majority is honest, and
+//! requires at least three parties.
+
+use super::*;
+use crate::arithimpl::primes::{has_small_factor, jacobi};
+use crate::proof::Transcript;
+use num_traits::Zero;
+use std::ops::Shr;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+const DOMAIN: &str = "paillier.keygen.biprimality";
+
+// Number of rounds of the biprimality test, each of which lets a composite modulus through with
+// probability at most one half.
+const BIPRIMALITY_ROUNDS: usize = 40;
+
+// Statistical security parameter for hiding values in integer secret sharings.
+const STATISTICAL_SECURITY: usize = 80;
+
+struct Message<I> {
+    round: u64,
+    from: usize,
+    values: Vec<I>,
+}
+
+/// Endpoint of a single party in a network of parties connected by channels.
+pub struct Channel<I> {
+    pub index: usize, // position of the party, in `1..parties+1`
+    pub parties: usize,
+    senders: Vec<Sender<Message<I>>>,
+    receiver: Receiver<Message<I>>,
+    round: u64,
+    pending: Vec<Message<I>>,
+}
+
+/// Connect `parties` parties to each other.
+pub fn channels<I>(parties: usize) -> Vec<Channel<I>> {
+    let (senders, receivers): (Vec<_>, Vec<_>) = (0..parties).map(|_| channel()).unzip();
+    receivers
+        .into_iter()
+        .enumerate()
+        .map(|(i, receiver)| Channel {
+            index: i + 1,
+            parties: parties,
+            senders: senders.clone(),
+            receiver: receiver,
+            round: 0,
+            pending: vec![],
+        })
+        .collect()
+}
+
+impl<I> Channel<I> {
+    // Send `outgoing[j]` to party `j+1` and return the values received from each party in turn.
+    fn exchange(&mut self, outgoing: Vec<Vec<I>>) -> Vec<Vec<I>> {
+        for (sender, values) in self.senders.iter().zip(outgoing) {
+            let message = Message {
+                round: self.round,
+                from: self.index,
+                values: values,
+            };
+            sender.send(message).expect("party disconnected");
+        }
+
+        // parties may run ahead, so keep messages for later rounds aside
+        let mut incoming: Vec<Option<Vec<I>>> = (0..self.parties).map(|_| None).collect();
+        let mut received = 0;
+        let (current, later): (Vec<_>, Vec<_>) = self
+            .pending
+            .drain(..)
+            .partition(|message| message.round == self.round);
+        self.pending = later;
+        for message in current {
+            incoming[message.from - 1] = Some(message.values);
+            received += 1;
+        }
+        while received < self.parties {
+            let message = self.receiver.recv().expect("party disconnected");
+            if message.round == self.round {
+                incoming[message.from - 1] = Some(message.values);
+                received += 1;
+            } else {
+                self.pending.push(message);
+            }
+        }
+
+        self.round += 1;
+        incoming.into_iter().map(|values| values.unwrap()).collect()
+    }
+
+    // Send `values` to every party and return the values received from each party in turn.
+    fn broadcast(&mut self, values: Vec<I>) -> Vec<Vec<I>>
+    where
+        I: Clone,
+    {
+        let outgoing = (0..self.parties).map(|_| values.clone()).collect();
+        self.exchange(outgoing)
+    }
+}
+
+/// Additive share of the decryption exponent held by a single party.
+#[derive(Debug, Clone)]
+pub struct KeyShare<I> {
+    pub index: usize,
+    pub parties: usize,
+    n: I,
+    nn: I,
+    exponent: I, // additive share of delta * phi(n) * beta, possibly negative
+    theta: I,    // public value delta * phi(n) * beta mod n
+}
+
+impl<I> crate::traits::DecryptionKey for KeyShare<I> {}
+
+/// Partial decryption produced by a single party.
+#[derive(Debug, Clone)]
+pub struct PartialDecryption<I> {
+    pub index: usize,
+    value: I, // c^{exponent}
+}
+
+impl<I, S> Decryption<KeyShare<I>, Ciphertext<I>, PartialDecryption<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    I: Zero,
+    I: NumberTests,
+    I: ModPow,
+    I: ModPowSec,
+    I: ModInv,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+{
+    fn decrypt(share: &KeyShare<I>, c: &Ciphertext<I>) -> PartialDecryption<I> {
+        let value = if NumberTests::is_negative(&share.exponent) {
+            let ref exponent = &I::zero() - &share.exponent;
+            I::modinv(&modpow_secret(&c.0, exponent, &share.nn), &share.nn)
+        } else {
+            modpow_secret(&c.0, &share.exponent, &share.nn)
+        };
+        PartialDecryption {
+            index: share.index,
+            value: value,
+        }
+    }
+}
+
+impl<I> KeyShare<I>
+where
+    I: One,
+    I: ModInv,
+    for<'a> &'a I: Sub<I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Div<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    /// Combine the partial decryptions of all parties into the plaintext.
+    pub fn combine(&self, partials: &[PartialDecryption<I>]) -> Result<Plaintext<I>, Error> {
+        let mut u = I::one();
+        for index in 1..self.parties + 1 {
+            let partial = partials
+                .iter()
+                .find(|partial| partial.index == index)
+                .ok_or(Error::NotEnoughShares)?;
+            u = (u * &partial.value) % &self.nn;
+        }
+        // u = (1+n)^{m * theta} since the exponent is a multiple of phi(n)
+        let m = (l(&u, &self.n) * &I::modinv(&self.theta, &self.n)) % &self.n;
+        Ok(Plaintext(m))
+    }
+}
+
+/// Run the key generation protocol as the party at the end of `channel`.
+///
+/// Every party must call this concurrently with the same `bit_length`; the resulting modulus has
+/// at most `bit_length` bits. Panics unless there are at least three parties and `bit_length`
+/// leaves room for shares of a few bits each.
+pub fn generate<I>(
+    channel: &mut Channel<I>,
+    bit_length: usize,
+) -> (standard::EncryptionKey<I>, KeyShare<I>)
+where
+    I: Clone,
+    I: PartialOrd,
+    I: ToBytes + FromBytes,
+    I: From<u32> + From<u64>,
+    I: Zero + One + NumberTests,
+    I: Samplable,
+    I: BitManipulation,
+    I: ModPow,
+    I: ModPowSec,
+    I: EGCD,
+    I: Add<I, Output = I>,
+    I: Shr<usize, Output = I>,
+    for<'a> &'a I: Sub<I, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Add<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Div<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    let parties = channel.parties;
+    assert!(parties >= 3, "at least three parties are needed");
+    assert!(
+        bit_length / 2 >= bits(parties) + 2,
+        "bit length too small for the number of parties"
+    );
+    let first = channel.index == 1;
+    let ref one = I::one();
+    let ref delta = factorial::<I>(parties);
+
+    // shares are small enough for their sum to fit in half the bits
+    let share_bits = bit_length / 2 - bits(parties);
+    let mask_bits = 2 * bit_length + STATISTICAL_SECURITY;
+
+    loop {
+        // the first party makes p and q equal to 3 mod 4, as needed by the biprimality test
+        let pi = sample_share::<I>(share_bits, first);
+        let qi = sample_share::<I>(share_bits, first);
+        let (opened, _) = multiply(channel, &pi, &qi, &I::zero(), bit_length);
+        let ref n = opened / delta;
+        if has_small_factor(n) {
+            continue;
+        }
+
+        // n = pq for primes p and q passes every round, other moduli only with small probability
+        let ref exponent = if first {
+            (&(n + one) - &(&pi + &qi)) / &I::from(4_u64)
+        } else {
+            (&pi + &qi) / &I::from(4_u64)
+        };
+        let bases = match biprimality_bases(n) {
+            Some(bases) => bases,
+            None => continue,
+        };
+        let values = bases
+            .iter()
+            .map(|g| modpow_secret(g, exponent, n))
+            .collect();
+        let received = channel.broadcast(values);
+        let biprime = (0..BIPRIMALITY_ROUNDS).all(|round| {
+            let others = received[1..]
+                .iter()
+                .fold(I::one(), |acc, values| (acc * &values[round]) % n);
+            let ref own = received[0][round];
+            own == &others || own == &(n - &others)
+        });
+        if !biprime {
+            continue;
+        }
+
+        // also require gcd(n, p+q-1) = 1, revealing only a random multiple of p+q-1 modulo n
+        let ref psi = if first { &(&pi + &qi) - one } else { &pi + &qi };
+        let r = I::sample(bit_length + STATISTICAL_SECURITY);
+        let ref mask = n * &I::sample(mask_bits);
+        let (opened, _) = multiply(channel, &r, psi, mask, bit_length);
+        let z = (opened / delta) % n;
+        if &I::egcd(&z, n).0 != one {
+            continue;
+        }
+
+        // share delta * phi(n) * beta for random beta, revealing it only modulo n
+        let ref phi = if first {
+            &(n + one) - &(&pi + &qi)
+        } else {
+            &I::zero() - &(&pi + &qi)
+        };
+        let beta = I::sample(bit_length + STATISTICAL_SECURITY);
+        let ref mask = n * &I::sample(mask_bits);
+        let (opened, product) = multiply(channel, phi, &beta, mask, bit_length);
+        let mut theta = &opened % n;
+        if NumberTests::is_negative(&theta) {
+            theta = &theta + n;
+        }
+        if &I::egcd(&theta, n).0 != one {
+            continue;
+        }
+
+        let share = KeyShare {
+            index: channel.index,
+            parties: parties,
+            n: n.clone(),
+            nn: n * n,
+            exponent: lagrange::<I>(channel.index, parties, delta) * &product,
+            theta: theta,
+        };
+        return (standard::EncryptionKey::from_modulus(n.clone()), share);
+    }
+}
+
+/// Run the key generation protocol between `parties` simulated parties, each on its own thread.
+pub fn simulate<I>(
+    parties: usize,
+    bit_length: usize,
+) -> Vec<(standard::EncryptionKey<I>, KeyShare<I>)>
+where
+    I: Send + 'static,
+    I: Clone,
+    I: PartialOrd,
+    I: ToBytes + FromBytes,
+    I: From<u32> + From<u64>,
+    I: Zero + One + NumberTests,
+    I: Samplable,
+    I: BitManipulation,
+    I: ModPow,
+    I: ModPowSec,
+    I: EGCD,
+    I: Add<I, Output = I>,
+    I: Shr<usize, Output = I>,
+    for<'a> &'a I: Sub<I, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Add<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Div<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    let handles: Vec<_> = channels(parties)
+        .into_iter()
+        .map(|mut channel| thread::spawn(move || generate(&mut channel, bit_length)))
+        .collect();
+    handles
+        .into_iter()
+        .map(|handle| handle.join().expect("party failed"))
+        .collect()
+}
+
+// Multiply the secrets `sum a_i` and `sum b_i` of all parties using BGW, returning the opened
+// value `delta * (sum a_i * sum b_i + sum mask_i)` together with this party's share of the
+// product, which lies on a polynomial of degree `2t` with constant term the product itself.
+fn multiply<I>(channel: &mut Channel<I>, a: &I, b: &I, mask: &I, bit_length: usize) -> (I, I)
+where
+    I: Clone,
+    I: From<u64>,
+    I: Zero + One,
+    I: Samplable,
+    for<'a> &'a I: Sub<I, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'b> I: Add<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Div<&'b I, Output = I>,
+{
+    let parties = channel.parties;
+    let degree = (parties - 1) / 2;
+    // coefficients are large enough to statistically hide secrets of up to 2 * bit_length bits
+    let bits = 2 * bit_length + STATISTICAL_SECURITY;
+    let fa = share(a, degree, bits, parties);
+    let fb = share(b, degree, bits, parties);
+    let fh = share(mask, 2 * degree, 2 * bits, parties);
+    let outgoing = (0..parties)
+        .map(|j| vec![fa[j].clone(), fb[j].clone(), fh[j].clone()])
+        .collect();
+    let received = channel.exchange(outgoing);
+
+    let sum = |k: usize| {
+        received
+            .iter()
+            .fold(I::zero(), |acc, values| acc + &values[k])
+    };
+    let product = sum(0) * &sum(1);
+    let masked = &product + &sum(2);
+
+    let ref delta = factorial::<I>(parties);
+    let opened = channel
+        .broadcast(vec![masked])
+        .iter()
+        .enumerate()
+        .fold(I::zero(), |acc, (j, values)| {
+            acc + &(lagrange::<I>(j + 1, parties, delta) * &values[0])
+        });
+    (opened, product)
+}
+
+// Evaluate a random polynomial of the given degree with constant term `secret` at `1..parties+1`.
+fn share<I>(secret: &I, degree: usize, bits: usize, parties: usize) -> Vec<I>
+where
+    I: Clone,
+    I: From<u64>,
+    I: Zero,
+    I: Samplable,
+    for<'b> I: Add<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+{
+    let mut coefficients = vec![secret.clone()];
+    for _ in 0..degree {
+        coefficients.push(I::sample(bits));
+    }
+    (1..parties + 1)
+        .map(|j| {
+            let ref x = I::from(j as u64);
+            coefficients
+                .iter()
+                .rev()
+                .fold(I::zero(), |acc, c| acc * x + c)
+        })
+        .collect()
+}
+
+// Integer Lagrange coefficient `delta * prod_{j != i} j / (j - i)` for interpolating at zero from
+// the evaluations at `1..parties+1`.
+fn lagrange<I>(i: usize, parties: usize, delta: &I) -> I
+where
+    I: Clone,
+    I: From<u64>,
+    I: Zero,
+    for<'a> &'a I: Sub<I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Div<&'b I, Output = I>,
+{
+    let mut numerator = delta.clone();
+    let mut denominator = I::from(1);
+    let mut negative = false;
+    for j in (1..parties + 1).filter(|&j| j != i) {
+        numerator = numerator * &I::from(j as u64);
+        if j > i {
+            denominator = denominator * &I::from((j - i) as u64);
+        } else {
+            denominator = denominator * &I::from((i - j) as u64);
+            negative = !negative;
+        }
+    }
+    // exact since delta = parties! is divisible by the denominator
+    let coefficient = numerator / &denominator;
+    if negative {
+        &I::zero() - coefficient
+    } else {
+        coefficient
+    }
+}
+
+// Sample an additive share of a prime; the first party's is 3 mod 4 and all others 0 mod 4.
+fn sample_share<I>(bits: usize, first: bool) -> I
+where
+    I: Samplable,
+    I: BitManipulation,
+{
+    let mut share = I::sample(bits);
+    share.set_bit(0, first);
+    share.set_bit(1, first);
+    share
+}
+
+// Public bases with Jacobi symbol one for the biprimality test, derived from `n` by hashing, or
+// `None` if a base sharing a factor with `n` was found.
+fn biprimality_bases<I>(n: &I) -> Option<Vec<I>>
+where
+    I: Clone,
+    I: PartialEq,
+    I: PartialOrd,
+    I: ToBytes + FromBytes,
+    I: From<u32> + From<u64>,
+    I: Zero + One + NumberTests,
+    I: Add<I, Output = I>,
+    I: Shr<usize, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+{
+    let mut bases = Vec::with_capacity(BIPRIMALITY_ROUNDS);
+    let mut transcript = Transcript::new(DOMAIN);
+    transcript.append_integer("n", n);
+    while bases.len() < BIPRIMALITY_ROUNDS {
+        let g = transcript.challenge_below("base", n);
+        match jacobi(&g, n) {
+            1 => bases.push(g),
+            0 => return None,
+            _ => continue,
+        }
+    }
+    Some(bases)
+}
+
+fn bits(k: usize) -> usize {
+    (usize::BITS - k.leading_zeros()) as usize
+}
+
+fn factorial<I>(k: usize) -> I
+where
+    I: From<u64>,
+    for<'b> I: Mul<&'b I, Output = I>,
+{
+    (1..k + 1).fold(I::from(1), |acc, i| acc * &I::from(i as u64))
+}
+
+bigint!(
+    I,
+    #[cfg(test)]
+    mod tests {
+
+        use super::I;
+        use crate::arithimpl::primes::{is_prime, jacobi};
+        use crate::core::distributed::*;
+        use crate::AbstractPaillier;
+        use crate::Error;
+
+        #[test]
+        fn test_jacobi() {
+            let n = I::from(45_u64);
+            let expected = [0, 1, -1, 0, 1, 0, 0, -1, -1, 0, 0, 1, 0, -1, 1];
+            for (a, symbol) in expected.iter().enumerate() {
+                assert_eq!(jacobi(&I::from(a as u64), &n), *symbol);
+            }
+        }
+
+        #[test]
+        fn test_distributed_keygen() {
+            let keys: Vec<(standard::EncryptionKey<I>, KeyShare<I>)> = simulate(3, 128);
+            let ref ek = keys[0].0;
+            assert!(keys.iter().all(|(other, _)| other.n == ek.n));
+            assert!(!is_prime(&ek.n));
+
+            let m = Plaintext::from(10);
+            let c = AbstractPaillier::encrypt(ek, &m);
+            let partials: Vec<PartialDecryption<I>> = keys
+                .iter()
+                .map(|(_, share)| AbstractPaillier::decrypt(share, &c))
+                .collect();
+            for (_, share) in keys.iter() {
+                assert_eq!(share.combine(&partials), Ok(m.clone()));
+            }
+
+            // all parties are needed
+            assert_eq!(
+                keys[0].1.combine(&partials[1..]),
+                Err(Error::NotEnoughShares)
+            );
+        }
+
+        #[test]
+        #[should_panic(expected = "bit length too small for the number of parties")]
+        fn test_distributed_keygen_small_bit_length() {
+            let mut channel = channels::<I>(3).remove(0);
+            generate(&mut channel, 6);
+        }
+    }
+);
@@ -0,0 +1,354 @@
+//! Damgård-Jurik generalisation with plaintext space `Z_{n^s}` and ciphertext space `Z*_{n^(s+1)}`.
+//!
+//! For `s = 1` this coincides with the standard scheme, while larger `s` give room for
+//! correspondingly larger plaintexts at the same key size.
+
+use super::*;
+use num_traits::Zero;
+
+/// Encryption key that may be shared publicly.
+#[derive(Debug, Clone)]
+pub struct EncryptionKey<I> {
+    pub n: I,     // the modulus
+    pub s: usize, // the exponent of the plaintext space
+    ns: I,        // the modulus to the power s
+    nsplusone: I, // the modulus to the power s+1
+}
+
+impl<I> crate::traits::EncryptionKey for EncryptionKey<I> {}
+
+impl<'kp, I> From<(&'kp Keypair<I>, usize)> for EncryptionKey<I>
+where
+    I: One,
+    I: Clone,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+{
+    fn from((keypair, s): (&'kp Keypair<I>, usize)) -> EncryptionKey<I> {
+        assert!(s >= 1, "exponent must be positive");
+        let ref modulus = &keypair.p * &keypair.q;
+        let ns = pow(modulus, s);
+        EncryptionKey {
+            n: modulus.clone(),
+            s: s,
+            nsplusone: &ns * modulus,
+            ns: ns,
+        }
+    }
+}
+
+/// Decryption key that should be kept private.
+#[derive(Debug, Clone)]
+pub struct DecryptionKey<I> {
+    n: I,         // the modulus (also in public key)
+    s: usize,     // the exponent of the plaintext space
+    ns: I,        // the modulus to the power s
+    nsplusone: I, // the modulus to the power s+1
+    lambda: I,    // fixed at lambda = (p-1)*(q-1)
+    mu: I,        // fixed at lambda^{-1} mod n^s
+}
+
+impl<I> crate::traits::DecryptionKey for DecryptionKey<I> {}
+
+impl<'kp, I> From<(&'kp Keypair<I>, usize)> for DecryptionKey<I>
+where
+    I: One,
+    I: Clone,
+    I: ModInv,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+{
+    fn from((keypair, s): (&'kp Keypair<I>, usize)) -> DecryptionKey<I> {
+        assert!(s >= 1, "exponent must be positive");
+        let ref one = I::one();
+        let ref modulus = &keypair.p * &keypair.q;
+        let ns = pow(modulus, s);
+        let lambda = (&keypair.p - one) * (&keypair.q - one);
+        let mu = I::modinv(&lambda, &ns);
+        DecryptionKey {
+            n: modulus.clone(),
+            s: s,
+            nsplusone: &ns * modulus,
+            ns: ns,
+            lambda: lambda,
+            mu: mu,
+        }
+    }
+}
+
+impl<I, S> Rerandomisation<EncryptionKey<I>, Ciphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    I: Samplable,
+    I: ModPow,
+    for<'a> &'a I: Mul<I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    fn rerandomise(ek: &EncryptionKey<I>, c: &Ciphertext<I>) -> Ciphertext<I> {
+        let r = I::sample_below(&ek.n);
+        let d = (&c.0 * I::modpow(&r, &ek.ns, &ek.nsplusone)) % &ek.nsplusone;
+        Ciphertext(d)
+    }
+}
+
+impl<I, S> Encryption<EncryptionKey<I>, Plaintext<I>, Ciphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Rerandomisation<EncryptionKey<I>, Ciphertext<I>>,
+    I: One,
+    I: ModPow,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+{
+    fn encrypt(ek: &EncryptionKey<I>, m: &Plaintext<I>) -> Ciphertext<I> {
+        // here we assume that g = n+1
+        let g = &ek.n + &I::one();
+        let gm = I::modpow(&g, &m.0, &ek.nsplusone);
+        Self::rerandomise(ek, &Ciphertext(gm))
+    }
+}
+
+impl<I, S> Addition<EncryptionKey<I>, Ciphertext<I>, Ciphertext<I>, Ciphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    fn add(ek: &EncryptionKey<I>, c1: &Ciphertext<I>, c2: &Ciphertext<I>) -> Ciphertext<I> {
+        let c = (&c1.0 * &c2.0) % &ek.nsplusone;
+        Ciphertext(c)
+    }
+}
+
+impl<I, S> Multiplication<EncryptionKey<I>, Ciphertext<I>, Plaintext<I>, Ciphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    I: ModPow,
+    I: NumberTests,
+    for<'b> I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+{
+    fn mul(ek: &EncryptionKey<I>, c1: &Ciphertext<I>, m2: &Plaintext<I>) -> Ciphertext<I> {
+        let c = if NumberTests::is_negative(&m2.0) {
+            // use exponent n^s - k for multiplier -k
+            let k = (&m2.0 % &ek.ns) + &ek.ns;
+            I::modpow(&c1.0, &k, &ek.nsplusone)
+        } else {
+            I::modpow(&c1.0, &m2.0, &ek.nsplusone)
+        };
+        Ciphertext(c)
+    }
+}
+
+impl<I, S> Decryption<DecryptionKey<I>, Ciphertext<I>, Plaintext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    I: From<u64>,
+    I: Clone,
+    I: Zero + One,
+    I: Samplable,
+    I: ModPow,
+    I: ModPowSec,
+    I: ModInv,
+    for<'a> &'a I: Mul<I, Output = I>,
+    for<'a> &'a I: Sub<I, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Div<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    fn decrypt(dk: &DecryptionKey<I>, c: &Ciphertext<I>) -> Plaintext<I> {
+        // in hardened mode the ciphertext is first blinded by a fresh encryption of zero, as for
+        // the Paillier scheme, since lambda is also the secret exponent here
+        let blinded;
+        let c = if cfg!(feature = "hardened") {
+            let r = I::sample_below(&dk.n);
+            blinded = (&c.0 * I::modpow(&r, &dk.ns, &dk.nsplusone)) % &dk.nsplusone;
+            &blinded
+        } else {
+            &c.0
+        };
+        // u = (1+n)^(m * lambda) mod n^(s+1)
+        let u = modpow_secret(c, &dk.lambda, &dk.nsplusone);
+        let mlambda = extract(&u, &dk.n, dk.s);
+        let m = (mlambda * &dk.mu) % &dk.ns;
+        Plaintext(m)
+    }
+}
+
+// Recover `i` from `a = (1+n)^i mod n^(s+1)` using the recursive algorithm from Section 4.1 of
+// the Damgård-Jurik paper, lifting `i mod n^j` one power of `n` at a time.
+fn extract<I>(a: &I, n: &I, s: usize) -> I
+where
+    I: From<u64>,
+    I: Clone,
+    I: Zero + One,
+    I: ModInv,
+    for<'a> &'a I: Sub<I, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Div<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    let ref one = I::one();
+    // powers[j] = n^j
+    let mut powers = vec![I::one()];
+    for j in 0..s + 1 {
+        let next = &powers[j] * n;
+        powers.push(next);
+    }
+
+    let mut i = I::zero();
+    for j in 1..s + 1 {
+        let ref nj = powers[j];
+        let mut t1 = l(&(a % &powers[j + 1]), n);
+        let mut t2 = i.clone();
+        let mut factorial = I::one();
+        for k in 2..j + 1 {
+            // all values are kept non-negative by working modulo n^j
+            i = (&(&i + nj) - one) % nj;
+            t2 = (t2 * &i) % nj;
+            factorial = factorial * &I::from(k as u64);
+            let term = (((&t2 * &powers[k - 1]) % nj) * &I::modinv(&factorial, nj)) % nj;
+            t1 = (&(&t1 + nj) - &term) % nj;
+        }
+        i = t1;
+    }
+    i
+}
+
+fn pow<I>(x: &I, e: usize) -> I
+where
+    I: One,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+{
+    let mut result = I::one();
+    for _ in 0..e {
+        result = &result * x;
+    }
+    result
+}
+
+// the tests work modulo powers of n up to the fourth
+bigint!(
+    I,
+    fixed = crate::Fixed4096,
+    #[cfg(test)]
+    mod tests {
+
+        use super::I;
+        use crate::core::dj::*;
+        use crate::integral;
+        use crate::AbstractPaillier;
+
+        fn test_keypair() -> Keypair<I> {
+            let p = str::parse("148677972634832330983979593310074301486537017973460461278300587514468301043894574906886127642530475786889672304776052879927627556769456140664043088700743909632312483413393134504352834240399191134336344285483935856491230340093391784574980688823380828143810804684752914935441384845195613674104960646037368551517").unwrap();
+            let q = str::parse("158741574437007245654463598139927898730476924736461654463975966787719309357536545869203069369466212089132653564188443272208127277664424448947476335413293018778018615899291704693105620242763173357203898195318179150836424196645745308205164116144020613415407736216097185962171301808761138424668335445923774195463").unwrap();
+            Keypair { p: p, q: q }
+        }
+
+        fn test_keys(s: usize) -> (EncryptionKey<I>, DecryptionKey<I>) {
+            let ref keypair = test_keypair();
+            (
+                EncryptionKey::from((keypair, s)),
+                DecryptionKey::from((keypair, s)),
+            )
+        }
+
+        #[test]
+        fn test_correct_encryption_decryption() {
+            for s in 1..4 {
+                let (ek, dk) = test_keys(s);
+
+                let m = Plaintext::from(10);
+                let c = AbstractPaillier::encrypt(&ek, &m);
+                assert_eq!(AbstractPaillier::decrypt(&dk, &c), m);
+
+                // plaintexts may be as large as n^s - 1
+                let m = Plaintext(&ek.ns - &I::from(1_u64));
+                let c = AbstractPaillier::encrypt(&ek, &m);
+                assert_eq!(AbstractPaillier::decrypt(&dk, &c), m);
+            }
+        }
+
+        #[test]
+        fn test_correct_addition() {
+            let (ek, dk) = test_keys(2);
+
+            let m1 = Plaintext(&ek.n + &I::from(10_u64));
+            let c1 = AbstractPaillier::encrypt(&ek, &m1);
+            let m2 = Plaintext(&ek.n * &I::from(20_u64));
+            let c2 = AbstractPaillier::encrypt(&ek, &m2);
+
+            let c = AbstractPaillier::add(&ek, &c1, &c2);
+            let m = AbstractPaillier::decrypt(&dk, &c);
+            assert_eq!(m, Plaintext(&(&ek.n * &I::from(21_u64)) + &I::from(10_u64)));
+        }
+
+        #[test]
+        fn test_correct_multiplication() {
+            let (ek, dk) = test_keys(2);
+
+            let m1 = Plaintext(&ek.n + &I::from(10_u64));
+            let c1 = AbstractPaillier::encrypt(&ek, &m1);
+            let m2 = Plaintext::from(20);
+
+            let c = AbstractPaillier::mul(&ek, &c1, &m2);
+            let m = AbstractPaillier::decrypt(&dk, &c);
+            assert_eq!(
+                m,
+                Plaintext(&(&ek.n * &I::from(20_u64)) + &I::from(200_u64))
+            );
+
+            let c = AbstractPaillier::mul(&ek, &c1, &Plaintext(-I::from(1_u64)));
+            let m = AbstractPaillier::decrypt(&dk, &c);
+            assert_eq!(m, Plaintext(&(&ek.ns - &ek.n) - &I::from(10_u64)));
+        }
+
+        #[test]
+        fn test_rerandomisation() {
+            let (ek, dk) = test_keys(2);
+
+            let m = Plaintext::from(10);
+            let c1 = AbstractPaillier::encrypt(&ek, &m);
+            let c2 = AbstractPaillier::rerandomise(&ek, &c1);
+            assert!(c1.0 != c2.0);
+            assert_eq!(AbstractPaillier::decrypt(&dk, &c2), m);
+        }
+
+        #[test]
+        #[cfg(feature = "hardened")]
+        fn test_hardened_decryption() {
+            let (ek, dk) = test_keys(2);
+
+            // blinding must not change the plaintext, including for ciphertexts of zero
+            for m in [Plaintext::from(0), Plaintext(&ek.ns - &I::from(1_u64))] {
+                let c = AbstractPaillier::encrypt(&ek, &m);
+                assert_eq!(AbstractPaillier::decrypt(&dk, &c), m);
+            }
+
+            // the trivial encryption of zero still decrypts to zero once blinded
+            let c = Ciphertext(I::from(1_u64));
+            assert_eq!(AbstractPaillier::decrypt(&dk, &c), Plaintext::from(0));
+        }
+
+        #[test]
+        fn test_vector_packing() {
+            let (ek, dk) = test_keys(2);
+
+            // 40 components of 64 bits do not fit below a 2048 bit modulus
+            let code = integral::Code::new(40, 64);
+            let m: Vec<u64> = (0..40).collect();
+
+            let p = code.encode(&m);
+            let c = AbstractPaillier::encrypt(&ek, &p);
+            let c = AbstractPaillier::add(&ek, &c, &c);
+            let recovered: Vec<u64> = code.decode(&AbstractPaillier::decrypt(&dk, &c));
+            assert_eq!(recovered, m.iter().map(|x| 2 * x).collect::<Vec<u64>>());
+        }
+    }
+);
@@ -1,6 +1,7 @@
 //! Slower generic encryption supporting an arbitrary generator.
 
 use super::*;
+use crate::proof::ProofKey;
 
 /// Encryption key that may be shared publicly.
 #[derive(Debug, Clone)]
@@ -41,6 +42,52 @@ where
     }
 }
 
+impl<I> EncryptionKey<I>
+where
+    I: Clone,
+    I: One,
+    I: PartialOrd,
+    I: NumberTests,
+    I: EGCD,
+{
+    /// Check that ciphertext `c` is an element of `Z*_{n^2}`.
+    pub fn validate_ciphertext(&self, c: &Ciphertext<I>) -> Result<ValidatedCiphertext<I>, Error> {
+        check_ciphertext(&c.0, &self.n, &self.nn)?;
+        Ok(ValidatedCiphertext(c.clone()))
+    }
+}
+
+impl<I> ProofKey<I> for EncryptionKey<I>
+where
+    I: ModPow,
+{
+    fn n(&self) -> &I {
+        &self.n
+    }
+
+    fn nn(&self) -> &I {
+        &self.nn
+    }
+
+    fn gm(&self, m: &I) -> I {
+        I::modpow(&self.g, m, &self.nn)
+    }
+}
+
+impl<I, S> Validation<EncryptionKey<I>, Ciphertext<I>, ValidatedCiphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    I: Clone,
+    I: One,
+    I: PartialOrd,
+    I: NumberTests,
+    I: EGCD,
+{
+    fn validate(ek: &EncryptionKey<I>, c: &Ciphertext<I>) -> Result<ValidatedCiphertext<I>, Error> {
+        ek.validate_ciphertext(c)
+    }
+}
+
 impl<I, S> Rerandomisation<EncryptionKey<I>, Ciphertext<I>> for S
 where
     S: AbstractScheme<BigInteger = I>,
@@ -68,6 +115,51 @@ where
     }
 }
 
+impl<I, S> EncryptionWithRandomness<EncryptionKey<I>, Plaintext<I>, Randomness<I>, Ciphertext<I>>
+    for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    I: Samplable,
+    I: ModPow,
+    I: One,
+    I: PartialOrd,
+    I: NumberTests,
+    I: EGCD,
+    I: Mul<I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    fn encrypt_with_randomness(
+        ek: &EncryptionKey<I>,
+        m: &Plaintext<I>,
+        r: &Randomness<I>,
+    ) -> Ciphertext<I> {
+        let gm = I::modpow(&ek.g, &m.0, &ek.nn);
+        let rn = I::modpow(&r.0, &ek.n, &ek.nn);
+        Ciphertext((gm * rn) % &ek.nn)
+    }
+
+    fn encrypt_returning_randomness(
+        ek: &EncryptionKey<I>,
+        m: &Plaintext<I>,
+    ) -> (Ciphertext<I>, Randomness<I>) {
+        let r = Randomness::sample(&ek.n);
+        (S::encrypt_with_randomness(ek, m, &r), r)
+    }
+}
+
+impl<I, S> TryEncryption<EncryptionKey<I>, Plaintext<I>, Ciphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Encryption<EncryptionKey<I>, Plaintext<I>, Ciphertext<I>>,
+    I: PartialOrd,
+    I: NumberTests,
+{
+    fn try_encrypt(ek: &EncryptionKey<I>, m: &Plaintext<I>) -> Result<Ciphertext<I>, Error> {
+        check_plaintext(&m.0, &ek.n)?;
+        Ok(S::encrypt(ek, m))
+    }
+}
+
 bigint!(
     I,
     #[cfg(test)]
@@ -97,5 +189,18 @@ bigint!(
             let recovered_m = AbstractPaillier::decrypt(&dk, &c);
             assert_eq!(recovered_m, m);
         }
+
+        #[test]
+        fn test_encryption_with_randomness() {
+            let ref keypair = test_keypair();
+            let ek: generic::EncryptionKey<_> = generic::EncryptionKey::from(keypair);
+            let dk: crt::DecryptionKey<_> = crt::DecryptionKey::from(keypair);
+
+            let m = Plaintext::from(10);
+            let (c, r) = AbstractPaillier::encrypt_returning_randomness(&ek, &m);
+            let d = AbstractPaillier::encrypt_with_randomness(&ek, &m, &r);
+            assert_eq!(c.0, d.0);
+            assert_eq!(AbstractPaillier::decrypt(&dk, &d), m);
+        }
     }
 );
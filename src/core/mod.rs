@@ -3,9 +3,17 @@
 use crate::traits::*;
 
 use crate::arithimpl::traits::*;
+use crate::Error;
 use num_traits::One;
 use std::ops::{Add, Div, Mul, Rem, Sub};
 
+#[cfg(feature = "serde")]
+use crate::serialize::*;
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "serde")]
+use std::str::FromStr;
+
 /// Representation of a keypair from which encryption and decryption keys can be derived.
 pub struct Keypair<I> {
     pub p: I,
@@ -32,13 +40,89 @@ pub struct Plaintext<I>(pub I);
 #[derive(Debug, Clone)]
 pub struct Ciphertext<I>(pub I);
 
+/// Representation of encrypted message known to be an element of `Z*_{n^2}`.
+///
+/// Can only be obtained through validation against an encryption key, and is only meaningful
+/// for that key.
+#[derive(Debug, Clone)]
+pub struct ValidatedCiphertext<I>(Ciphertext<I>);
+
+impl<I> AsRef<Plaintext<I>> for Plaintext<I> {
+    fn as_ref(&self) -> &Plaintext<I> {
+        self
+    }
+}
+
+impl<I> AsRef<Ciphertext<I>> for Ciphertext<I> {
+    fn as_ref(&self) -> &Ciphertext<I> {
+        self
+    }
+}
+
+impl<I> AsRef<Ciphertext<I>> for ValidatedCiphertext<I> {
+    fn as_ref(&self) -> &Ciphertext<I> {
+        &self.0
+    }
+}
+
+impl<I> From<ValidatedCiphertext<I>> for Ciphertext<I> {
+    fn from(c: ValidatedCiphertext<I>) -> Ciphertext<I> {
+        c.0
+    }
+}
+
+/// Randomness used in encryption, known to be an element of `Z*_n`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Randomness<I>(I);
+
+impl<I> Randomness<I> {
+    /// Check that `r` is an element of `Z*_n`.
+    pub fn new(r: I, n: &I) -> Result<Randomness<I>, Error>
+    where
+        I: One,
+        I: PartialOrd,
+        I: NumberTests,
+        I: EGCD,
+    {
+        if NumberTests::is_negative(&r) || NumberTests::is_zero(&r) || &r >= n {
+            return Err(Error::InvalidRandomness);
+        }
+        if I::egcd(&r, n).0 != I::one() {
+            return Err(Error::InvalidRandomness);
+        }
+        Ok(Randomness(r))
+    }
+
+    /// Sample fresh randomness uniformly from `Z*_n`.
+    pub fn sample(n: &I) -> Randomness<I>
+    where
+        I: Samplable,
+        I: One,
+        I: PartialOrd,
+        I: NumberTests,
+        I: EGCD,
+    {
+        loop {
+            if let Ok(r) = Self::new(I::sample_below(n), n) {
+                return r;
+            }
+        }
+    }
+}
+
+impl<I> AsRef<I> for Randomness<I> {
+    fn as_ref(&self) -> &I {
+        &self.0
+    }
+}
+
 impl<I> DefaultKeys for Keypair<I>
 where
     // TODO clean up bounds
     I: From<u64>,
     I: Clone,
     I: Samplable,
-    I: ModInv,
+    I: TryModInv,
     I: One,
     I: Mul<Output = I>,
     for<'a> &'a I: Mul<I, Output = I>,
@@ -85,6 +169,90 @@ where
     }
 }
 
+#[cfg(feature = "serde")]
+impl<I> Serialize for Keypair<I>
+where
+    I: fmt::Display,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        KeypairRepr {
+            p: to_repr(&self.p),
+            q: to_repr(&self.q),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, I> Deserialize<'de> for Keypair<I>
+where
+    I: FromStr,
+    I: One,
+    I: PartialOrd,
+    I: EGCD,
+    for<'a> &'a I: Sub<I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = KeypairRepr::deserialize(deserializer)?;
+        let p: I = from_repr(&repr.p)?;
+        let q: I = from_repr(&repr.q)?;
+        // reject values from which no valid decryption key could be derived, which needs
+        // gcd(p, q) = 1 for the CRT and gcd(n, (p-1)(q-1)) = 1 for the inverses of n
+        let ref one = I::one();
+        if &p <= one || &q <= one || I::egcd(&p, &q).0 != *one {
+            return Err(de::Error::custom("invalid keypair"));
+        }
+        let ref phi = &(&p - I::one()) * &(&q - I::one());
+        if I::egcd(&(&p * &q), phi).0 != *one {
+            return Err(de::Error::custom("invalid keypair"));
+        }
+        Ok(Keypair { p: p, q: q })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<I> Serialize for Plaintext<I>
+where
+    I: fmt::Display,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&to_repr(&self.0))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, I> Deserialize<'de> for Plaintext<I>
+where
+    I: FromStr,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = String::deserialize(deserializer)?;
+        Ok(Plaintext(from_repr(&repr)?))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<I> Serialize for Ciphertext<I>
+where
+    I: fmt::Display,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&to_repr(&self.0))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, I> Deserialize<'de> for Ciphertext<I>
+where
+    I: FromStr,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = String::deserialize(deserializer)?;
+        Ok(Ciphertext(from_repr(&repr)?))
+    }
+}
+
 // impl<I, T> Encoding<T, Plaintext<I>> for Scheme<I>
 // where
 //     T: Copy,
@@ -114,10 +282,44 @@ where
     (u - I::one()) / n
 }
 
+// Check that `m` is a valid plaintext for modulus `n`, i.e. that `0 <= m < n`.
+pub(crate) fn check_plaintext<I>(m: &I, n: &I) -> Result<(), Error>
+where
+    I: PartialOrd,
+    I: NumberTests,
+{
+    if NumberTests::is_negative(m) || m >= n {
+        return Err(Error::PlaintextOutOfRange);
+    }
+    Ok(())
+}
+
+// Check that `c` is an element of `Z*_{n^2}`, i.e. that `0 < c < n^2` and `gcd(c, n) = 1`.
+pub(crate) fn check_ciphertext<I>(c: &I, n: &I, nn: &I) -> Result<(), Error>
+where
+    I: One,
+    I: PartialOrd,
+    I: NumberTests,
+    I: EGCD,
+{
+    if NumberTests::is_negative(c) || NumberTests::is_zero(c) || c >= nn {
+        return Err(Error::InvalidCiphertext);
+    }
+    if I::egcd(c, n).0 != I::one() {
+        return Err(Error::InvalidCiphertext);
+    }
+    Ok(())
+}
+
 pub mod crt;
+pub mod dj;
 pub mod generic;
+pub mod pool;
 pub mod standard;
+pub mod threshold;
 
+#[cfg(feature = "keygen")]
+pub mod distributed;
 #[cfg(feature = "keygen")]
 pub mod keygen;
 #[cfg(feature = "keygen")]
@@ -131,6 +333,7 @@ bigint!(
         use super::I;
         use crate::core::*;
         use crate::AbstractPaillier;
+        use crate::Error;
 
         fn test_keypair() -> Keypair<I> {
             let p = str::parse("148677972634832330983979593310074301486537017973460461278300587514468301043894574906886127642530475786889672304776052879927627556769456140664043088700743909632312483413393134504352834240399191134336344285483935856491230340093391784574980688823380828143810804684752914935441384845195613674104960646037368551517").unwrap();
@@ -176,6 +379,231 @@ bigint!(
             assert_eq!(m, Plaintext::from(200));
         }
 
+        #[test]
+        fn test_correct_subtraction() {
+            let (ek, dk) = test_keypair().keys();
+
+            let c1 = AbstractPaillier::encrypt(&ek, &Plaintext::from(30));
+            let c2 = AbstractPaillier::encrypt(&ek, &Plaintext::from(10));
+
+            let c = AbstractPaillier::sub(&ek, &c1, &c2);
+            let m = AbstractPaillier::decrypt(&dk, &c);
+            assert_eq!(m, Plaintext::from(20));
+
+            let c = AbstractPaillier::neg(&ek, &c2);
+            let m: Plaintext<I> = AbstractPaillier::decrypt(&dk, &c);
+            assert_eq!(m.0, &ek.n - &I::from(10_u64));
+        }
+
+        #[test]
+        fn test_negative_multiplication() {
+            let (ek, dk) = test_keypair().keys();
+
+            let c1 = AbstractPaillier::encrypt(&ek, &Plaintext::from(10));
+            let m2 = Plaintext(I::from(0_u64) - I::from(3_u64));
+
+            let c = AbstractPaillier::mul(&ek, &c1, &m2);
+            let c3 = AbstractPaillier::encrypt(&ek, &Plaintext::from(50));
+            let m = AbstractPaillier::decrypt(&dk, &AbstractPaillier::add(&ek, &c, &c3));
+            assert_eq!(m, Plaintext::from(20));
+
+            let c = AbstractPaillier::try_mul(&ek, &c1, &m2).unwrap();
+            let m = AbstractPaillier::decrypt(&dk, &AbstractPaillier::add(&ek, &c, &c3));
+            assert_eq!(m, Plaintext::from(20));
+        }
+
+        #[test]
+        fn test_plaintext_out_of_range() {
+            let ref keypair = test_keypair();
+            let (ek, _dk) = keypair.keys();
+            let n = &keypair.p * &keypair.q;
+
+            let m = Plaintext(n.clone());
+            let res: Result<Ciphertext<_>, _> = AbstractPaillier::try_encrypt(&ek, &m);
+            assert_eq!(res.err(), Some(Error::PlaintextOutOfRange));
+
+            let c = AbstractPaillier::encrypt(&ek, &Plaintext::from(10));
+            let res = AbstractPaillier::try_mul(&ek, &c, &Plaintext(n));
+            assert_eq!(res.err(), Some(Error::PlaintextOutOfRange));
+        }
+
+        #[test]
+        fn test_invalid_ciphertext() {
+            let ref keypair = test_keypair();
+            let (ek, dk) = keypair.keys();
+            let n = &keypair.p * &keypair.q;
+
+            let valid = AbstractPaillier::encrypt(&ek, &Plaintext::from(10));
+            let invalid = [
+                Ciphertext(I::from(0_u64)),
+                Ciphertext(&n * &n),
+                Ciphertext(keypair.p.clone()),
+            ];
+            for c in invalid.iter() {
+                let res: Result<Plaintext<_>, _> = AbstractPaillier::try_decrypt(&dk, c);
+                assert_eq!(res.err(), Some(Error::InvalidCiphertext));
+                let res = AbstractPaillier::try_add(&ek, &valid, c);
+                assert_eq!(res.err(), Some(Error::InvalidCiphertext));
+                let res: Result<Ciphertext<_>, _> = AbstractPaillier::try_neg(&ek, c);
+                assert_eq!(res.err(), Some(Error::InvalidCiphertext));
+            }
+
+            let c = AbstractPaillier::try_neg(&ek, &valid).unwrap();
+            let c = AbstractPaillier::add(&ek, &c, &valid);
+            assert_eq!(AbstractPaillier::decrypt(&dk, &c), Plaintext::from(0));
+
+            let m = AbstractPaillier::try_decrypt(&dk, &valid).unwrap();
+            assert_eq!(m, Plaintext::from(10));
+        }
+
+        #[test]
+        fn test_validation() {
+            let ref keypair = test_keypair();
+            let (ek, dk) = keypair.keys();
+            let n = &keypair.p * &keypair.q;
+
+            // a multiple of a prime factor would leak it through decryption
+            let leaky = Ciphertext(&keypair.q * &I::from(5_u64));
+            assert_eq!(
+                ek.validate_ciphertext(&leaky).err(),
+                Some(Error::InvalidCiphertext)
+            );
+            let huge = Ciphertext(&n * &n + I::from(1_u64));
+            assert_eq!(
+                ek.validate_ciphertext(&huge).err(),
+                Some(Error::InvalidCiphertext)
+            );
+
+            let c1 = AbstractPaillier::encrypt(&ek, &Plaintext::from(10));
+            let c1 = ek.validate_ciphertext(&c1).unwrap();
+            let c2 = AbstractPaillier::encrypt(&ek, &Plaintext::from(20));
+            let c2: ValidatedCiphertext<_> = AbstractPaillier::validate(&ek, &c2).unwrap();
+
+            let c = AbstractPaillier::add(&ek, &c1, &c2);
+            let m = AbstractPaillier::decrypt(&dk, &c);
+            assert_eq!(m, Plaintext::from(30));
+        }
+
+        #[test]
+        fn test_try_modinv() {
+            let ref keypair = test_keypair();
+            let n = &keypair.p * &keypair.q;
+            assert_eq!(I::try_modinv(&keypair.p, &n), Err(Error::NotInvertible));
+            let inv = I::try_modinv(&I::from(3_u64), &n).unwrap();
+            assert_eq!((inv * I::from(3_u64)) % &n, I::from(1_u64));
+        }
+
+        #[test]
+        fn test_modpow_sec() {
+            let ref keypair = test_keypair();
+            let n = &keypair.p * &keypair.q;
+            let base = I::from(7_u64);
+            let exponents = [
+                I::from(0_u64),
+                I::from(1_u64),
+                &keypair.p - I::from(1_u64),
+                n.clone(),
+            ];
+            for e in exponents.iter() {
+                assert_eq!(I::modpow_sec(&base, e, &n), I::modpow(&base, e, &n));
+            }
+            assert_eq!(
+                I::modpow_sec(&base, &I::from(5_u64), &I::from(1_u64)),
+                I::from(0_u64)
+            );
+        }
+
+        #[test]
+        fn test_sample() {
+            assert_eq!(I::sample(0), I::from(0_u64));
+            for bits in 1..70 {
+                assert!(I::sample(bits) < I::from(1_u64) << bits);
+            }
+        }
+
+        #[test]
+        #[cfg(feature = "hardened")]
+        fn test_hardened_decryption() {
+            let ref keypair = test_keypair();
+            let ek = standard::EncryptionKey::from(keypair);
+            let dk = standard::DecryptionKey::from(keypair);
+            let crt_dk = crt::DecryptionKey::from(keypair);
+
+            // blinded ciphertexts and the ladder must give the same results as the fast path
+            let m = Plaintext::from(10);
+            let c = AbstractPaillier::encrypt(&ek, &m);
+            assert_eq!(AbstractPaillier::decrypt(&dk, &c), m);
+            assert_eq!(AbstractPaillier::decrypt(&crt_dk, &c), m);
+
+            let ref n = ek.n;
+            let ref e = &keypair.q - I::from(1_u64);
+            let base = I::from(12345_u64);
+            assert_eq!(modpow_secret(&base, e, n), I::modpow(&base, e, n));
+        }
+
+        #[test]
+        fn test_encryption_with_randomness() {
+            let (ek, dk) = test_keypair().keys();
+            let m = Plaintext::from(10);
+
+            let (c, r) = AbstractPaillier::encrypt_returning_randomness(&ek, &m);
+            let d = AbstractPaillier::encrypt_with_randomness(&ek, &m, &r);
+            assert_eq!(c.0, d.0);
+            assert_eq!(AbstractPaillier::decrypt(&dk, &d), m);
+
+            let r = Randomness::new(I::from(5_u64), &ek.n).unwrap();
+            let c: Ciphertext<I> = AbstractPaillier::encrypt_with_randomness(&ek, &m, &r);
+            assert_eq!(AbstractPaillier::decrypt(&dk, &c), m);
+        }
+
+        #[test]
+        fn test_opening() {
+            let (ek, dk) = test_keypair().keys();
+            let m = Plaintext::from(10);
+
+            let (c, r) = AbstractPaillier::encrypt_returning_randomness(&ek, &m);
+            let (recovered_m, recovered_r) = dk.open(&c);
+            assert_eq!(recovered_m, m);
+            assert_eq!(recovered_r, r);
+
+            let c = AbstractPaillier::add(&ek, &c, &c);
+            let (recovered_m, recovered_r) = dk.open(&c);
+            let d = AbstractPaillier::encrypt_with_randomness(&ek, &recovered_m, &recovered_r);
+            assert_eq!(recovered_m, Plaintext::from(20));
+            assert_eq!(c.0, d.0);
+        }
+
+        #[test]
+        fn test_owner_encryption() {
+            let (ek, dk) = test_keypair().keys();
+
+            let m1 = Plaintext::from(10);
+            let c1 = AbstractPaillier::encrypt(&dk, &m1);
+            let m2 = Plaintext::from(20);
+            let c2 = AbstractPaillier::encrypt(&ek, &m2);
+
+            let c = AbstractPaillier::add(&ek, &c1, &c2);
+            let m = AbstractPaillier::decrypt(&dk, &c);
+            assert_eq!(m, Plaintext::from(30));
+
+            // the randomness used must be a proper n-th power, as for the public key
+            let (_, r) = dk.open(&c1);
+            let d = AbstractPaillier::encrypt_with_randomness(&ek, &m1, &r);
+            assert_eq!(c1.0, d.0);
+        }
+
+        #[test]
+        fn test_invalid_randomness() {
+            let ref keypair = test_keypair();
+            let (ek, _) = keypair.keys();
+            let res = Randomness::new(I::from(0_u64), &ek.n);
+            assert_eq!(res.err(), Some(Error::InvalidRandomness));
+            let res = Randomness::new(ek.n.clone(), &ek.n);
+            assert_eq!(res.err(), Some(Error::InvalidRandomness));
+            let res = Randomness::new(keypair.q.clone(), &ek.n);
+            assert_eq!(res.err(), Some(Error::InvalidRandomness));
+        }
+
         #[cfg(feature = "keygen")]
         #[test]
         fn test_correct_keygen() {
@@ -188,5 +616,38 @@ bigint!(
             let recovered_m = AbstractPaillier::decrypt(&dk, &c);
             assert_eq!(recovered_m, m);
         }
+
+        #[cfg(feature = "keygen")]
+        #[test]
+        fn test_keygen_leaves_no_cached_factor() {
+            use crate::arithimpl::montgomery::{cached_moduli, context};
+
+            // a public modulus in the cache beforehand is left in place
+            let ref n = test_keypair().keys().0.n;
+            context(n);
+            let keypair: Keypair<I> = AbstractPaillier::keypair_with_modulus_size(512);
+            let cached = cached_moduli::<I>();
+            assert!(cached.contains(n));
+            for modulus in cached.iter() {
+                assert!(!NumberTests::is_zero(&(modulus % &keypair.p)));
+                assert!(!NumberTests::is_zero(&(modulus % &keypair.q)));
+            }
+        }
+
+        #[cfg(all(feature = "keygen", feature = "hardened"))]
+        #[test]
+        fn test_hardened_keygen() {
+            use crate::arithimpl::primes::is_prime;
+
+            // key generation in hardened mode still yields primes and working keys
+            let keypair: Keypair<I> = AbstractPaillier::keypair_with_modulus_size(512);
+            assert!(is_prime(&keypair.p));
+            assert!(is_prime(&keypair.q));
+
+            let (ek, dk) = keypair.keys();
+            let m = Plaintext::from(10);
+            let c = AbstractPaillier::encrypt(&ek, &m);
+            assert_eq!(AbstractPaillier::decrypt(&dk, &c), m);
+        }
     }
 );
@@ -0,0 +1,327 @@
+//! Offline/online encryption using a pool of precomputed randomness.
+//!
+//! Nearly all of the cost of encryption lies in computing `r^n mod n^2`, which is independent of
+//! the plaintext. A `RandomnessPool` computes these values ahead of time, either on demand or on
+//! a background thread, so that encryption and rerandomisation only require a multiplication.
+//! Every precomputed value is removed from the pool when used and hence never reused.
+//!
+//! A background refill stops when the pool is dropped, which waits for the value being computed.
+
+use super::*;
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// Pool of precomputed randomness for a fixed encryption key.
+///
+/// The pool may be used in place of a `standard::EncryptionKey` for encryption and
+/// rerandomisation. Once it runs dry, `encrypt` and `rerandomise` quietly fall back to computing
+/// `r^n mod n^2` online at the full cost of ordinary encryption, and so do not surface the
+/// exhaustion; `try_encrypt` fails with `RandomnessExhausted` instead.
+pub struct RandomnessPool<I> {
+    n: I,
+    nn: I,
+    low: usize,
+    high: usize,
+    background: bool,
+    values: Arc<Mutex<VecDeque<I>>>,
+    refilling: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+    refill: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl<I> crate::traits::EncryptionKey for RandomnessPool<I> {}
+
+impl<I> RandomnessPool<I>
+where
+    I: Clone,
+{
+    /// Create an empty pool for `ek`, to be refilled to `high` values once fewer than `low` remain.
+    pub fn new(ek: &standard::EncryptionKey<I>, low: usize, high: usize) -> RandomnessPool<I> {
+        assert!(low <= high, "refill threshold must not exceed capacity");
+        RandomnessPool {
+            n: ek.n.clone(),
+            nn: ek.nn.clone(),
+            low: low,
+            high: high,
+            background: false,
+            values: Arc::new(Mutex::new(VecDeque::with_capacity(high))),
+            refilling: Arc::new(AtomicBool::new(false)),
+            stop: Arc::new(AtomicBool::new(false)),
+            refill: Mutex::new(None),
+        }
+    }
+
+    /// Refill the pool on a background thread whenever it runs low.
+    pub fn with_background_refill(mut self) -> RandomnessPool<I> {
+        self.background = true;
+        self
+    }
+}
+
+impl<I> RandomnessPool<I> {
+    /// Number of precomputed values currently available.
+    pub fn len(&self) -> usize {
+        self.values.lock().unwrap().len()
+    }
+
+    /// Whether the pool has run out of precomputed values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<I> RandomnessPool<I>
+where
+    I: Samplable,
+    I: ModPow,
+    I: One,
+    I: PartialOrd,
+    I: NumberTests,
+    I: EGCD,
+{
+    /// Precompute values until the pool is full, blocking the current thread.
+    pub fn fill(&self) {
+        while self.len() < self.high {
+            let value = precompute(&self.n, &self.nn);
+            push(&self.values, self.high, value);
+        }
+    }
+
+    /// Remove a precomputed value `r^n mod n^2` from the pool, if any is left.
+    fn take(&self) -> Option<I>
+    where
+        I: Clone,
+        I: Send + 'static,
+    {
+        let (value, remaining) = {
+            let mut values = self.values.lock().unwrap();
+            (values.pop_front(), values.len())
+        };
+        if self.background && remaining < self.low {
+            self.spawn_refill();
+        }
+        value
+    }
+
+    fn spawn_refill(&self)
+    where
+        I: Clone,
+        I: Send + 'static,
+    {
+        if self.refilling.swap(true, Ordering::SeqCst) {
+            // another thread is already refilling
+            return;
+        }
+        let values = self.values.clone();
+        let refilling = self.refilling.clone();
+        let stop = self.stop.clone();
+        let n = self.n.clone();
+        let nn = self.nn.clone();
+        let high = self.high;
+        let handle = thread::spawn(move || {
+            while !stop.load(Ordering::SeqCst) && values.lock().unwrap().len() < high {
+                let value = precompute(&n, &nn);
+                push(&values, high, value);
+            }
+            refilling.store(false, Ordering::SeqCst);
+        });
+        // any previous refill has cleared `refilling` and is about to finish
+        let previous = self.refill.lock().unwrap().replace(handle);
+        if let Some(previous) = previous {
+            let _ = previous.join();
+        }
+    }
+}
+
+impl<I> Drop for RandomnessPool<I> {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.refill.lock().unwrap().take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+// Add `value` unless the pool already holds `high` values, checking while holding the lock so
+// that concurrent refills never exceed the capacity.
+fn push<I>(values: &Mutex<VecDeque<I>>, high: usize, value: I) {
+    let mut values = values.lock().unwrap();
+    if values.len() < high {
+        values.push_back(value);
+    }
+}
+
+fn precompute<I>(n: &I, nn: &I) -> I
+where
+    I: Samplable,
+    I: ModPow,
+    I: One,
+    I: PartialOrd,
+    I: NumberTests,
+    I: EGCD,
+{
+    let r = Randomness::sample(n);
+    I::modpow(&r.0, n, nn)
+}
+
+impl<I, S> Rerandomisation<RandomnessPool<I>, Ciphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    I: Clone + Send + 'static,
+    I: Samplable,
+    I: ModPow,
+    I: One,
+    I: PartialOrd,
+    I: NumberTests,
+    I: EGCD,
+    for<'a> &'a I: Mul<I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    fn rerandomise(pool: &RandomnessPool<I>, c: &Ciphertext<I>) -> Ciphertext<I> {
+        // fall back to online computation once the pool is exhausted
+        let rn = pool.take().unwrap_or_else(|| precompute(&pool.n, &pool.nn));
+        Ciphertext((&c.0 * rn) % &pool.nn)
+    }
+}
+
+impl<I, S> Encryption<RandomnessPool<I>, Plaintext<I>, Ciphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Rerandomisation<RandomnessPool<I>, Ciphertext<I>>,
+    I: One,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    fn encrypt(pool: &RandomnessPool<I>, m: &Plaintext<I>) -> Ciphertext<I> {
+        // here we assume that g = n+1
+        let nm = &m.0 * &pool.n;
+        let gm = (&nm + &I::one()) % &pool.nn;
+        S::rerandomise(pool, &Ciphertext(gm))
+    }
+}
+
+impl<I, S> TryEncryption<RandomnessPool<I>, Plaintext<I>, Ciphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    I: Clone + Send + 'static,
+    I: Samplable,
+    I: ModPow,
+    I: One,
+    I: PartialOrd,
+    I: NumberTests,
+    I: EGCD,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    I: Mul<I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    fn try_encrypt(pool: &RandomnessPool<I>, m: &Plaintext<I>) -> Result<Ciphertext<I>, Error> {
+        check_plaintext(&m.0, &pool.n)?;
+        let rn = pool.take().ok_or(Error::RandomnessExhausted)?;
+        // here we assume that g = n+1
+        let nm = &m.0 * &pool.n;
+        let gm = (&nm + &I::one()) % &pool.nn;
+        Ok(Ciphertext((gm * rn) % &pool.nn))
+    }
+}
+
+bigint!(
+    I,
+    #[cfg(test)]
+    mod tests {
+
+        use super::I;
+        use crate::core::pool::*;
+        use crate::AbstractPaillier;
+        use crate::Error;
+        use std::time::{Duration, Instant};
+
+        fn test_keypair() -> Keypair<I> {
+            let p = str::parse("148677972634832330983979593310074301486537017973460461278300587514468301043894574906886127642530475786889672304776052879927627556769456140664043088700743909632312483413393134504352834240399191134336344285483935856491230340093391784574980688823380828143810804684752914935441384845195613674104960646037368551517").unwrap();
+            let q = str::parse("158741574437007245654463598139927898730476924736461654463975966787719309357536545869203069369466212089132653564188443272208127277664424448947476335413293018778018615899291704693105620242763173357203898195318179150836424196645745308205164116144020613415407736216097185962171301808761138424668335445923774195463").unwrap();
+            Keypair { p: p, q: q }
+        }
+
+        #[test]
+        fn test_pooled_encryption() {
+            let (ek, dk) = test_keypair().keys();
+            let pool = RandomnessPool::new(&ek, 1, 3);
+            pool.fill();
+            assert_eq!(pool.len(), 3);
+
+            let m = Plaintext::from(10);
+            let c1: Ciphertext<I> = AbstractPaillier::encrypt(&pool, &m);
+            let c2: Ciphertext<I> = AbstractPaillier::try_encrypt(&pool, &m).unwrap();
+            assert_eq!(pool.len(), 1);
+            assert!(c1.0 != c2.0);
+            assert_eq!(AbstractPaillier::decrypt(&dk, &c1), m);
+            assert_eq!(AbstractPaillier::decrypt(&dk, &c2), m);
+
+            let c = AbstractPaillier::rerandomise(&pool, &c1);
+            assert!(c.0 != c1.0);
+            assert_eq!(AbstractPaillier::decrypt(&dk, &c), m);
+            assert!(pool.is_empty());
+        }
+
+        #[test]
+        fn test_exhausted_pool() {
+            let (ek, dk) = test_keypair().keys();
+            let pool = RandomnessPool::new(&ek, 0, 0);
+
+            let m = Plaintext::from(10);
+            let res: Result<Ciphertext<I>, _> = AbstractPaillier::try_encrypt(&pool, &m);
+            assert_eq!(res.err(), Some(Error::RandomnessExhausted));
+
+            // infallible encryption falls back to computing randomness online
+            let c: Ciphertext<I> = AbstractPaillier::encrypt(&pool, &m);
+            assert_eq!(AbstractPaillier::decrypt(&dk, &c), m);
+        }
+
+        #[test]
+        fn test_background_refill() {
+            let (ek, dk) = test_keypair().keys();
+            let pool = RandomnessPool::new(&ek, 2, 4).with_background_refill();
+            pool.fill();
+
+            let m = Plaintext::from(10);
+            for _ in 0..3 {
+                let c: Ciphertext<I> = AbstractPaillier::encrypt(&pool, &m);
+                assert_eq!(AbstractPaillier::decrypt(&dk, &c), m);
+            }
+            // a refill thread that never runs must fail the test rather than hang it
+            let deadline = Instant::now() + Duration::from_secs(60);
+            while pool.len() < 4 && Instant::now() < deadline {
+                ::std::thread::sleep(Duration::from_millis(10));
+            }
+            assert_eq!(pool.len(), 4);
+        }
+
+        #[test]
+        fn test_refill_capacity_and_drop() {
+            let (ek, _) = test_keypair().keys();
+            let pool = RandomnessPool::new(&ek, 2, 4).with_background_refill();
+            pool.fill();
+
+            // filling while a background refill runs stays within the capacity
+            let m = Plaintext::from(10);
+            for _ in 0..3 {
+                let _: Ciphertext<I> = AbstractPaillier::encrypt(&pool, &m);
+            }
+            pool.fill();
+            assert_eq!(pool.len(), 4);
+
+            // dropping the pool stops the refill thread and releases the queue
+            let _: Ciphertext<I> = AbstractPaillier::encrypt(&pool, &m);
+            let _: Ciphertext<I> = AbstractPaillier::encrypt(&pool, &m);
+            let _: Ciphertext<I> = AbstractPaillier::encrypt(&pool, &m);
+            let values = pool.values.clone();
+            drop(pool);
+            assert_eq!(Arc::strong_count(&values), 1);
+            assert!(values.lock().unwrap().len() <= 4);
+        }
+    }
+);
@@ -1,12 +1,13 @@
 //! Standard encryption and decryption.
 
 use super::*;
+use crate::proof::ProofKey;
 
 /// Encryption key that may be shared publicly.
 #[derive(Debug, Clone)]
 pub struct EncryptionKey<I> {
-    pub n: I, // the modulus
-    nn: I,    // the modulus squared
+    pub n: I,         // the modulus
+    pub(super) nn: I, // the modulus squared
 }
 
 impl<I> crate::traits::EncryptionKey for EncryptionKey<I> {}
@@ -25,6 +26,66 @@ where
     }
 }
 
+impl<I> EncryptionKey<I>
+where
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+{
+    /// Create key from modulus `n`, for instance when its factorisation is not known to anyone.
+    pub fn from_modulus(n: I) -> EncryptionKey<I> {
+        EncryptionKey { nn: &n * &n, n: n }
+    }
+}
+
+impl<I> EncryptionKey<I>
+where
+    I: Clone,
+    I: One,
+    I: PartialOrd,
+    I: NumberTests,
+    I: EGCD,
+{
+    /// Check that ciphertext `c` is an element of `Z*_{n^2}`.
+    ///
+    /// Besides range checks this rejects ciphertexts sharing a factor with `n`, which could
+    /// otherwise leak the factorisation through the results of decryption.
+    pub fn validate_ciphertext(&self, c: &Ciphertext<I>) -> Result<ValidatedCiphertext<I>, Error> {
+        check_ciphertext(&c.0, &self.n, &self.nn)?;
+        Ok(ValidatedCiphertext(c.clone()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<I> Serialize for EncryptionKey<I>
+where
+    I: fmt::Display,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        EncryptionKeyRepr {
+            n: to_repr(&self.n),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, I> Deserialize<'de> for EncryptionKey<I>
+where
+    I: FromStr,
+    I: One,
+    I: PartialOrd,
+    I: NumberTests,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = EncryptionKeyRepr::deserialize(deserializer)?;
+        let n: I = from_repr(&repr.n)?;
+        if n <= I::one() || NumberTests::is_even(&n) {
+            return Err(de::Error::custom("invalid modulus"));
+        }
+        Ok(EncryptionKey { nn: &n * &n, n: n })
+    }
+}
+
 /// Decryption key that should be kept private.
 #[derive(Debug, Clone)]
 pub struct DecryptionKey<I> {
@@ -96,6 +157,42 @@ where
     }
 }
 
+impl<I, S> EncryptionWithRandomness<EncryptionKey<I>, Plaintext<I>, Randomness<I>, Ciphertext<I>>
+    for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    I: Samplable,
+    I: ModPow,
+    I: One,
+    I: PartialOrd,
+    I: NumberTests,
+    I: EGCD,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    I: Mul<I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    fn encrypt_with_randomness(
+        ek: &EncryptionKey<I>,
+        m: &Plaintext<I>,
+        r: &Randomness<I>,
+    ) -> Ciphertext<I> {
+        // here we assume that g = n+1
+        let nm = &m.0 * &ek.n;
+        let gm = (&nm + &I::one()) % &ek.nn;
+        let rn = I::modpow(&r.0, &ek.n, &ek.nn);
+        Ciphertext((gm * rn) % &ek.nn)
+    }
+
+    fn encrypt_returning_randomness(
+        ek: &EncryptionKey<I>,
+        m: &Plaintext<I>,
+    ) -> (Ciphertext<I>, Randomness<I>) {
+        let r = Randomness::sample(&ek.n);
+        (S::encrypt_with_randomness(ek, m, &r), r)
+    }
+}
+
 impl<I, S> Addition<EncryptionKey<I>, Ciphertext<I>, Ciphertext<I>, Ciphertext<I>> for S
 where
     S: AbstractScheme<BigInteger = I>,
@@ -108,30 +205,291 @@ where
     }
 }
 
+impl<I, S> Negation<EncryptionKey<I>, Ciphertext<I>, Ciphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    I: ModInv,
+{
+    /// Panics with some arithmetic libraries if `c` is not a unit modulo n^2.
+    fn neg(ek: &EncryptionKey<I>, c: &Ciphertext<I>) -> Ciphertext<I> {
+        let d = I::modinv(&c.0, &ek.nn);
+        Ciphertext(d)
+    }
+}
+
+impl<I, S> Subtraction<EncryptionKey<I>, Ciphertext<I>, Ciphertext<I>, Ciphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Negation<EncryptionKey<I>, Ciphertext<I>, Ciphertext<I>>,
+    S: Addition<EncryptionKey<I>, Ciphertext<I>, Ciphertext<I>, Ciphertext<I>>,
+{
+    fn sub(ek: &EncryptionKey<I>, c1: &Ciphertext<I>, c2: &Ciphertext<I>) -> Ciphertext<I> {
+        S::add(ek, c1, &S::neg(ek, c2))
+    }
+}
+
 impl<I, S> Multiplication<EncryptionKey<I>, Ciphertext<I>, Plaintext<I>, Ciphertext<I>> for S
 where
     S: AbstractScheme<BigInteger = I>,
     I: ModPow,
+    I: NumberTests,
+    for<'b> I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
 {
     fn mul(ek: &EncryptionKey<I>, c1: &Ciphertext<I>, m2: &Plaintext<I>) -> Ciphertext<I> {
-        let c = I::modpow(&c1.0, &m2.0, &ek.nn);
+        let c = if NumberTests::is_negative(&m2.0) {
+            // use exponent n - k for multiplier -k
+            let k = (&m2.0 % &ek.n) + &ek.n;
+            I::modpow(&c1.0, &k, &ek.nn)
+        } else {
+            I::modpow(&c1.0, &m2.0, &ek.nn)
+        };
         Ciphertext(c)
     }
 }
 
+impl<I, S> TryEncryption<EncryptionKey<I>, Plaintext<I>, Ciphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Encryption<EncryptionKey<I>, Plaintext<I>, Ciphertext<I>>,
+    I: PartialOrd,
+    I: NumberTests,
+{
+    fn try_encrypt(ek: &EncryptionKey<I>, m: &Plaintext<I>) -> Result<Ciphertext<I>, Error> {
+        check_plaintext(&m.0, &ek.n)?;
+        Ok(S::encrypt(ek, m))
+    }
+}
+
+impl<I, S> TryAddition<EncryptionKey<I>, Ciphertext<I>, Ciphertext<I>, Ciphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Addition<EncryptionKey<I>, Ciphertext<I>, Ciphertext<I>, Ciphertext<I>>,
+    I: One,
+    I: PartialOrd,
+    I: NumberTests,
+    I: EGCD,
+{
+    fn try_add(
+        ek: &EncryptionKey<I>,
+        c1: &Ciphertext<I>,
+        c2: &Ciphertext<I>,
+    ) -> Result<Ciphertext<I>, Error> {
+        check_ciphertext(&c1.0, &ek.n, &ek.nn)?;
+        check_ciphertext(&c2.0, &ek.n, &ek.nn)?;
+        Ok(S::add(ek, c1, c2))
+    }
+}
+
+impl<I, S> TryNegation<EncryptionKey<I>, Ciphertext<I>, Ciphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    I: One,
+    I: PartialOrd,
+    I: NumberTests,
+    I: TryModInv,
+{
+    fn try_neg(ek: &EncryptionKey<I>, c: &Ciphertext<I>) -> Result<Ciphertext<I>, Error> {
+        check_ciphertext(&c.0, &ek.n, &ek.nn)?;
+        Ok(Ciphertext(I::try_modinv(&c.0, &ek.nn)?))
+    }
+}
+
+impl<I, S> TrySubtraction<EncryptionKey<I>, Ciphertext<I>, Ciphertext<I>, Ciphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: TryNegation<EncryptionKey<I>, Ciphertext<I>, Ciphertext<I>>,
+    S: TryAddition<EncryptionKey<I>, Ciphertext<I>, Ciphertext<I>, Ciphertext<I>>,
+{
+    fn try_sub(
+        ek: &EncryptionKey<I>,
+        c1: &Ciphertext<I>,
+        c2: &Ciphertext<I>,
+    ) -> Result<Ciphertext<I>, Error> {
+        S::try_add(ek, c1, &S::try_neg(ek, c2)?)
+    }
+}
+
+impl<I, S> TryMultiplication<EncryptionKey<I>, Ciphertext<I>, Plaintext<I>, Ciphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Multiplication<EncryptionKey<I>, Ciphertext<I>, Plaintext<I>, Ciphertext<I>>,
+    I: One,
+    I: PartialOrd,
+    I: NumberTests,
+    I: EGCD,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+{
+    fn try_mul(
+        ek: &EncryptionKey<I>,
+        c1: &Ciphertext<I>,
+        m2: &Plaintext<I>,
+    ) -> Result<Ciphertext<I>, Error> {
+        check_ciphertext(&c1.0, &ek.n, &ek.nn)?;
+        if NumberTests::is_negative(&m2.0) {
+            // negative multipliers must be in range `[-n, 0)`
+            check_plaintext(&(&m2.0 + &ek.n), &ek.n)?;
+        } else {
+            check_plaintext(&m2.0, &ek.n)?;
+        }
+        Ok(S::mul(ek, c1, m2))
+    }
+}
+
+impl<I> ProofKey<I> for EncryptionKey<I>
+where
+    I: One,
+    I: Add<I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    fn n(&self) -> &I {
+        &self.n
+    }
+
+    fn nn(&self) -> &I {
+        &self.nn
+    }
+
+    fn gm(&self, m: &I) -> I {
+        // here we have g = n+1 and hence g^m = 1 + mn
+        (m * &self.n + I::one()) % &self.nn
+    }
+}
+
+impl<I, S> Validation<EncryptionKey<I>, Ciphertext<I>, ValidatedCiphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    I: Clone,
+    I: One,
+    I: PartialOrd,
+    I: NumberTests,
+    I: EGCD,
+{
+    fn validate(ek: &EncryptionKey<I>, c: &Ciphertext<I>) -> Result<ValidatedCiphertext<I>, Error> {
+        ek.validate_ciphertext(c)
+    }
+}
+
+impl<I, S>
+    Addition<
+        EncryptionKey<I>,
+        ValidatedCiphertext<I>,
+        ValidatedCiphertext<I>,
+        ValidatedCiphertext<I>,
+    > for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Addition<EncryptionKey<I>, Ciphertext<I>, Ciphertext<I>, Ciphertext<I>>,
+{
+    fn add(
+        ek: &EncryptionKey<I>,
+        c1: &ValidatedCiphertext<I>,
+        c2: &ValidatedCiphertext<I>,
+    ) -> ValidatedCiphertext<I> {
+        // units are closed under multiplication
+        ValidatedCiphertext(S::add(ek, &c1.0, &c2.0))
+    }
+}
+
+impl<I, S> Negation<EncryptionKey<I>, ValidatedCiphertext<I>, ValidatedCiphertext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Negation<EncryptionKey<I>, Ciphertext<I>, Ciphertext<I>>,
+{
+    fn neg(ek: &EncryptionKey<I>, c: &ValidatedCiphertext<I>) -> ValidatedCiphertext<I> {
+        // units are closed under inversion
+        ValidatedCiphertext(S::neg(ek, &c.0))
+    }
+}
+
+impl<I, S>
+    Subtraction<
+        EncryptionKey<I>,
+        ValidatedCiphertext<I>,
+        ValidatedCiphertext<I>,
+        ValidatedCiphertext<I>,
+    > for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Subtraction<EncryptionKey<I>, Ciphertext<I>, Ciphertext<I>, Ciphertext<I>>,
+{
+    fn sub(
+        ek: &EncryptionKey<I>,
+        c1: &ValidatedCiphertext<I>,
+        c2: &ValidatedCiphertext<I>,
+    ) -> ValidatedCiphertext<I> {
+        ValidatedCiphertext(S::sub(ek, &c1.0, &c2.0))
+    }
+}
+
+impl<I, S>
+    Multiplication<EncryptionKey<I>, ValidatedCiphertext<I>, Plaintext<I>, ValidatedCiphertext<I>>
+    for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Multiplication<EncryptionKey<I>, Ciphertext<I>, Plaintext<I>, Ciphertext<I>>,
+{
+    fn mul(
+        ek: &EncryptionKey<I>,
+        c1: &ValidatedCiphertext<I>,
+        m2: &Plaintext<I>,
+    ) -> ValidatedCiphertext<I> {
+        ValidatedCiphertext(S::mul(ek, &c1.0, m2))
+    }
+}
+
 impl<I, S> Decryption<DecryptionKey<I>, Ciphertext<I>, Plaintext<I>> for S
 where
     S: AbstractScheme<BigInteger = I>,
     I: One,
+    I: Samplable,
     I: ModPow,
+    I: ModPowSec,
     for<'a> &'a I: Sub<I, Output = I>,
+    for<'a> &'a I: Mul<I, Output = I>,
     for<'b> I: Mul<&'b I, Output = I>,
     for<'b> I: Div<&'b I, Output = I>,
     for<'a> I: Rem<&'a I, Output = I>,
 {
     fn decrypt(dk: &DecryptionKey<I>, c: &Ciphertext<I>) -> Plaintext<I> {
-        let u = I::modpow(&c.0, &dk.lambda, &dk.nn);
+        // in hardened mode the ciphertext is first blinded by a fresh encryption of zero, so
+        // that the exponentiation below never operates on a value chosen by an adversary
+        let blinded;
+        let c = if cfg!(feature = "hardened") {
+            let r = I::sample_below(&dk.n);
+            blinded = (&c.0 * I::modpow(&r, &dk.n, &dk.nn)) % &dk.nn;
+            &blinded
+        } else {
+            &c.0
+        };
+        let u = modpow_secret(c, &dk.lambda, &dk.nn);
         let m = (l(&u, &dk.n) * &dk.mu) % &dk.n;
         Plaintext(m)
     }
 }
+
+impl<I, S> Decryption<DecryptionKey<I>, ValidatedCiphertext<I>, Plaintext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Decryption<DecryptionKey<I>, Ciphertext<I>, Plaintext<I>>,
+{
+    fn decrypt(dk: &DecryptionKey<I>, c: &ValidatedCiphertext<I>) -> Plaintext<I> {
+        S::decrypt(dk, &c.0)
+    }
+}
+
+impl<I, S> TryDecryption<DecryptionKey<I>, Ciphertext<I>, Plaintext<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    S: Decryption<DecryptionKey<I>, Ciphertext<I>, Plaintext<I>>,
+    I: One,
+    I: PartialOrd,
+    I: NumberTests,
+    I: EGCD,
+{
+    fn try_decrypt(dk: &DecryptionKey<I>, c: &Ciphertext<I>) -> Result<Plaintext<I>, Error> {
+        check_ciphertext(&c.0, &dk.n, &dk.nn)?;
+        Ok(S::decrypt(dk, c))
+    }
+}
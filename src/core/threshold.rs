@@ -0,0 +1,388 @@
+//! Threshold decryption in which any `t` out of `l` key shares are needed to decrypt.
+//!
+//! Follows the scheme of Damgård and Jurik (for `s = 1`), itself based on Shoup's threshold RSA.
+//! A trusted dealer shares a decryption exponent `d` with `d = 0 mod lambda(n)` and `d = 1 mod n`
+//! using a polynomial of degree `t-1` over `Z_{n*lambda(n)}`. Each share holder computes a partial
+//! decryption `c^{2*delta*s_i}`, where `delta = l!`, and proves it correct against its public
+//! verification key `v^{delta*s_i}`. Any `t` correct partial decryptions may then be combined.
+//!
+//! The security proofs of the scheme assume that `p` and `q` are safe primes.
+
+use super::*;
+use crate::proof::Transcript;
+use num_traits::Zero;
+
+const DOMAIN: &str = "paillier.threshold.decryption";
+
+/// Secret key share held by a single decryption server.
+#[derive(Debug, Clone)]
+pub struct KeyShare<I> {
+    pub index: usize, // the evaluation point of the share, in `1..l+1`
+    share: I,         // the evaluation f(index)
+    n: I,
+    nn: I,
+    delta: I, // l!
+    v: I,     // base of the verification keys
+    vk: I,    // verification key of this share
+}
+
+impl<I> crate::traits::DecryptionKey for KeyShare<I> {}
+
+/// Public information needed to verify and combine partial decryptions.
+#[derive(Debug, Clone)]
+pub struct VerificationKey<I> {
+    pub threshold: usize,
+    pub parties: usize,
+    n: I,
+    nn: I,
+    delta: I,
+    v: I,
+    vks: Vec<I>, // verification keys v^{delta*s_i} of the shares, in order of index
+}
+
+/// Partial decryption produced by a single share holder, together with a proof of correctness.
+#[derive(Debug, Clone)]
+pub struct PartialDecryption<I> {
+    pub index: usize,
+    value: I,     // c^{2*delta*s_i}
+    challenge: I, // Fiat-Shamir challenge of the proof
+    response: I,  // r + challenge * delta * s_i
+}
+
+/// Split the decryption capability of `keypair` into `parties` shares, any `threshold` of which
+/// are needed to decrypt.
+///
+/// Ciphertexts are produced as usual using the `standard::EncryptionKey` of `keypair`.
+///
+/// The primes of `keypair` are not checked: the caller must make sure that they are safe primes,
+/// i.e. that `(p-1)/2` and `(q-1)/2` are prime too. Otherwise decryption still works, but the
+/// verification keys, and hence the proofs of correct partial decryption, are unsound.
+pub fn deal<I>(
+    keypair: &Keypair<I>,
+    threshold: usize,
+    parties: usize,
+) -> (VerificationKey<I>, Vec<KeyShare<I>>)
+where
+    I: Clone,
+    I: From<u64>,
+    I: Zero + One,
+    I: Samplable,
+    I: ModPow,
+    I: ModInv,
+    I: EGCD,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Add<&'b I, Output = I>,
+    for<'b> I: Div<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    assert!(
+        0 < threshold && threshold <= parties,
+        "threshold must be between one and the number of parties"
+    );
+    let ref one = I::one();
+    let ref n = &keypair.p * &keypair.q;
+    let ref nn = n * n;
+    let ref pminusone = &keypair.p - one;
+    let ref qminusone = &keypair.q - one;
+    let lambda = (pminusone * qminusone) / &I::egcd(pminusone, qminusone).0;
+    let ref nlambda = n * &lambda;
+
+    // d = 0 mod lambda and d = 1 mod n
+    let d = &lambda * &I::modinv(&lambda, n);
+    let mut coefficients = vec![d];
+    for _ in 1..threshold {
+        coefficients.push(I::sample_below(nlambda));
+    }
+
+    let delta = factorial::<I>(parties);
+    let r = I::sample_below(nn);
+    let v = (&r * &r) % nn;
+
+    let shares: Vec<KeyShare<I>> = (1..parties + 1)
+        .map(|index| {
+            // evaluate the polynomial at index using Horner's rule
+            let ref x = I::from(index as u64);
+            let share = coefficients
+                .iter()
+                .rev()
+                .fold(I::zero(), |acc, a| (acc * x + a) % nlambda);
+            let vk = I::modpow(&v, &(&delta * &share), nn);
+            KeyShare {
+                index: index,
+                share: share,
+                n: n.clone(),
+                nn: nn.clone(),
+                delta: delta.clone(),
+                v: v.clone(),
+                vk: vk,
+            }
+        })
+        .collect();
+
+    let vk = VerificationKey {
+        threshold: threshold,
+        parties: parties,
+        n: n.clone(),
+        nn: nn.clone(),
+        delta: delta,
+        v: v,
+        vks: shares.iter().map(|share| share.vk.clone()).collect(),
+    };
+    (vk, shares)
+}
+
+impl<I, S> Decryption<KeyShare<I>, Ciphertext<I>, PartialDecryption<I>> for S
+where
+    S: AbstractScheme<BigInteger = I>,
+    I: ToBytes + FromBytes + NumberTests,
+    I: From<u64>,
+    I: Samplable,
+    I: ModPow,
+    I: ModPowSec,
+    I: Add<I, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    fn decrypt(share: &KeyShare<I>, c: &Ciphertext<I>) -> PartialDecryption<I> {
+        let ref nn = share.nn;
+        let ref exponent = &share.delta * &share.share;
+        let value = modpow_secret(&c.0, &(exponent + exponent), nn);
+
+        // prove that log_{c^4}(value^2) = log_v(vk) = delta * s_i
+        let ref four = I::from(4);
+        let ref two = I::from(2);
+        let c4 = I::modpow(&c.0, four, nn);
+        let value2 = I::modpow(&value, two, nn);
+        let r = I::sample_below(&(nn * nn));
+        let a = modpow_secret(&c4, &r, nn);
+        let b = modpow_secret(&share.v, &r, nn);
+        let e = fiat_shamir([&share.n, &share.v, &c4, &share.vk, &value2, &a, &b]);
+        let z = r + (&e * exponent);
+
+        PartialDecryption {
+            index: share.index,
+            value: value,
+            challenge: e,
+            response: z,
+        }
+    }
+}
+
+impl<I> VerificationKey<I>
+where
+    I: Clone,
+    I: ToBytes + FromBytes,
+    I: From<u64>,
+    I: Zero + One,
+    I: PartialOrd,
+    I: NumberTests,
+    I: ModPow,
+    I: TryModInv,
+    I: Add<I, Output = I>,
+    for<'a> &'a I: Sub<I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Div<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    /// Check the proof attached to a partial decryption of ciphertext `c`.
+    pub fn verify(&self, c: &Ciphertext<I>, partial: &PartialDecryption<I>) -> Result<(), Error> {
+        if partial.index == 0 || partial.index > self.parties {
+            return Err(Error::InvalidProof);
+        }
+        if NumberTests::is_negative(&partial.response) {
+            return Err(Error::InvalidProof);
+        }
+        let ref nn = self.nn;
+        let ref vk = self.vks[partial.index - 1];
+        let ref e = partial.challenge;
+        let ref z = partial.response;
+
+        let c4 = I::modpow(&c.0, &I::from(4), nn);
+        let value2 = I::modpow(&partial.value, &I::from(2), nn);
+        // recompute the commitments a = c4^z / value2^e and b = v^z / vk^e
+        let a = (I::modpow(&c4, z, nn) * &inverse(&I::modpow(&value2, e, nn), nn)?) % nn;
+        let b = (I::modpow(&self.v, z, nn) * &inverse(&I::modpow(vk, e, nn), nn)?) % nn;
+        let expected = fiat_shamir([&self.n, &self.v, &c4, vk, &value2, &a, &b]);
+        if &expected != e {
+            return Err(Error::InvalidProof);
+        }
+        Ok(())
+    }
+
+    /// Combine partial decryptions of ciphertext `c` into the plaintext.
+    ///
+    /// Partial decryptions failing verification, as well as repeated indices, are ignored; an
+    /// error is returned if fewer than `threshold` remain.
+    pub fn combine(
+        &self,
+        c: &Ciphertext<I>,
+        partials: &[PartialDecryption<I>],
+    ) -> Result<Plaintext<I>, Error> {
+        let mut selected: Vec<&PartialDecryption<I>> = Vec::with_capacity(self.threshold);
+        for partial in partials {
+            if selected.len() == self.threshold {
+                break;
+            }
+            if selected.iter().any(|s| s.index == partial.index) {
+                continue;
+            }
+            if self.verify(c, partial).is_ok() {
+                selected.push(partial);
+            }
+        }
+        if selected.len() < self.threshold {
+            return Err(Error::NotEnoughShares);
+        }
+
+        // interpolate in the exponent to obtain c^{4*delta^2*d}
+        let ref nn = self.nn;
+        let indices: Vec<usize> = selected.iter().map(|s| s.index).collect();
+        let mut combined = I::one();
+        for partial in selected {
+            let (coefficient, negative) = lagrange::<I>(partial.index, &indices, &self.delta);
+            let ref exponent = &coefficient * &I::from(2);
+            let mut x = I::modpow(&partial.value, exponent, nn);
+            if negative {
+                x = inverse(&x, nn)?;
+            }
+            combined = (combined * &x) % nn;
+        }
+
+        // since d = 1 mod n this is (1+n)^{4*delta^2*m}
+        let ref n = self.n;
+        let ref scale = (&self.delta * &self.delta) * &I::from(4);
+        let m = (l(&combined, n) * &I::modinv(scale, n)) % n;
+        Ok(Plaintext(m))
+    }
+}
+
+// Integer Lagrange coefficient `delta * prod_{j != i} j / (j - i)` for interpolating at zero,
+// returned as its absolute value and whether it is negative.
+fn lagrange<I>(i: usize, indices: &[usize], delta: &I) -> (I, bool)
+where
+    I: Clone,
+    I: From<u64>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Div<&'b I, Output = I>,
+{
+    let mut numerator = delta.clone();
+    let mut denominator = I::from(1);
+    let mut negative = false;
+    for &j in indices.iter().filter(|&&j| j != i) {
+        numerator = numerator * &I::from(j as u64);
+        if j > i {
+            denominator = denominator * &I::from((j - i) as u64);
+        } else {
+            denominator = denominator * &I::from((i - j) as u64);
+            negative = !negative;
+        }
+    }
+    // exact since delta = l! is divisible by the denominator
+    (numerator / &denominator, negative)
+}
+
+fn inverse<I>(x: &I, modulus: &I) -> Result<I, Error>
+where
+    I: TryModInv,
+{
+    I::try_modinv(x, modulus).map_err(|_| Error::InvalidProof)
+}
+
+// Challenge for the proof that the logarithms of `value^2` to base `c^4` and of the
+// verification key to base `v` are equal.
+fn fiat_shamir<I>(values: [&I; 7]) -> I
+where
+    I: ToBytes + FromBytes + NumberTests,
+{
+    let labels = ["n", "v", "c4", "vk", "value2", "a", "b"];
+    let mut transcript = Transcript::new(DOMAIN);
+    for (label, value) in labels.iter().zip(values.iter()) {
+        transcript.append_integer(label, *value);
+    }
+    transcript.challenge("e")
+}
+
+fn factorial<I>(k: usize) -> I
+where
+    I: From<u64>,
+    for<'b> I: Mul<&'b I, Output = I>,
+{
+    (1..k + 1).fold(I::from(1), |acc, i| acc * &I::from(i as u64))
+}
+
+bigint!(
+    I,
+    #[cfg(test)]
+    mod tests {
+
+        use super::I;
+        use crate::core::threshold::*;
+        use crate::AbstractPaillier;
+        use crate::Error;
+
+        fn test_keypair() -> Keypair<I> {
+            let p = str::parse("148677972634832330983979593310074301486537017973460461278300587514468301043894574906886127642530475786889672304776052879927627556769456140664043088700743909632312483413393134504352834240399191134336344285483935856491230340093391784574980688823380828143810804684752914935441384845195613674104960646037368551517").unwrap();
+            let q = str::parse("158741574437007245654463598139927898730476924736461654463975966787719309357536545869203069369466212089132653564188443272208127277664424448947476335413293018778018615899291704693105620242763173357203898195318179150836424196645745308205164116144020613415407736216097185962171301808761138424668335445923774195463").unwrap();
+            Keypair { p: p, q: q }
+        }
+
+        #[test]
+        fn test_threshold_decryption() {
+            let ref keypair = test_keypair();
+            let ek = standard::EncryptionKey::from(keypair);
+            let (vk, shares) = deal(keypair, 3, 5);
+
+            let m = Plaintext::from(10);
+            let c = AbstractPaillier::encrypt(&ek, &m);
+            let partials: Vec<PartialDecryption<I>> = shares
+                .iter()
+                .map(|share| AbstractPaillier::decrypt(share, &c))
+                .collect();
+            for partial in partials.iter() {
+                assert_eq!(vk.verify(&c, partial), Ok(()));
+            }
+
+            // any three shares suffice
+            assert_eq!(vk.combine(&c, &partials[0..3]), Ok(m.clone()));
+            assert_eq!(vk.combine(&c, &partials[2..5]), Ok(m.clone()));
+            let subset = vec![
+                partials[4].clone(),
+                partials[0].clone(),
+                partials[2].clone(),
+            ];
+            assert_eq!(vk.combine(&c, &subset), Ok(m));
+        }
+
+        #[test]
+        fn test_invalid_partial_decryption() {
+            let ref keypair = test_keypair();
+            let ek = standard::EncryptionKey::from(keypair);
+            let (vk, shares) = deal(keypair, 2, 3);
+
+            let m = Plaintext::from(10);
+            let c = AbstractPaillier::encrypt(&ek, &m);
+            let mut partials: Vec<PartialDecryption<I>> = shares
+                .iter()
+                .map(|share| AbstractPaillier::decrypt(share, &c))
+                .collect();
+
+            // tamper with the first partial decryption
+            partials[0].value = (&partials[0].value * &partials[0].value) % &vk.nn;
+            assert_eq!(vk.verify(&c, &partials[0]), Err(Error::InvalidProof));
+
+            // the bad partial decryption is skipped
+            assert_eq!(vk.combine(&c, &partials[0..2]), Err(Error::NotEnoughShares));
+            assert_eq!(vk.combine(&c, &partials), Ok(m));
+
+            // as are repeated ones
+            let repeated = vec![partials[1].clone(), partials[1].clone()];
+            assert_eq!(vk.combine(&c, &repeated), Err(Error::NotEnoughShares));
+        }
+    }
+);
@@ -0,0 +1,45 @@
+//! Errors reported by the fallible operations of the library.
+
+use std::fmt;
+
+/// Reasons for which an operation may fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Plaintext is not in the range `[0, n)` of the key.
+    PlaintextOutOfRange,
+    /// Ciphertext is not an element of `Z*_{n^2}` for the key.
+    InvalidCiphertext,
+    /// Randomness is not an element of `Z*_n` for the key.
+    InvalidRandomness,
+    /// No precomputed randomness is left in the pool.
+    RandomnessExhausted,
+    /// Element has no inverse for the given modulus.
+    NotInvertible,
+    /// Decoded value does not fit in the target type.
+    DecodeOverflow,
+    /// Value cannot be encoded or decoded exactly with the available precision.
+    PrecisionLoss,
+    /// Zero-knowledge proof does not verify.
+    InvalidProof,
+    /// Fewer valid decryption shares than the threshold were given.
+    NotEnoughShares,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match *self {
+            Error::PlaintextOutOfRange => "plaintext out of range",
+            Error::InvalidCiphertext => "invalid ciphertext",
+            Error::InvalidRandomness => "invalid randomness",
+            Error::RandomnessExhausted => "randomness pool exhausted",
+            Error::NotInvertible => "element not invertible",
+            Error::DecodeOverflow => "overflow while decoding",
+            Error::PrecisionLoss => "precision lost while encoding or decoding",
+            Error::InvalidProof => "invalid proof",
+            Error::NotEnoughShares => "not enough decryption shares",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl ::std::error::Error for Error {}
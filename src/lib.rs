@@ -9,14 +9,22 @@ extern crate test;
 mod macros;
 
 pub mod arithimpl;
+#[cfg(feature = "parallel")]
+pub mod batch;
 pub mod coding;
 pub mod core;
+pub mod error;
+pub mod proof;
 pub mod traits;
 
+#[cfg(feature = "serde")]
+mod serialize;
+
 pub use crate::coding::*;
 pub use crate::core::crt::DecryptionKey;
 pub use crate::core::standard::EncryptionKey;
 pub use crate::core::Keypair;
+pub use crate::error::Error;
 pub use crate::traits::*;
 
 /// Parameterised type onto which all operations are added (see `Paillier`).
@@ -78,3 +86,38 @@ mod gmpinstance {
 }
 #[cfg(feature = "inclgmp")]
 pub use self::gmpinstance::*;
+
+/**************
+ Rug instance
+**************/
+
+#[cfg(feature = "inclrug")]
+mod ruginstance {
+    pub use crate::arithimpl::rugimpl::BigInteger as RugBigInteger;
+    pub type RugPaillier = crate::AbstractPaillier<RugBigInteger>;
+
+    #[cfg(feature = "defaultrug")]
+    pub type BigInteger = RugBigInteger;
+    #[cfg(feature = "defaultrug")]
+    pub type Paillier = RugPaillier;
+}
+#[cfg(feature = "inclrug")]
+pub use self::ruginstance::*;
+
+/****************
+ Fixed instance
+****************/
+
+#[cfg(feature = "inclfixed")]
+mod fixedinstance {
+    pub use crate::arithimpl::fixedimpl::BigInteger as FixedBigInteger;
+    pub use crate::arithimpl::fixedimpl::{Fixed2048, Fixed3072, Fixed4096};
+    pub type FixedPaillier = crate::AbstractPaillier<FixedBigInteger>;
+
+    #[cfg(feature = "defaultfixed")]
+    pub type BigInteger = FixedBigInteger;
+    #[cfg(feature = "defaultfixed")]
+    pub type Paillier = FixedPaillier;
+}
+#[cfg(feature = "inclfixed")]
+pub use self::fixedinstance::*;
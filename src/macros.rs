@@ -1,5 +1,6 @@
 macro_rules! bigint {
-    ( $t:ident, $body:item ) => {
+    // the fixed-width type may be chosen, for modules needing more than n² arithmetic
+    ( $t:ident, fixed = $fixed:ty, $body:item ) => {
 
         #[cfg(feature="inclramp")]
         mod ramp {
@@ -22,5 +23,23 @@ macro_rules! bigint {
             $body
         }
 
+        #[cfg(feature="inclrug")]
+        mod rug {
+            #[allow(dead_code)]
+            type $t = crate::RugBigInteger;
+            $body
+        }
+
+        #[cfg(feature="inclfixed")]
+        mod fixed {
+            #[allow(dead_code)]
+            type $t = $fixed;
+            $body
+        }
+
+    };
+
+    ( $t:ident, $body:item ) => {
+        bigint!($t, fixed = crate::FixedBigInteger, $body);
     };
 }
@@ -0,0 +1,492 @@
+//! Proof that a ciphertext was obtained from another by a committed affine operation.
+//!
+//! Given a ciphertext `c` under the verifier's key, the prover computes `d = c^a * E(b)` for
+//! secret `a` and `b`, as in MtA-style protocols, and commits to `a` and `b` by encrypting them
+//! under its own key. The proof shows that the same `a` and `b` were used throughout and that
+//! they are small, which prevents the prover from substituting other values.
+//!
+//! This is the protocol of Canetti, Gennaro, Goldfeder, Makriyannis, and Peled (2020), using the
+//! verifier's `RingPedersen` setup to bind the prover to `a` and `b` as integers.
+
+use super::range::{bit_length, pedersen, pedersen_public, pow2, RingPedersen};
+use super::transcript::{Decoder, Encoder, Proof, Transcript};
+use super::ProofKey;
+use crate::arithimpl::traits::*;
+use crate::core::standard::EncryptionKey;
+use crate::core::{check_ciphertext, Ciphertext, Plaintext, Randomness};
+use crate::Error;
+use num_traits::One;
+use std::ops::{Add, Mul, Neg, Rem, Shl, Shr, Sub};
+
+const DOMAIN: &str = "paillier.proof.affine";
+
+/// Bit length of challenges.
+const CHALLENGE_BITS: usize = 128;
+
+/// Bits of statistical security with which the proof hides `a` and `b`.
+const STATISTICAL_BITS: usize = 80;
+
+/// Public values of an affine operation `d = c^a * (1+n)^b * r^n mod n^2`.
+#[derive(Debug, Clone)]
+pub struct AffineStatement<'s, I> {
+    /// Key of the verifier, under which `c` and `d` are encrypted.
+    pub ek: &'s EncryptionKey<I>,
+    /// Key of the prover, under which `a` and `b` are committed.
+    pub prover_ek: &'s EncryptionKey<I>,
+    /// Ciphertext to which the operation was applied.
+    pub c: &'s Ciphertext<I>,
+    /// Result of the operation.
+    pub d: &'s Ciphertext<I>,
+    /// Encryption of `a` under the prover's key.
+    pub x: &'s Ciphertext<I>,
+    /// Encryption of `b` under the prover's key.
+    pub y: &'s Ciphertext<I>,
+    /// Upper bound on the bit length of `a`.
+    pub a_bits: usize,
+    /// Upper bound on the bit length of `b`.
+    pub b_bits: usize,
+}
+
+/// Secret values of an affine operation, known only to the prover.
+#[derive(Debug, Clone)]
+pub struct AffineWitness<'w, I> {
+    /// Multiplier applied to `c`.
+    pub a: &'w Plaintext<I>,
+    /// Value added to `c^a`.
+    pub b: &'w Plaintext<I>,
+    /// Randomness used to encrypt `b` under the verifier's key.
+    pub r: &'w Randomness<I>,
+    /// Randomness used to encrypt `a` under the prover's key.
+    pub ra: &'w Randomness<I>,
+    /// Randomness used to encrypt `b` under the prover's key.
+    pub rb: &'w Randomness<I>,
+}
+
+/// Non-interactive proof of a committed affine operation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AffineProof<I> {
+    sa: I, // s^a t^mu_a mod N
+    sb: I, // s^b t^mu_b mod N
+    ad: I, // c^alpha (1+n)^beta r'^n mod n^2
+    ax: I, // encryption of alpha under the prover's key
+    ay: I, // encryption of beta under the prover's key
+    ea: I, // s^alpha t^gamma mod N
+    eb: I, // s^beta t^delta mod N
+    z1: I, // alpha + e*a
+    z2: I, // beta + e*b
+    z3: I, // gamma + e*mu_a
+    z4: I, // delta + e*mu_b
+    w: I,  // r' * r^e mod n
+    wx: I, // ra' * ra^e mod n'
+    wy: I, // rb' * rb^e mod n'
+}
+
+impl<I> AffineProof<I>
+where
+    I: Clone,
+    I: One,
+    I: PartialOrd,
+    I: ToBytes + FromBytes,
+    I: From<u64>,
+    I: NumberTests,
+    I: Samplable,
+    I: EGCD,
+    I: ModPow,
+    I: ModPowSec,
+    I: Add<I, Output = I>,
+    I: Shl<usize, Output = I>,
+    I: Shr<usize, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    /// Prove that `statement.d` was computed from `statement.c` using the committed values,
+    /// failing if these exceed the bounds of the statement, or if the bounds with their slack
+    /// reach any of the moduli involved.
+    ///
+    /// The setup must be that of the verifier.
+    pub fn prove(
+        setup: &RingPedersen<I>,
+        statement: &AffineStatement<I>,
+        witness: &AffineWitness<I>,
+    ) -> Result<AffineProof<I>, Error> {
+        let (n, nn) = (statement.ek.n(), statement.ek.nn());
+        let (pn, pnn) = (statement.prover_ek.n(), statement.prover_ek.nn());
+        let ref nhat = setup.n;
+        let (a, b) = (&witness.a.0, &witness.b.0);
+        for (x, bits) in &[(a, statement.a_bits), (b, statement.b_bits)] {
+            check_slack(*bits, &[nhat, n, pn]).map_err(|_| Error::PlaintextOutOfRange)?;
+            if NumberTests::is_negative(*x) || *x >= &pow2(*bits) {
+                return Err(Error::PlaintextOutOfRange);
+            }
+        }
+        let eps = CHALLENGE_BITS + STATISTICAL_BITS;
+        let a_bound = pow2::<I>(statement.a_bits);
+        let b_bound = pow2::<I>(statement.b_bits);
+
+        let ref alpha = I::sample(statement.a_bits + eps);
+        let ref beta = I::sample(statement.b_bits + eps);
+        let ref mu_a = I::sample_below(&(&a_bound * nhat));
+        let ref mu_b = I::sample_below(&(&b_bound * nhat));
+        let ref gamma = I::sample_below(&(pow2::<I>(eps) * &a_bound * nhat));
+        let ref delta = I::sample_below(&(pow2::<I>(eps) * &b_bound * nhat));
+        let ref rd = Randomness::sample(n).as_ref().clone();
+        let ref rx = Randomness::sample(pn).as_ref().clone();
+        let ref ry = Randomness::sample(pn).as_ref().clone();
+
+        let sa = pedersen(&setup.s, a, &setup.t, mu_a, nhat);
+        let sb = pedersen(&setup.s, b, &setup.t, mu_b, nhat);
+        let ad = (&((&modpow_secret(&statement.c.0, alpha, nn) * &statement.ek.gm(beta)) % nn)
+            * &modpow_secret(rd, n, nn))
+            % nn;
+        let ax = (statement.prover_ek.gm(alpha) * &modpow_secret(rx, pn, pnn)) % pnn;
+        let ay = (statement.prover_ek.gm(beta) * &modpow_secret(ry, pn, pnn)) % pnn;
+        let ea = pedersen(&setup.s, alpha, &setup.t, gamma, nhat);
+        let eb = pedersen(&setup.s, beta, &setup.t, delta, nhat);
+
+        let ref e = fiat_shamir(setup, statement, &[&sa, &sb, &ad, &ax, &ay, &ea, &eb]);
+        Ok(AffineProof {
+            sa: sa,
+            sb: sb,
+            ad: ad,
+            ax: ax,
+            ay: ay,
+            ea: ea,
+            eb: eb,
+            z1: alpha + &(e * a),
+            z2: beta + &(e * b),
+            z3: gamma + &(e * mu_a),
+            z4: delta + &(e * mu_b),
+            w: (rd * &modpow_secret(witness.r.as_ref(), e, n)) % n,
+            wx: (rx * &modpow_secret(witness.ra.as_ref(), e, pn)) % pn,
+            wy: (ry * &modpow_secret(witness.rb.as_ref(), e, pn)) % pn,
+        })
+    }
+
+    /// Verify that `statement.d` was computed from `statement.c` using the committed values.
+    pub fn verify(
+        &self,
+        setup: &RingPedersen<I>,
+        statement: &AffineStatement<I>,
+    ) -> Result<(), Error> {
+        let (n, nn) = (statement.ek.n(), statement.ek.nn());
+        let (pn, pnn) = (statement.prover_ek.n(), statement.prover_ek.nn());
+        let ref nhat = setup.n;
+        check_slack(statement.a_bits, &[nhat, n, pn])?;
+        check_slack(statement.b_bits, &[nhat, n, pn])?;
+
+        for x in &[&statement.c.0, &statement.d.0, &self.ad] {
+            check_ciphertext(*x, n, nn).map_err(|_| Error::InvalidProof)?;
+        }
+        for x in &[&statement.x.0, &statement.y.0, &self.ax, &self.ay] {
+            check_ciphertext(*x, pn, pnn).map_err(|_| Error::InvalidProof)?;
+        }
+        for x in &[&self.sa, &self.sb, &self.ea, &self.eb] {
+            Randomness::new((*x).clone(), nhat).map_err(|_| Error::InvalidProof)?;
+        }
+        Randomness::new(self.w.clone(), n).map_err(|_| Error::InvalidProof)?;
+        Randomness::new(self.wx.clone(), pn).map_err(|_| Error::InvalidProof)?;
+        Randomness::new(self.wy.clone(), pn).map_err(|_| Error::InvalidProof)?;
+        let eps = CHALLENGE_BITS + STATISTICAL_BITS;
+        if NumberTests::is_negative(&self.z1)
+            || NumberTests::is_negative(&self.z2)
+            || NumberTests::is_negative(&self.z3)
+            || NumberTests::is_negative(&self.z4)
+            || self.z1 >= pow2(statement.a_bits + eps + 1)
+            || self.z2 >= pow2(statement.b_bits + eps + 1)
+        {
+            return Err(Error::InvalidProof);
+        }
+
+        let ref e = fiat_shamir(
+            setup,
+            statement,
+            &[
+                &self.sa, &self.sb, &self.ad, &self.ax, &self.ay, &self.ea, &self.eb,
+            ],
+        );
+        let checks = [
+            (
+                (&((&I::modpow(&statement.c.0, &self.z1, nn) * &statement.ek.gm(&self.z2)) % nn)
+                    * &I::modpow(&self.w, n, nn))
+                    % nn,
+                (&self.ad * &I::modpow(&statement.d.0, e, nn)) % nn,
+            ),
+            (
+                (statement.prover_ek.gm(&self.z1) * &I::modpow(&self.wx, pn, pnn)) % pnn,
+                (&self.ax * &I::modpow(&statement.x.0, e, pnn)) % pnn,
+            ),
+            (
+                (statement.prover_ek.gm(&self.z2) * &I::modpow(&self.wy, pn, pnn)) % pnn,
+                (&self.ay * &I::modpow(&statement.y.0, e, pnn)) % pnn,
+            ),
+            (
+                pedersen_public(&setup.s, &self.z1, &setup.t, &self.z3, nhat),
+                (&self.ea * &I::modpow(&self.sa, e, nhat)) % nhat,
+            ),
+            (
+                pedersen_public(&setup.s, &self.z2, &setup.t, &self.z4, nhat),
+                (&self.eb * &I::modpow(&self.sb, e, nhat)) % nhat,
+            ),
+        ];
+        if checks.iter().any(|(lhs, rhs)| lhs != rhs) {
+            return Err(Error::InvalidProof);
+        }
+        Ok(())
+    }
+}
+
+// Check that responses for values of `bits` bits, including the slack of `CHALLENGE_BITS` and
+// `STATISTICAL_BITS`, stay below each of `moduli` and so cannot wrap around.
+fn check_slack<I>(bits: usize, moduli: &[&I]) -> Result<(), Error>
+where
+    I: Clone,
+    I: NumberTests,
+    I: Shr<usize, Output = I>,
+{
+    let slack = bits + CHALLENGE_BITS + STATISTICAL_BITS + 1;
+    if moduli.iter().any(|modulus| slack >= bit_length(*modulus)) {
+        return Err(Error::InvalidProof);
+    }
+    Ok(())
+}
+
+fn fiat_shamir<I>(setup: &RingPedersen<I>, statement: &AffineStatement<I>, commitments: &[&I]) -> I
+where
+    I: One,
+    I: ToBytes + FromBytes + NumberTests,
+    I: Add<I, Output = I>,
+    I: Shl<usize, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    let mut transcript = Transcript::new(DOMAIN);
+    transcript.append_key("ek", statement.ek);
+    transcript.append_key("prover_ek", statement.prover_ek);
+    transcript.append_integer("setup.n", &setup.n);
+    transcript.append_integer("setup.s", &setup.s);
+    transcript.append_integer("setup.t", &setup.t);
+    transcript.append_ciphertext("c", statement.c);
+    transcript.append_ciphertext("d", statement.d);
+    transcript.append_ciphertext("x", statement.x);
+    transcript.append_ciphertext("y", statement.y);
+    transcript.append_bytes("a_bits", &(statement.a_bits as u64).to_be_bytes());
+    transcript.append_bytes("b_bits", &(statement.b_bits as u64).to_be_bytes());
+    for a in commitments {
+        transcript.append_integer("commitment", *a);
+    }
+    transcript.challenge_below("e", &pow2(CHALLENGE_BITS))
+}
+
+impl<I> Proof for AffineProof<I>
+where
+    I: Clone,
+    I: One,
+    I: PartialOrd,
+    I: ToBytes + FromBytes,
+    I: From<u64>,
+    I: NumberTests,
+    I: Samplable,
+    I: EGCD,
+    I: ModPow,
+    I: ModPowSec,
+    I: Neg<Output = I>,
+    I: Add<I, Output = I>,
+    I: Shl<usize, Output = I>,
+    I: Shr<usize, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    type Statement<'s>
+        = (&'s RingPedersen<I>, AffineStatement<'s, I>)
+    where
+        Self: 's;
+
+    fn verify(&self, statement: &Self::Statement<'_>) -> Result<(), Error> {
+        let (setup, ref statement) = *statement;
+        AffineProof::verify(self, setup, statement)
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        for x in &[
+            &self.sa, &self.sb, &self.ad, &self.ax, &self.ay, &self.ea, &self.eb, &self.z1,
+            &self.z2, &self.z3, &self.z4, &self.w, &self.wx, &self.wy,
+        ] {
+            encoder.write_integer(*x);
+        }
+    }
+
+    fn decode(decoder: &mut Decoder) -> Result<Self, Error> {
+        Ok(AffineProof {
+            sa: decoder.read_integer()?,
+            sb: decoder.read_integer()?,
+            ad: decoder.read_integer()?,
+            ax: decoder.read_integer()?,
+            ay: decoder.read_integer()?,
+            ea: decoder.read_integer()?,
+            eb: decoder.read_integer()?,
+            z1: decoder.read_integer()?,
+            z2: decoder.read_integer()?,
+            z3: decoder.read_integer()?,
+            z4: decoder.read_integer()?,
+            w: decoder.read_integer()?,
+            wx: decoder.read_integer()?,
+            wy: decoder.read_integer()?,
+        })
+    }
+}
+
+bigint!(
+    I,
+    #[cfg(test)]
+    mod tests {
+
+        use super::I;
+        use crate::core::*;
+        use crate::proof::affine::*;
+        use crate::proof::range::RingPedersen;
+        use crate::proof::Proof;
+        use crate::*;
+
+        fn test_keypair() -> Keypair<I> {
+            let p = str::parse("148677972634832330983979593310074301486537017973460461278300587514468301043894574906886127642530475786889672304776052879927627556769456140664043088700743909632312483413393134504352834240399191134336344285483935856491230340093391784574980688823380828143810804684752914935441384845195613674104960646037368551517").unwrap();
+            let q = str::parse("158741574437007245654463598139927898730476924736461654463975966787719309357536545869203069369466212089132653564188443272208127277664424448947476335413293018778018615899291704693105620242763173357203898195318179150836424196645745308205164116144020613415407736216097185962171301808761138424668335445923774195463").unwrap();
+            Keypair { p: p, q: q }
+        }
+
+        fn test_prover_keypair() -> Keypair<I> {
+            let p = str::parse("12903881414585511172264417526338553849670448264656070269005198163801819791958578781491976357030717338187446119108967590352584144687956100408066792506882013").unwrap();
+            let q = str::parse("10864789119530378261420227632789604136610322985831143664906614745571965809417228913642942076392707196990417265625748004451706152732674737769545514873840647").unwrap();
+            Keypair { p: p, q: q }
+        }
+
+        fn test_setup() -> RingPedersen<I> {
+            let p = str::parse("10770908117087230285325789289897283940317801756288494110056130677249695557468535949664735832776330761204390292104453881822657254099741970459746021226115819").unwrap();
+            let q = str::parse("10142634210367665828052173247239184241335177139128114095655063569598821985124380111473658401798150425781798354151698455060921515828077107368408566127327187").unwrap();
+            RingPedersen::from(&Keypair { p: p, q: q })
+        }
+
+        #[test]
+        fn test_affine_proof() {
+            let ek = standard::EncryptionKey::from(&test_keypair());
+            let prover_ek = standard::EncryptionKey::from(&test_prover_keypair());
+            let setup = test_setup();
+
+            // client
+            let c = AbstractPaillier::encrypt(&ek, &core::Plaintext::from(7));
+
+            // server
+            let a = core::Plaintext::from(11);
+            let b = core::Plaintext::from(13);
+            let (eb, r) = AbstractPaillier::encrypt_returning_randomness(&ek, &b);
+            let d = AbstractPaillier::add(&ek, &AbstractPaillier::mul(&ek, &c, &a), &eb);
+            let (x, ra) = AbstractPaillier::encrypt_returning_randomness(&prover_ek, &a);
+            let (y, rb) = AbstractPaillier::encrypt_returning_randomness(&prover_ek, &b);
+            let statement = AffineStatement {
+                ek: &ek,
+                prover_ek: &prover_ek,
+                c: &c,
+                d: &d,
+                x: &x,
+                y: &y,
+                a_bits: 64,
+                b_bits: 64,
+            };
+            let witness = AffineWitness {
+                a: &a,
+                b: &b,
+                r: &r,
+                ra: &ra,
+                rb: &rb,
+            };
+            let proof = AffineProof::prove(&setup, &statement, &witness).unwrap();
+            assert_eq!(proof.verify(&setup, &statement), Ok(()));
+
+            let decoded = AffineProof::<I>::from_bytes(&Proof::to_bytes(&proof)).unwrap();
+            assert_eq!(
+                Proof::verify(&decoded, &(&setup, statement.clone())),
+                Ok(())
+            );
+
+            // substituting another multiplier is detected
+            let d2 = AbstractPaillier::add(
+                &ek,
+                &AbstractPaillier::mul(&ek, &c, &core::Plaintext::from(12)),
+                &eb,
+            );
+            let statement2 = AffineStatement {
+                d: &d2,
+                ..statement.clone()
+            };
+            assert_eq!(proof.verify(&setup, &statement2), Err(Error::InvalidProof));
+            let proof2 = AffineProof::prove(&setup, &statement2, &witness).unwrap();
+            assert_eq!(proof2.verify(&setup, &statement2), Err(Error::InvalidProof));
+
+            // as are values outside the bounds
+            let statement3 = AffineStatement {
+                a_bits: 3,
+                ..statement.clone()
+            };
+            assert_eq!(
+                AffineProof::prove(&setup, &statement3, &witness),
+                Err(Error::PlaintextOutOfRange)
+            );
+        }
+
+        #[test]
+        fn test_affine_proof_large_bounds() {
+            let ek = standard::EncryptionKey::from(&test_keypair());
+            let prover_ek = standard::EncryptionKey::from(&test_prover_keypair());
+            let setup = test_setup();
+
+            let c = AbstractPaillier::encrypt(&ek, &core::Plaintext::from(7));
+            let a = core::Plaintext::from(11);
+            let b = core::Plaintext::from(13);
+            let (eb, r) = AbstractPaillier::encrypt_returning_randomness(&ek, &b);
+            let d = AbstractPaillier::add(&ek, &AbstractPaillier::mul(&ek, &c, &a), &eb);
+            let (x, ra) = AbstractPaillier::encrypt_returning_randomness(&prover_ek, &a);
+            let (y, rb) = AbstractPaillier::encrypt_returning_randomness(&prover_ek, &b);
+            let statement = AffineStatement {
+                ek: &ek,
+                prover_ek: &prover_ek,
+                c: &c,
+                d: &d,
+                x: &x,
+                y: &y,
+                a_bits: 64,
+                b_bits: 64,
+            };
+            let witness = AffineWitness {
+                a: &a,
+                b: &b,
+                r: &r,
+                ra: &ra,
+                rb: &rb,
+            };
+            let proof = AffineProof::prove(&setup, &statement, &witness).unwrap();
+
+            // the slack of bounds close to the 1024-bit moduli would allow responses wrapping
+            // around them
+            for (a_bits, b_bits) in [(900, 64), (64, 900)] {
+                let large = AffineStatement {
+                    a_bits: a_bits,
+                    b_bits: b_bits,
+                    ..statement.clone()
+                };
+                assert_eq!(
+                    AffineProof::prove(&setup, &large, &witness),
+                    Err(Error::PlaintextOutOfRange)
+                );
+                assert_eq!(proof.verify(&setup, &large), Err(Error::InvalidProof));
+            }
+        }
+    }
+);
@@ -0,0 +1,256 @@
+//! Verifiable decryption, allowing anyone holding the encryption key to check a claimed
+//! plaintext.
+//!
+//! A plaintext `m` is the decryption of `c` exactly when `c * g^-m` is an `n`-th residue modulo
+//! `n^2`, and the key holder proves knowledge of its `n`-th root without revealing it.
+
+use super::transcript::{Decoder, Encoder, Proof, Transcript};
+use super::ProofKey;
+use crate::arithimpl::traits::*;
+use crate::core::crt::DecryptionKey;
+use crate::core::standard::EncryptionKey;
+use crate::core::{check_ciphertext, check_plaintext, Ciphertext, Keypair, Plaintext, Randomness};
+use crate::Error;
+use num_traits::One;
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+#[cfg(feature = "serde")]
+use crate::serialize::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "serde")]
+use std::fmt;
+#[cfg(feature = "serde")]
+use std::str::FromStr;
+
+const DOMAIN: &str = "paillier.proof.decryption";
+
+/// Proof that a plaintext is the decryption of a ciphertext.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecryptionProof<I> {
+    commitment: I, // s^n mod n^2
+    response: I,   // s * r^e mod n
+}
+
+/// Decrypt ciphertext `c`, additionally proving that the plaintext is correct.
+///
+/// The ciphertext is assumed to be well-formed; see
+/// `standard::EncryptionKey::validate_ciphertext`.
+pub fn decrypt_with_proof<I>(
+    dk: &DecryptionKey<I>,
+    c: &Ciphertext<I>,
+) -> (Plaintext<I>, DecryptionProof<I>)
+where
+    I: Clone,
+    I: One,
+    I: PartialOrd,
+    I: ToBytes + FromBytes,
+    I: From<u64>,
+    I: NumberTests,
+    I: Samplable,
+    I: EGCD,
+    I: ModInv,
+    I: ModPow,
+    I: ModPowSec,
+    I: Add<I, Output = I>,
+    for<'a> &'a I: Add<I, Output = I>,
+    for<'b> I: Add<&'b I, Output = I>,
+    for<'a> &'a I: Sub<I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a> &'a I: Mul<I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'b> I: Div<&'b I, Output = I>,
+    for<'a> I: Rem<&'a I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+{
+    let ref ek = EncryptionKey::from(&Keypair::from(dk));
+    let (n, nn) = (ek.n(), ek.nn());
+    let (m, r) = dk.open(c);
+
+    let ref s = Randomness::sample(n).as_ref().clone();
+    let commitment = modpow_secret(s, n, nn);
+    let ref e = fiat_shamir(ek, c, &m, &commitment);
+    let response = (s * &modpow_secret(r.as_ref(), e, n)) % n;
+
+    let proof = DecryptionProof {
+        commitment: commitment,
+        response: response,
+    };
+    (m, proof)
+}
+
+/// Verify that plaintext `m` is the decryption of ciphertext `c`.
+pub fn verify_decryption<I>(
+    ek: &EncryptionKey<I>,
+    c: &Ciphertext<I>,
+    m: &Plaintext<I>,
+    proof: &DecryptionProof<I>,
+) -> Result<(), Error>
+where
+    I: Clone,
+    I: One,
+    I: PartialOrd,
+    I: ToBytes + FromBytes,
+    I: From<u64>,
+    I: NumberTests,
+    I: EGCD,
+    I: ModPow,
+    I: Add<I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    let (n, nn) = (ek.n(), ek.nn());
+    check_ciphertext(&c.0, n, nn).map_err(|_| Error::InvalidProof)?;
+    check_plaintext(&m.0, n).map_err(|_| Error::InvalidProof)?;
+    check_ciphertext(&proof.commitment, n, nn).map_err(|_| Error::InvalidProof)?;
+    Randomness::new(proof.response.clone(), n).map_err(|_| Error::InvalidProof)?;
+
+    // since g^n = 1 we have g^-m = g^(n-m)
+    let ref u = (&c.0 * &ek.gm(&(n - &m.0))) % nn;
+    let ref e = fiat_shamir(ek, c, m, &proof.commitment);
+    let lhs = I::modpow(&proof.response, n, nn);
+    let rhs = (&proof.commitment * &I::modpow(u, e, nn)) % nn;
+    if lhs != rhs {
+        return Err(Error::InvalidProof);
+    }
+    Ok(())
+}
+
+fn fiat_shamir<I>(ek: &EncryptionKey<I>, c: &Ciphertext<I>, m: &Plaintext<I>, commitment: &I) -> I
+where
+    I: ToBytes + FromBytes + NumberTests,
+{
+    let mut transcript = Transcript::new(DOMAIN);
+    transcript.append_integer("n", &ek.n);
+    transcript.append_ciphertext("c", c);
+    transcript.append_plaintext("m", m);
+    transcript.append_integer("commitment", commitment);
+    transcript.challenge("e")
+}
+
+impl<I> Proof for DecryptionProof<I>
+where
+    I: Clone,
+    I: One,
+    I: PartialOrd,
+    I: ToBytes + FromBytes,
+    I: From<u64>,
+    I: NumberTests,
+    I: EGCD,
+    I: ModPow,
+    I: Neg<Output = I>,
+    I: Add<I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    type Statement<'s>
+        = (&'s EncryptionKey<I>, &'s Ciphertext<I>, &'s Plaintext<I>)
+    where
+        Self: 's;
+
+    fn verify(&self, statement: &Self::Statement<'_>) -> Result<(), Error> {
+        let (ek, c, m) = *statement;
+        verify_decryption(ek, c, m, self)
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_integer(&self.commitment);
+        encoder.write_integer(&self.response);
+    }
+
+    fn decode(decoder: &mut Decoder) -> Result<Self, Error> {
+        Ok(DecryptionProof {
+            commitment: decoder.read_integer()?,
+            response: decoder.read_integer()?,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<I> Serialize for DecryptionProof<I>
+where
+    I: fmt::Display,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        DecryptionProofRepr {
+            commitment: to_repr(&self.commitment),
+            response: to_repr(&self.response),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, I> Deserialize<'de> for DecryptionProof<I>
+where
+    I: FromStr,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = DecryptionProofRepr::deserialize(deserializer)?;
+        Ok(DecryptionProof {
+            commitment: from_repr(&repr.commitment)?,
+            response: from_repr(&repr.response)?,
+        })
+    }
+}
+
+bigint!(
+    I,
+    #[cfg(test)]
+    mod tests {
+
+        use super::I;
+        use crate::core::*;
+        use crate::proof::decryption::*;
+        use crate::proof::Proof;
+        use crate::*;
+
+        fn test_keypair() -> Keypair<I> {
+            let p = str::parse("148677972634832330983979593310074301486537017973460461278300587514468301043894574906886127642530475786889672304776052879927627556769456140664043088700743909632312483413393134504352834240399191134336344285483935856491230340093391784574980688823380828143810804684752914935441384845195613674104960646037368551517").unwrap();
+            let q = str::parse("158741574437007245654463598139927898730476924736461654463975966787719309357536545869203069369466212089132653564188443272208127277664424448947476335413293018778018615899291704693105620242763173357203898195318179150836424196645745308205164116144020613415407736216097185962171301808761138424668335445923774195463").unwrap();
+            Keypair { p: p, q: q }
+        }
+
+        #[test]
+        fn test_verifiable_decryption() {
+            let (ek, dk) = test_keypair().keys();
+            let c1 = AbstractPaillier::encrypt(&ek, &core::Plaintext::from(10));
+            let c2 = AbstractPaillier::encrypt(&ek, &core::Plaintext::from(20));
+            let c = AbstractPaillier::add(&ek, &c1, &c2);
+
+            let (m, proof) = decrypt_with_proof(&dk, &c);
+            assert_eq!(m, core::Plaintext::from(30));
+            assert_eq!(verify_decryption(&ek, &c, &m, &proof), Ok(()));
+
+            let decoded = DecryptionProof::<I>::from_bytes(&Proof::to_bytes(&proof)).unwrap();
+            assert_eq!(Proof::verify(&decoded, &(&ek, &c, &m)), Ok(()));
+
+            let wrong = core::Plaintext::from(31);
+            assert_eq!(
+                verify_decryption(&ek, &c, &wrong, &proof),
+                Err(Error::InvalidProof)
+            );
+            assert_eq!(
+                verify_decryption(&ek, &c1, &m, &proof),
+                Err(Error::InvalidProof)
+            );
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn test_serialize() {
+            let (ek, dk) = test_keypair().keys();
+            let c = AbstractPaillier::encrypt(&ek, &core::Plaintext::from(10));
+            let (m, proof) = decrypt_with_proof(&dk, &c);
+
+            let json = serde_json::to_string(&proof).unwrap();
+            let proof: DecryptionProof<I> = serde_json::from_str(&json).unwrap();
+            assert_eq!(verify_decryption(&ek, &c, &m, &proof), Ok(()));
+        }
+    }
+);
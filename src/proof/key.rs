@@ -0,0 +1,476 @@
+//! Proofs that an encryption key is well-formed, to be checked before encrypting under it.
+//!
+//! Both proofs are produced by the key holder from the `Keypair` (which may be obtained from a
+//! `crt::DecryptionKey`), and verified using only the `standard::EncryptionKey`.
+
+use super::transcript::{Decoder, Encoder, Proof, Transcript};
+use crate::arithimpl::primes::{has_small_factor, is_prime, jacobi};
+use crate::arithimpl::traits::*;
+use crate::core::standard::EncryptionKey;
+use crate::core::Keypair;
+use crate::Error;
+use num_traits::{One, Zero};
+use std::ops::{Add, Div, Mul, Neg, Rem, Shr, Sub};
+
+const CORRECT_KEY_DOMAIN: &str = "paillier.proof.correct-key";
+const BLUM_MODULUS_DOMAIN: &str = "paillier.proof.blum-modulus";
+
+// After trial division every prime factor of n exceeds 2^14, so a modulus sharing a factor with
+// phi(n) passes each round with probability below 2^-14.
+const CORRECT_KEY_ROUNDS: usize = 10;
+
+// Each round catches a modulus that is not a Paillier-Blum modulus with probability one half.
+const BLUM_MODULUS_ROUNDS: usize = 80;
+
+/// Proof that `gcd(n, phi(n)) = 1`, which in particular means that `n` is square-free.
+///
+/// Consists of `n`-th roots of challenges derived from `n`, which exist for all challenges only
+/// if raising to the power `n` is a permutation of `Z*_n`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorrectKeyProof<I> {
+    roots: Vec<I>,
+}
+
+impl<I> CorrectKeyProof<I>
+where
+    I: Clone,
+    I: PartialOrd,
+    I: ToBytes + FromBytes,
+    I: From<u32> + From<u64>,
+    I: Zero + One + NumberTests,
+    I: ModPow,
+    I: ModPowSec,
+    I: ModInv,
+    I: Add<I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+{
+    /// Prove that the modulus of `keypair` is correct.
+    pub fn prove(keypair: &Keypair<I>) -> CorrectKeyProof<I> {
+        let ref one = I::one();
+        let ref n = &keypair.p * &keypair.q;
+        let ref phi = &(&keypair.p - one) * &(&keypair.q - one);
+        let ref ninv = I::modinv(n, phi);
+        let roots = challenges(CORRECT_KEY_DOMAIN, n, &[], CORRECT_KEY_ROUNDS)
+            .iter()
+            .map(|rho| modpow_secret(rho, ninv, n))
+            .collect();
+        CorrectKeyProof { roots: roots }
+    }
+
+    /// Verify that the modulus of `ek` is correct.
+    pub fn verify(&self, ek: &EncryptionKey<I>) -> Result<(), Error> {
+        let ref n = ek.n;
+        if n <= &I::one() || NumberTests::is_even(n) || has_small_factor(n) {
+            return Err(Error::InvalidProof);
+        }
+        if self.roots.len() != CORRECT_KEY_ROUNDS {
+            return Err(Error::InvalidProof);
+        }
+        let rhos = challenges(CORRECT_KEY_DOMAIN, n, &[], CORRECT_KEY_ROUNDS);
+        for (rho, sigma) in rhos.iter().zip(self.roots.iter()) {
+            if &I::modpow(sigma, n, n) != rho {
+                return Err(Error::InvalidProof);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Proof that `n` is a Paillier-Blum modulus, i.e. that `n = pq` for primes `p = q = 3 mod 4`
+/// and that `gcd(n, phi(n)) = 1`.
+///
+/// This is the protocol of Canetti, Gennaro, Goldfeder, Makriyannis, and Peled (2020), in which
+/// the prover shows that every challenge or a fixed modification of it has a fourth root.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlumModulusProof<I> {
+    w: I, // element with Jacobi symbol -1
+    rounds: Vec<BlumModulusRound<I>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct BlumModulusRound<I> {
+    x: I,    // fourth root of (-1)^a * w^b * y
+    a: bool, // whether y was negated
+    b: bool, // whether y was multiplied by w
+    z: I,    // n-th root of y
+}
+
+impl<I> BlumModulusProof<I>
+where
+    I: Clone,
+    I: Ord,
+    I: ToBytes + FromBytes,
+    I: From<u32> + From<u64>,
+    I: Zero + One + Neg<Output = I> + NumberTests,
+    I: Samplable,
+    I: BitManipulation,
+    I: ModPow,
+    I: ModPowSec,
+    I: ModInv,
+    I: Add<I, Output = I>,
+    I: Sub<I, Output = I>,
+    I: Shr<usize, Output = I>,
+    for<'a> &'a I: Mul<I, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Div<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Add<&'b I, Output = I>,
+    for<'b> I: Sub<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Div<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    /// Prove that the modulus of `keypair` is a Paillier-Blum modulus.
+    ///
+    /// Panics if either prime is not congruent to 3 modulo 4.
+    pub fn prove(keypair: &Keypair<I>) -> BlumModulusProof<I> {
+        let ref one = I::one();
+        let ref four = I::from(4_u64);
+        let ref p = keypair.p;
+        let ref q = keypair.q;
+        assert!(
+            p % four == I::from(3_u64) && q % four == I::from(3_u64),
+            "primes must be congruent to 3 modulo 4"
+        );
+        let ref n = p * q;
+        let ref phi = &(p - one) * &(q - one);
+        let ref ninv = I::modinv(n, phi);
+
+        let w = loop {
+            let w = I::sample_below(n);
+            if jacobi(&w, n) == -1 {
+                break w;
+            }
+        };
+
+        // exponents taking a quadratic residue to a fourth root that is itself a residue
+        let ref ep = fourth_root_exponent(p);
+        let ref eq = fourth_root_exponent(q);
+        let ref pinvq = I::modinv(p, q);
+
+        let rounds = challenges(BLUM_MODULUS_DOMAIN, n, &[&w], BLUM_MODULUS_ROUNDS)
+            .into_iter()
+            .map(|y| {
+                // exactly one of y, -y, wy, -wy is a quadratic residue modulo both primes
+                let (a, b, yy) = [(false, false), (true, false), (false, true), (true, true)]
+                    .iter()
+                    .map(|&(a, b)| (a, b, modify(&y, &w, a, b, n)))
+                    .find(|(_, _, yy)| jacobi(yy, p) != -1 && jacobi(yy, q) != -1)
+                    .expect("modulus is not a Paillier-Blum modulus");
+                let xp = modpow_secret(&(&yy % p), ep, p);
+                let xq = modpow_secret(&(&yy % q), eq, q);
+                BlumModulusRound {
+                    x: crt(&xp, &xq, p, q, pinvq),
+                    a: a,
+                    b: b,
+                    z: modpow_secret(&y, ninv, n),
+                }
+            })
+            .collect();
+
+        BlumModulusProof {
+            w: w,
+            rounds: rounds,
+        }
+    }
+
+    /// Verify that the modulus of `ek` is a Paillier-Blum modulus.
+    pub fn verify(&self, ek: &EncryptionKey<I>) -> Result<(), Error> {
+        let ref n = ek.n;
+        if n <= &I::one() || NumberTests::is_even(n) || is_prime(n) {
+            return Err(Error::InvalidProof);
+        }
+        if self.rounds.len() != BLUM_MODULUS_ROUNDS {
+            return Err(Error::InvalidProof);
+        }
+        // w must be a unit of Jacobi symbol -1; with for instance w = 0 every fourth root check
+        // could be passed by x = 0, leaving only the n-th roots to be proven
+        if jacobi(&self.w, n) != -1 {
+            return Err(Error::InvalidProof);
+        }
+        let ref four = I::from(4_u64);
+        let ys = challenges(BLUM_MODULUS_DOMAIN, n, &[&self.w], BLUM_MODULUS_ROUNDS);
+        for (y, round) in ys.iter().zip(self.rounds.iter()) {
+            if &I::modpow(&round.z, n, n) != y {
+                return Err(Error::InvalidProof);
+            }
+            let yy = modify(y, &self.w, round.a, round.b, n);
+            if I::modpow(&round.x, four, n) != yy {
+                return Err(Error::InvalidProof);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<I> Proof for CorrectKeyProof<I>
+where
+    I: Clone,
+    I: PartialOrd,
+    I: ToBytes + FromBytes,
+    I: From<u32> + From<u64>,
+    I: Zero + One + Neg<Output = I> + NumberTests,
+    I: ModPow,
+    I: ModPowSec,
+    I: ModInv,
+    I: Add<I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+{
+    type Statement<'s>
+        = EncryptionKey<I>
+    where
+        Self: 's;
+
+    fn verify(&self, ek: &EncryptionKey<I>) -> Result<(), Error> {
+        CorrectKeyProof::verify(self, ek)
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_integers(&self.roots);
+    }
+
+    fn decode(decoder: &mut Decoder) -> Result<Self, Error> {
+        Ok(CorrectKeyProof {
+            roots: decoder.read_integers()?,
+        })
+    }
+}
+
+impl<I> Proof for BlumModulusProof<I>
+where
+    I: Clone,
+    I: Ord,
+    I: ToBytes + FromBytes,
+    I: From<u32> + From<u64>,
+    I: Zero + One + Neg<Output = I> + NumberTests,
+    I: Samplable,
+    I: BitManipulation,
+    I: ModPow,
+    I: ModPowSec,
+    I: ModInv,
+    I: Add<I, Output = I>,
+    I: Sub<I, Output = I>,
+    I: Shr<usize, Output = I>,
+    for<'a> &'a I: Mul<I, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Div<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Add<&'b I, Output = I>,
+    for<'b> I: Sub<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Div<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    type Statement<'s>
+        = EncryptionKey<I>
+    where
+        Self: 's;
+
+    fn verify(&self, ek: &EncryptionKey<I>) -> Result<(), Error> {
+        BlumModulusProof::verify(self, ek)
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_integer(&self.w);
+        encoder.write_u64(self.rounds.len() as u64);
+        for round in &self.rounds {
+            encoder.write_integer(&round.x);
+            encoder.write_bool(round.a);
+            encoder.write_bool(round.b);
+            encoder.write_integer(&round.z);
+        }
+    }
+
+    fn decode(decoder: &mut Decoder) -> Result<Self, Error> {
+        let w = decoder.read_integer()?;
+        let len = decoder.read_u64()?;
+        let mut rounds = vec![];
+        for _ in 0..len {
+            rounds.push(BlumModulusRound {
+                x: decoder.read_integer()?,
+                a: decoder.read_bool()?,
+                b: decoder.read_bool()?,
+                z: decoder.read_integer()?,
+            });
+        }
+        Ok(BlumModulusProof {
+            w: w,
+            rounds: rounds,
+        })
+    }
+}
+
+// Challenges in `Z_n` for the given number of rounds, derived from `n` and `values`.
+fn challenges<I>(domain: &str, n: &I, values: &[&I], rounds: usize) -> Vec<I>
+where
+    I: ToBytes + FromBytes + NumberTests,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+{
+    let mut transcript = Transcript::new(domain);
+    transcript.append_integer("n", n);
+    for value in values {
+        transcript.append_integer("value", *value);
+    }
+    (0..rounds)
+        .map(|_| transcript.challenge_below("y", n))
+        .collect()
+}
+
+// Compute (-1)^a * w^b * y mod n.
+fn modify<I>(y: &I, w: &I, a: bool, b: bool, n: &I) -> I
+where
+    I: Clone,
+    I: NumberTests,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+{
+    let y = if b { &(w * y) % n } else { y.clone() };
+    if a && !NumberTests::is_zero(&y) {
+        n - &y
+    } else {
+        y
+    }
+}
+
+// For p = 3 mod 4 the map x -> x^((p+1)/4) takes a quadratic residue to its square root that is
+// itself a residue, so applying it twice gives a fourth root.
+fn fourth_root_exponent<I>(p: &I) -> I
+where
+    I: From<u64>,
+    I: One,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Div<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+{
+    let ref one = I::one();
+    let ref e = &(p + one) / &I::from(4_u64);
+    &(e * e) % &(p - one)
+}
+
+fn crt<I>(xp: &I, xq: &I, p: &I, q: &I, pinvq: &I) -> I
+where
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+{
+    // x = xp + p * ((xq - xp) * p^-1 mod q), keeping all values non-negative
+    let ref diff = &(&(xq + q) - &(xp % q)) % q;
+    let ref u = &(diff * pinvq) % q;
+    xp + &(p * u)
+}
+
+bigint!(
+    I,
+    #[cfg(test)]
+    mod tests {
+
+        use super::I;
+        use crate::arithimpl::traits::*;
+        use crate::core::*;
+        use crate::proof::key::*;
+        use crate::proof::Proof;
+        use crate::traits::DefaultKeys;
+        use crate::Error;
+
+        fn test_keypair() -> Keypair<I> {
+            let p = str::parse("148677972634832330983979593310074301486537017973460461278300587514468301043894574906886127642530475786889672304776052879927627556769456140664043088700743909632312483413393134504352834240399191134336344285483935856491230340093391784574980688823380828143810804684752914935441384845195613674104960646037368551517").unwrap();
+            let q = str::parse("158741574437007245654463598139927898730476924736461654463975966787719309357536545869203069369466212089132653564188443272208127277664424448947476335413293018778018615899291704693105620242763173357203898195318179150836424196645745308205164116144020613415407736216097185962171301808761138424668335445923774195463").unwrap();
+            Keypair { p: p, q: q }
+        }
+
+        fn test_blum_keypair() -> Keypair<I> {
+            // 512 bit primes congruent to 3 modulo 4
+            let p = str::parse("10770908117087230285325789289897283940317801756288494110056130677249695557468535949664735832776330761204390292104453881822657254099741970459746021226115819").unwrap();
+            let q = str::parse("10142634210367665828052173247239184241335177139128114095655063569598821985124380111473658401798150425781798354151698455060921515828077107368408566127327187").unwrap();
+            Keypair { p: p, q: q }
+        }
+
+        #[test]
+        fn test_correct_key_proof() {
+            let ref keypair = test_keypair();
+            let (ek, dk) = keypair.keys();
+            let proof = CorrectKeyProof::prove(&Keypair::from(&dk));
+            assert_eq!(proof.verify(&ek), Ok(()));
+
+            // the proof is bound to the modulus
+            let other = standard::EncryptionKey::from(&test_blum_keypair());
+            assert_eq!(proof.verify(&other), Err(Error::InvalidProof));
+
+            // moduli with small factors are rejected outright
+            let small = standard::EncryptionKey::from_modulus(&ek.n * &I::from(3_u64));
+            assert_eq!(proof.verify(&small), Err(Error::InvalidProof));
+
+            let mut tampered = proof.clone();
+            tampered.roots[0] = &tampered.roots[0] + &I::from(1_u64);
+            assert_eq!(tampered.verify(&ek), Err(Error::InvalidProof));
+        }
+
+        #[test]
+        fn test_blum_modulus_proof() {
+            let ref keypair = test_blum_keypair();
+            let ek = standard::EncryptionKey::from(keypair);
+            let proof = BlumModulusProof::prove(keypair);
+            assert_eq!(proof.verify(&ek), Ok(()));
+
+            let other = standard::EncryptionKey::from(&test_keypair());
+            assert_eq!(proof.verify(&other), Err(Error::InvalidProof));
+
+            let mut tampered = proof.clone();
+            tampered.rounds[0].a = !tampered.rounds[0].a;
+            assert_eq!(tampered.verify(&ek), Err(Error::InvalidProof));
+
+            let decoded = BlumModulusProof::<I>::from_bytes(&Proof::to_bytes(&proof)).unwrap();
+            assert_eq!(decoded, proof);
+            assert_eq!(Proof::verify(&decoded, &ek), Ok(()));
+        }
+
+        #[test]
+        fn test_blum_modulus_proof_rejects_degenerate_w() {
+            // a square-free modulus with three prime factors and n-th roots known to the prover
+            let ref r = test_keypair().p;
+            let Keypair { p, q } = test_blum_keypair();
+            let ref n: I = &(&p * &q) * r;
+            let ref one = I::from(1_u64);
+            let ref phi: I = &(&(&p - one) * &(&q - one)) * &(r - one);
+            let ref ninv = <I as ModInv>::modinv(n, phi);
+            let ek = standard::EncryptionKey::from_modulus(n.clone());
+
+            // with w = 0 and every y multiplied by it, x = 0 is a fourth root in each round
+            let w = I::from(0_u64);
+            let rounds = challenges(BLUM_MODULUS_DOMAIN, n, &[&w], BLUM_MODULUS_ROUNDS)
+                .iter()
+                .map(|y| BlumModulusRound {
+                    x: I::from(0_u64),
+                    a: false,
+                    b: true,
+                    z: I::modpow(y, ninv, n),
+                })
+                .collect();
+            let forged = BlumModulusProof {
+                w: w,
+                rounds: rounds,
+            };
+            assert_eq!(forged.verify(&ek), Err(Error::InvalidProof));
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_blum_modulus_proof_requires_blum_primes() {
+            BlumModulusProof::prove(&test_keypair());
+        }
+    }
+);
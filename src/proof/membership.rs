@@ -0,0 +1,335 @@
+//! Proof that a ciphertext encrypts a member of a small public set, such as `{0, 1}`.
+//!
+//! A ciphertext `c` encrypts `v` exactly when `c * g^-v` is an `n`-th residue, and the prover
+//! shows that this holds for one of the values without revealing which, by simulating the proofs
+//! for all other values as in the disjunctive proofs of Cramer, Damgard, and Schoenmakers (1994).
+
+use super::transcript::{Decoder, Encoder, Proof, Transcript};
+use super::ProofKey;
+use crate::arithimpl::traits::*;
+use crate::core::{check_ciphertext, check_plaintext, Ciphertext, Plaintext, Randomness};
+use crate::Error;
+use num_traits::{One, Zero};
+use std::ops::{Add, Mul, Neg, Rem, Sub};
+
+const DOMAIN: &str = "paillier.proof.membership";
+
+/// Non-interactive proof that a ciphertext encrypts one of a public set of values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MembershipProof<I> {
+    branches: Vec<Branch<I>>,
+}
+
+// Proof that `c * g^-v` is an `n`-th residue for one value `v` of the set.
+#[derive(Debug, Clone, PartialEq)]
+struct Branch<I> {
+    a: I, // commitment
+    e: I, // challenge, with those of all branches summing to the overall challenge
+    z: I, // response
+}
+
+/// Randomness kept secret by the prover between the commitment and the response.
+pub(super) struct MembershipNonce<I> {
+    index: usize, // the branch taken
+    s: I,
+}
+
+impl<I> MembershipProof<I>
+where
+    I: Clone,
+    I: PartialOrd,
+    I: ToBytes + FromBytes,
+    I: From<u64>,
+    I: Zero + One + NumberTests,
+    I: Samplable,
+    I: EGCD,
+    I: ModInv,
+    I: ModPow,
+    I: ModPowSec,
+    I: Add<I, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    /// Prove that `c`, encrypting `m` under `ek` using randomness `r`, contains one of `values`,
+    /// failing if `m` is not among them.
+    pub fn prove<K>(
+        ek: &K,
+        c: &Ciphertext<I>,
+        values: &[Plaintext<I>],
+        m: &Plaintext<I>,
+        r: &Randomness<I>,
+    ) -> Result<MembershipProof<I>, Error>
+    where
+        K: ProofKey<I> + ?Sized,
+    {
+        let index = values
+            .iter()
+            .position(|v| v == m)
+            .ok_or(Error::PlaintextOutOfRange)?;
+        for v in values {
+            check_plaintext(&v.0, ek.n())?;
+        }
+        let (mut proof, nonce) = Self::commit(ek, &c.0, values, index);
+        let ref e = fiat_shamir(ek, &c.0, values, &proof.commitments());
+        proof.respond(ek.n(), nonce, r.as_ref(), e);
+        Ok(proof)
+    }
+
+    /// Verify that `c` contains one of `values`.
+    pub fn verify<K>(&self, ek: &K, c: &Ciphertext<I>, values: &[Plaintext<I>]) -> Result<(), Error>
+    where
+        K: ProofKey<I> + ?Sized,
+    {
+        check_ciphertext(&c.0, ek.n(), ek.nn()).map_err(|_| Error::InvalidProof)?;
+        for v in values {
+            check_plaintext(&v.0, ek.n()).map_err(|_| Error::InvalidProof)?;
+        }
+        let ref e = fiat_shamir(ek, &c.0, values, &self.commitments());
+        self.check(ek, &c.0, values, e)
+    }
+
+    /// Commit to the branch for `values[index]` and simulate all others.
+    pub(super) fn commit<K>(
+        ek: &K,
+        c: &I,
+        values: &[Plaintext<I>],
+        index: usize,
+    ) -> (MembershipProof<I>, MembershipNonce<I>)
+    where
+        K: ProofKey<I> + ?Sized,
+    {
+        let (n, nn) = (ek.n(), ek.nn());
+        let s = Randomness::sample(n).as_ref().clone();
+        let branches = values
+            .iter()
+            .enumerate()
+            .map(|(j, v)| {
+                if j == index {
+                    Branch {
+                        a: modpow_secret(&s, n, nn),
+                        e: I::zero(),
+                        z: I::zero(),
+                    }
+                } else {
+                    // choose the challenge and response first, then solve for the commitment
+                    let e = I::sample_below(n);
+                    let z = Randomness::sample(n).as_ref().clone();
+                    let ue = I::modpow(&residue(ek, c, v), &e, nn);
+                    let a = (I::modpow(&z, n, nn) * &I::modinv(&ue, nn)) % nn;
+                    Branch { a: a, e: e, z: z }
+                }
+            })
+            .collect();
+        let proof = MembershipProof { branches: branches };
+        (proof, MembershipNonce { index: index, s: s })
+    }
+
+    /// Complete the branch taken for challenge `e`, using randomness `r` of the ciphertext.
+    pub(super) fn respond(&mut self, n: &I, nonce: MembershipNonce<I>, r: &I, e: &I) {
+        let simulated = self
+            .branches
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != nonce.index)
+            .fold(I::zero(), |acc, (_, branch)| &(&acc + &branch.e) % n);
+        let ref er = &(&(e % n) + n) - &simulated;
+        let ref er = er % n;
+        let branch = &mut self.branches[nonce.index];
+        branch.z = (nonce.s * &modpow_secret(r, er, n)) % n;
+        branch.e = er.clone();
+    }
+
+    /// Check all branches against challenge `e`.
+    pub(super) fn check<K>(
+        &self,
+        ek: &K,
+        c: &I,
+        values: &[Plaintext<I>],
+        e: &I,
+    ) -> Result<(), Error>
+    where
+        K: ProofKey<I> + ?Sized,
+    {
+        let (n, nn) = (ek.n(), ek.nn());
+        if self.branches.len() != values.len() {
+            return Err(Error::InvalidProof);
+        }
+        let mut sum = I::zero();
+        for (branch, v) in self.branches.iter().zip(values.iter()) {
+            check_ciphertext(&branch.a, n, nn).map_err(|_| Error::InvalidProof)?;
+            Randomness::new(branch.z.clone(), n).map_err(|_| Error::InvalidProof)?;
+            if NumberTests::is_negative(&branch.e) || &branch.e >= n {
+                return Err(Error::InvalidProof);
+            }
+            let lhs = I::modpow(&branch.z, n, nn);
+            let rhs = (&branch.a * &I::modpow(&residue(ek, c, v), &branch.e, nn)) % nn;
+            if lhs != rhs {
+                return Err(Error::InvalidProof);
+            }
+            sum = &(&sum + &branch.e) % n;
+        }
+        if sum != e % n {
+            return Err(Error::InvalidProof);
+        }
+        Ok(())
+    }
+}
+
+impl<I> MembershipProof<I> {
+    /// Commitments of all branches, to be included in the challenge.
+    pub(super) fn commitments(&self) -> Vec<&I> {
+        self.branches.iter().map(|branch| &branch.a).collect()
+    }
+}
+
+// Compute `c * g^-v mod n^2`, which is an `n`-th residue if and only if `c` encrypts `v`.
+fn residue<I, K>(ek: &K, c: &I, v: &Plaintext<I>) -> I
+where
+    K: ProofKey<I> + ?Sized,
+    I: ModInv,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    (c * &I::modinv(&ek.gm(&v.0), ek.nn())) % ek.nn()
+}
+
+fn fiat_shamir<I, K>(ek: &K, c: &I, values: &[Plaintext<I>], commitments: &[&I]) -> I
+where
+    K: ProofKey<I> + ?Sized,
+    I: One,
+    I: ToBytes + FromBytes + NumberTests,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+{
+    let mut transcript = Transcript::new(DOMAIN);
+    transcript.append_key("ek", ek);
+    transcript.append_integer("c", c);
+    for v in values {
+        transcript.append_plaintext("value", v);
+    }
+    for a in commitments {
+        transcript.append_integer("commitment", *a);
+    }
+    transcript.challenge_below("e", ek.n())
+}
+
+impl<I> Proof for MembershipProof<I>
+where
+    I: Clone,
+    I: PartialOrd,
+    I: ToBytes + FromBytes,
+    I: From<u64>,
+    I: Zero + One + NumberTests,
+    I: Samplable,
+    I: EGCD,
+    I: ModInv,
+    I: ModPow,
+    I: ModPowSec,
+    I: Neg<Output = I>,
+    I: Add<I, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    type Statement<'s>
+        = (&'s dyn ProofKey<I>, &'s Ciphertext<I>, &'s [Plaintext<I>])
+    where
+        Self: 's;
+
+    fn verify(&self, statement: &Self::Statement<'_>) -> Result<(), Error> {
+        let (ek, c, values) = *statement;
+        MembershipProof::verify(self, ek, c, values)
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_u64(self.branches.len() as u64);
+        for branch in &self.branches {
+            encoder.write_integer(&branch.a);
+            encoder.write_integer(&branch.e);
+            encoder.write_integer(&branch.z);
+        }
+    }
+
+    fn decode(decoder: &mut Decoder) -> Result<Self, Error> {
+        let len = decoder.read_u64()?;
+        let mut branches = vec![];
+        for _ in 0..len {
+            branches.push(Branch {
+                a: decoder.read_integer()?,
+                e: decoder.read_integer()?,
+                z: decoder.read_integer()?,
+            });
+        }
+        Ok(MembershipProof { branches: branches })
+    }
+}
+
+bigint!(
+    I,
+    #[cfg(test)]
+    mod tests {
+
+        use super::I;
+        use crate::core::*;
+        use crate::proof::membership::*;
+        use crate::proof::{Proof, ProofKey};
+        use crate::*;
+
+        fn test_keypair() -> Keypair<I> {
+            let p = str::parse("148677972634832330983979593310074301486537017973460461278300587514468301043894574906886127642530475786889672304776052879927627556769456140664043088700743909632312483413393134504352834240399191134336344285483935856491230340093391784574980688823380828143810804684752914935441384845195613674104960646037368551517").unwrap();
+            let q = str::parse("158741574437007245654463598139927898730476924736461654463975966787719309357536545869203069369466212089132653564188443272208127277664424448947476335413293018778018615899291704693105620242763173357203898195318179150836424196645745308205164116144020613415407736216097185962171301808761138424668335445923774195463").unwrap();
+            Keypair { p: p, q: q }
+        }
+
+        #[test]
+        fn test_vote() {
+            let ek = standard::EncryptionKey::from(&test_keypair());
+            let values = [core::Plaintext::from(0), core::Plaintext::from(1)];
+            for m in &values {
+                let (c, r) = AbstractPaillier::encrypt_returning_randomness(&ek, m);
+                let proof = MembershipProof::prove(&ek, &c, &values, m, &r).unwrap();
+                assert_eq!(proof.verify(&ek, &c, &values), Ok(()));
+
+                let d = AbstractPaillier::add(&ek, &c, &c);
+                assert_eq!(proof.verify(&ek, &d, &values), Err(Error::InvalidProof));
+            }
+
+            let m = core::Plaintext::from(2);
+            let (c, r) = AbstractPaillier::encrypt_returning_randomness(&ek, &m);
+            assert_eq!(
+                MembershipProof::prove(&ek, &c, &values, &m, &r),
+                Err(Error::PlaintextOutOfRange)
+            );
+        }
+
+        #[test]
+        fn test_set() {
+            let ek = standard::EncryptionKey::from(&test_keypair());
+            let values: Vec<core::Plaintext<I>> = [3, 14, 15, 92, 65]
+                .iter()
+                .map(|&x| core::Plaintext::from(x))
+                .collect();
+            let m = core::Plaintext::from(92);
+            let (c, r) = AbstractPaillier::encrypt_returning_randomness(&ek, &m);
+            let proof = MembershipProof::prove(&ek, &c, &values, &m, &r).unwrap();
+            assert_eq!(proof.verify(&ek, &c, &values), Ok(()));
+
+            let decoded = MembershipProof::<I>::from_bytes(&Proof::to_bytes(&proof)).unwrap();
+            let statement = (&ek as &dyn ProofKey<I>, &c, &values[..]);
+            assert_eq!(Proof::verify(&decoded, &statement), Ok(()));
+
+            // the proof is bound to the set
+            let other = &values[..4];
+            assert_eq!(proof.verify(&ek, &c, other), Err(Error::InvalidProof));
+            let mut other = values.clone();
+            other[0] = core::Plaintext::from(4);
+            assert_eq!(proof.verify(&ek, &c, &other), Err(Error::InvalidProof));
+        }
+    }
+);
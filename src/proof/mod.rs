@@ -0,0 +1,23 @@
+//! Zero-knowledge proofs made non-interactive using the Fiat-Shamir transform.
+
+pub mod affine;
+pub mod decryption;
+pub mod key;
+pub mod membership;
+pub mod plaintext;
+pub mod range;
+pub mod transcript;
+
+pub use self::transcript::{Proof, Transcript};
+
+/// Encryption keys against which proofs about ciphertexts can be made and verified.
+pub trait ProofKey<I> {
+    /// The modulus `n`.
+    fn n(&self) -> &I;
+
+    /// The modulus squared.
+    fn nn(&self) -> &I;
+
+    /// Compute `g^m mod n^2` for the generator `g` of the key.
+    fn gm(&self, m: &I) -> I;
+}
@@ -0,0 +1,371 @@
+//! Proof of knowledge of the plaintext and randomness underlying a ciphertext.
+//!
+//! Shows that the prover knows `(m, r)` such that `c = g^m * r^n mod n^2`, which prevents a
+//! party from submitting a transformed copy of a ciphertext created by someone else.
+//! The protocol may be run interactively through `commit`, `respond`, and `check`, or
+//! non-interactively through `prove` and `verify`.
+
+use super::transcript::{Decoder, Encoder, Proof, Transcript};
+use super::ProofKey;
+use crate::arithimpl::traits::*;
+use crate::core::{check_ciphertext, Ciphertext, Plaintext, Randomness};
+use crate::Error;
+use num_traits::One;
+use std::ops::{Add, Div, Mul, Neg, Rem};
+
+#[cfg(feature = "serde")]
+use crate::serialize::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "serde")]
+use std::fmt;
+#[cfg(feature = "serde")]
+use std::str::FromStr;
+
+const DOMAIN: &str = "paillier.proof.plaintext-knowledge";
+
+/// Bit length of challenges, matching those derived by the Fiat-Shamir transform.
+pub const CHALLENGE_BITS: usize = 256;
+
+/// First message of the prover, committing to fresh randomness.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaintextKnowledgeCommitment<I>(I);
+
+/// Randomness kept secret by the prover between the commitment and the response.
+#[derive(Debug, Clone)]
+pub struct PlaintextKnowledgeNonce<I> {
+    x: I, // masks the plaintext
+    s: I, // masks the randomness
+}
+
+/// Answer of the prover to a challenge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaintextKnowledgeResponse<I> {
+    z1: I, // x + e*m mod n
+    z2: I, // s * r^e * g^((x + e*m) div n) mod n
+}
+
+/// Non-interactive proof of knowledge of the plaintext and randomness of a ciphertext.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaintextKnowledgeProof<I> {
+    commitment: PlaintextKnowledgeCommitment<I>,
+    response: PlaintextKnowledgeResponse<I>,
+}
+
+impl<I> PlaintextKnowledgeProof<I>
+where
+    I: Clone,
+    I: One,
+    I: PartialOrd,
+    I: ToBytes + FromBytes,
+    I: From<u64>,
+    I: NumberTests,
+    I: Samplable,
+    I: EGCD,
+    I: ModPow,
+    I: ModPowSec,
+    I: Add<I, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Div<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    /// Commit to fresh randomness, to be sent to the verifier before receiving a challenge.
+    pub fn commit<K>(ek: &K) -> (PlaintextKnowledgeCommitment<I>, PlaintextKnowledgeNonce<I>)
+    where
+        K: ProofKey<I> + ?Sized,
+    {
+        let x = I::sample_below(ek.n());
+        let s = Randomness::sample(ek.n()).as_ref().clone();
+        let a = (ek.gm(&x) * &modpow_secret(&s, ek.n(), ek.nn())) % ek.nn();
+        (
+            PlaintextKnowledgeCommitment(a),
+            PlaintextKnowledgeNonce { x: x, s: s },
+        )
+    }
+
+    /// Sample a challenge on behalf of the verifier.
+    pub fn sample_challenge() -> I {
+        I::sample(CHALLENGE_BITS)
+    }
+
+    /// Answer challenge `e` for a ciphertext encrypting `m` with randomness `r`.
+    pub fn respond<K>(
+        ek: &K,
+        m: &Plaintext<I>,
+        r: &Randomness<I>,
+        nonce: PlaintextKnowledgeNonce<I>,
+        e: &I,
+    ) -> PlaintextKnowledgeResponse<I>
+    where
+        K: ProofKey<I> + ?Sized,
+    {
+        let n = ek.n();
+        let ref t = &nonce.x + &(e * &(&m.0 % n));
+        // the multiple of n dropped from the exponent is moved into the randomness
+        let ref gk = ek.gm(&(t / n)) % n;
+        let ref re = modpow_secret(r.as_ref(), e, n);
+        PlaintextKnowledgeResponse {
+            z1: t % n,
+            z2: (&(&nonce.s * re) % n * gk) % n,
+        }
+    }
+
+    /// Check the response to challenge `e` for ciphertext `c`.
+    pub fn check<K>(
+        ek: &K,
+        c: &Ciphertext<I>,
+        commitment: &PlaintextKnowledgeCommitment<I>,
+        e: &I,
+        response: &PlaintextKnowledgeResponse<I>,
+    ) -> Result<(), Error>
+    where
+        K: ProofKey<I> + ?Sized,
+    {
+        let (n, nn) = (ek.n(), ek.nn());
+        check_ciphertext(&c.0, n, nn).map_err(|_| Error::InvalidProof)?;
+        check_ciphertext(&commitment.0, n, nn).map_err(|_| Error::InvalidProof)?;
+        if NumberTests::is_negative(&response.z1) || &response.z1 >= n {
+            return Err(Error::InvalidProof);
+        }
+        Randomness::new(response.z2.clone(), n).map_err(|_| Error::InvalidProof)?;
+
+        let lhs = (ek.gm(&response.z1) * &I::modpow(&response.z2, n, nn)) % nn;
+        let rhs = (&commitment.0 * &I::modpow(&c.0, e, nn)) % nn;
+        if lhs != rhs {
+            return Err(Error::InvalidProof);
+        }
+        Ok(())
+    }
+
+    /// Prove knowledge of plaintext `m` and randomness `r` such that `c` encrypts `m` using `r`.
+    pub fn prove<K>(
+        ek: &K,
+        c: &Ciphertext<I>,
+        m: &Plaintext<I>,
+        r: &Randomness<I>,
+    ) -> PlaintextKnowledgeProof<I>
+    where
+        K: ProofKey<I> + ?Sized,
+    {
+        let (commitment, nonce) = Self::commit(ek);
+        let ref e = fiat_shamir(ek, c, &commitment);
+        let response = Self::respond(ek, m, r, nonce, e);
+        PlaintextKnowledgeProof {
+            commitment: commitment,
+            response: response,
+        }
+    }
+
+    /// Verify that the prover knows the plaintext and randomness of `c`.
+    pub fn verify<K>(&self, ek: &K, c: &Ciphertext<I>) -> Result<(), Error>
+    where
+        K: ProofKey<I> + ?Sized,
+    {
+        let ref e = fiat_shamir(ek, c, &self.commitment);
+        Self::check(ek, c, &self.commitment, e, &self.response)
+    }
+}
+
+fn fiat_shamir<I, K>(ek: &K, c: &Ciphertext<I>, commitment: &PlaintextKnowledgeCommitment<I>) -> I
+where
+    K: ProofKey<I> + ?Sized,
+    I: One,
+    I: ToBytes + FromBytes + NumberTests,
+{
+    let mut transcript = Transcript::new(DOMAIN);
+    transcript.append_key("ek", ek);
+    transcript.append_ciphertext("c", c);
+    transcript.append_integer("commitment", &commitment.0);
+    transcript.challenge("e")
+}
+
+impl<I> Proof for PlaintextKnowledgeProof<I>
+where
+    I: Clone,
+    I: One,
+    I: PartialOrd,
+    I: ToBytes + FromBytes,
+    I: From<u64>,
+    I: NumberTests,
+    I: Samplable,
+    I: EGCD,
+    I: ModPow,
+    I: ModPowSec,
+    I: Neg<Output = I>,
+    I: Add<I, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Div<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    type Statement<'s>
+        = (&'s dyn ProofKey<I>, &'s Ciphertext<I>)
+    where
+        Self: 's;
+
+    fn verify(&self, statement: &Self::Statement<'_>) -> Result<(), Error> {
+        let (ek, c) = *statement;
+        PlaintextKnowledgeProof::verify(self, ek, c)
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_integer(&self.commitment.0);
+        encoder.write_integer(&self.response.z1);
+        encoder.write_integer(&self.response.z2);
+    }
+
+    fn decode(decoder: &mut Decoder) -> Result<Self, Error> {
+        Ok(PlaintextKnowledgeProof {
+            commitment: PlaintextKnowledgeCommitment(decoder.read_integer()?),
+            response: PlaintextKnowledgeResponse {
+                z1: decoder.read_integer()?,
+                z2: decoder.read_integer()?,
+            },
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<I> Serialize for PlaintextKnowledgeProof<I>
+where
+    I: fmt::Display,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PlaintextKnowledgeProofRepr {
+            commitment: to_repr(&self.commitment.0),
+            z1: to_repr(&self.response.z1),
+            z2: to_repr(&self.response.z2),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, I> Deserialize<'de> for PlaintextKnowledgeProof<I>
+where
+    I: FromStr,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = PlaintextKnowledgeProofRepr::deserialize(deserializer)?;
+        Ok(PlaintextKnowledgeProof {
+            commitment: PlaintextKnowledgeCommitment(from_repr(&repr.commitment)?),
+            response: PlaintextKnowledgeResponse {
+                z1: from_repr(&repr.z1)?,
+                z2: from_repr(&repr.z2)?,
+            },
+        })
+    }
+}
+
+bigint!(
+    I,
+    #[cfg(test)]
+    mod tests {
+
+        use super::I;
+        use crate::core::*;
+        use crate::proof::plaintext::*;
+        use crate::proof::{Proof, ProofKey};
+        use crate::*;
+
+        fn test_keypair() -> Keypair<I> {
+            let p = str::parse("148677972634832330983979593310074301486537017973460461278300587514468301043894574906886127642530475786889672304776052879927627556769456140664043088700743909632312483413393134504352834240399191134336344285483935856491230340093391784574980688823380828143810804684752914935441384845195613674104960646037368551517").unwrap();
+            let q = str::parse("158741574437007245654463598139927898730476924736461654463975966787719309357536545869203069369466212089132653564188443272208127277664424448947476335413293018778018615899291704693105620242763173357203898195318179150836424196645745308205164116144020613415407736216097185962171301808761138424668335445923774195463").unwrap();
+            Keypair { p: p, q: q }
+        }
+
+        #[test]
+        fn test_proof_standard() {
+            let ek = standard::EncryptionKey::from(&test_keypair());
+            let m = core::Plaintext::from(10);
+            let (c, r) = AbstractPaillier::encrypt_returning_randomness(&ek, &m);
+            let proof = PlaintextKnowledgeProof::prove(&ek, &c, &m, &r);
+            assert_eq!(proof.verify(&ek, &c), Ok(()));
+
+            // a rerandomised copy cannot reuse the proof
+            let d: core::Ciphertext<I> = AbstractPaillier::rerandomise(&ek, &c);
+            assert_eq!(proof.verify(&ek, &d), Err(Error::InvalidProof));
+
+            // nor can a proof be made without knowing the randomness
+            let s = Randomness::sample(&ek.n);
+            let proof = PlaintextKnowledgeProof::prove(&ek, &c, &m, &s);
+            assert_eq!(proof.verify(&ek, &c), Err(Error::InvalidProof));
+        }
+
+        #[test]
+        fn test_proof_generic() {
+            let ref keypair = test_keypair();
+            let ref n = &keypair.p * &keypair.q;
+            let ek = generic::EncryptionKey::from((keypair, &(n * &I::from(3) + I::from(1))));
+            let m = core::Plaintext::from(10);
+            let (c, r) = AbstractPaillier::encrypt_returning_randomness(&ek, &m);
+            let proof = PlaintextKnowledgeProof::prove(&ek, &c, &m, &r);
+            assert_eq!(proof.verify(&ek, &c), Ok(()));
+
+            let other = standard::EncryptionKey::from(keypair);
+            assert_eq!(proof.verify(&other, &c), Err(Error::InvalidProof));
+        }
+
+        #[test]
+        fn test_interactive() {
+            let ek = standard::EncryptionKey::from(&test_keypair());
+            let m = core::Plaintext::from(10);
+            let (c, r) = AbstractPaillier::encrypt_returning_randomness(&ek, &m);
+
+            let (commitment, nonce) = PlaintextKnowledgeProof::commit(&ek);
+            let ref e = PlaintextKnowledgeProof::sample_challenge();
+            let response = PlaintextKnowledgeProof::respond(&ek, &m, &r, nonce, e);
+            assert_eq!(
+                PlaintextKnowledgeProof::check(&ek, &c, &commitment, e, &response),
+                Ok(())
+            );
+
+            let ref f = e + &I::from(1);
+            assert_eq!(
+                PlaintextKnowledgeProof::check(&ek, &c, &commitment, f, &response),
+                Err(Error::InvalidProof)
+            );
+        }
+
+        #[test]
+        fn test_bytes() {
+            let ek = standard::EncryptionKey::from(&test_keypair());
+            let m = core::Plaintext::from(10);
+            let (c, r) = AbstractPaillier::encrypt_returning_randomness(&ek, &m);
+            let proof = PlaintextKnowledgeProof::prove(&ek, &c, &m, &r);
+
+            let bytes = Proof::to_bytes(&proof);
+            let decoded = PlaintextKnowledgeProof::<I>::from_bytes(&bytes).unwrap();
+            assert_eq!(decoded, proof);
+            assert_eq!(
+                Proof::verify(&decoded, &(&ek as &dyn ProofKey<I>, &c)),
+                Ok(())
+            );
+
+            let mut bytes = bytes;
+            bytes.push(0);
+            assert_eq!(
+                PlaintextKnowledgeProof::<I>::from_bytes(&bytes),
+                Err(Error::InvalidProof)
+            );
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn test_serialize() {
+            let ek = standard::EncryptionKey::from(&test_keypair());
+            let m = core::Plaintext::from(10);
+            let (c, r) = AbstractPaillier::encrypt_returning_randomness(&ek, &m);
+            let proof = PlaintextKnowledgeProof::prove(&ek, &c, &m, &r);
+
+            let json = serde_json::to_string(&proof).unwrap();
+            let proof: PlaintextKnowledgeProof<I> = serde_json::from_str(&json).unwrap();
+            assert_eq!(proof.verify(&ek, &c), Ok(()));
+        }
+    }
+);
@@ -0,0 +1,793 @@
+//! Range proofs showing that a ciphertext encrypts a value in `[0, B)`.
+//!
+//! Two proof systems are provided behind the `RangeProofSystem` trait: `BitDecomposition`,
+//! which needs no setup but produces proofs linear in the bit length of `B`, and
+//! `RingPedersen`, which produces constant size proofs using an auxiliary RSA setup of the
+//! verifier at the cost of some slack in the proven range.
+
+use super::membership::MembershipProof;
+use super::transcript::{Decoder, Encoder, Proof, Transcript};
+use super::ProofKey;
+use crate::arithimpl::traits::*;
+use crate::core::{check_ciphertext, Ciphertext, Keypair, Plaintext, Randomness};
+use crate::Error;
+use num_traits::{One, Zero};
+use std::ops::{Add, Mul, Neg, Rem, Shl, Shr, Sub};
+
+const BIT_DOMAIN: &str = "paillier.proof.range.bit-decomposition";
+const PEDERSEN_DOMAIN: &str = "paillier.proof.range.ring-pedersen";
+
+/// Bit length of challenges in ring-Pedersen range proofs.
+const CHALLENGE_BITS: usize = 128;
+
+/// Bits of statistical security with which ring-Pedersen range proofs hide the value.
+const STATISTICAL_BITS: usize = 80;
+
+/// Proof system for showing that a ciphertext encrypts a value in `[0, B)`.
+pub trait RangeProofSystem<I> {
+    type Proof;
+
+    /// Prove that `c`, encrypting `m` under `ek` using randomness `r`, contains a value in
+    /// `[0, bound)`, failing if `m` is not in that range.
+    fn prove<K, C, P>(
+        &self,
+        ek: &K,
+        c: &C,
+        m: &P,
+        r: &Randomness<I>,
+        bound: &I,
+    ) -> Result<Self::Proof, Error>
+    where
+        K: ProofKey<I> + ?Sized,
+        C: AsRef<Ciphertext<I>>,
+        P: AsRef<Plaintext<I>>;
+
+    /// Verify that `c` contains a value in `[0, bound)`.
+    fn verify<K, C>(&self, ek: &K, c: &C, bound: &I, proof: &Self::Proof) -> Result<(), Error>
+    where
+        K: ProofKey<I> + ?Sized,
+        C: AsRef<Ciphertext<I>>;
+}
+
+/// Range proofs encrypting each bit of the value separately, together with proofs that each
+/// such ciphertext encrypts either zero or one.
+///
+/// The range proven is exact, and `B` need not be a power of two.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BitDecomposition;
+
+/// Proof produced by `BitDecomposition`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitDecompositionProof<I> {
+    bits: Vec<BitProof<I>>,
+    rho: I, // randomness of the ciphertext divided by the weighted randomness of the bits
+}
+
+// Encryption of a bit, with a proof that it encrypts either zero or one.
+#[derive(Debug, Clone, PartialEq)]
+struct BitProof<I> {
+    c: I,
+    membership: MembershipProof<I>,
+}
+
+impl<I> RangeProofSystem<I> for BitDecomposition
+where
+    I: Clone,
+    I: PartialOrd,
+    I: ToBytes + FromBytes,
+    I: From<u64>,
+    I: Zero + One + NumberTests,
+    I: Samplable,
+    I: EGCD,
+    I: ModInv,
+    I: ModPow,
+    I: ModPowSec,
+    I: Add<I, Output = I>,
+    I: Shl<usize, Output = I>,
+    I: Shr<usize, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Sub<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    type Proof = BitDecompositionProof<I>;
+
+    fn prove<K, C, P>(
+        &self,
+        ek: &K,
+        c: &C,
+        m: &P,
+        r: &Randomness<I>,
+        bound: &I,
+    ) -> Result<BitDecompositionProof<I>, Error>
+    where
+        K: ProofKey<I> + ?Sized,
+        C: AsRef<Ciphertext<I>>,
+        P: AsRef<Plaintext<I>>,
+    {
+        let (n, nn) = (ek.n(), ek.nn());
+        let m = &m.as_ref().0;
+        check_bound(bound, n).map_err(|_| Error::PlaintextOutOfRange)?;
+        if NumberTests::is_negative(m) || m >= bound {
+            return Err(Error::PlaintextOutOfRange);
+        }
+        let weights = weights(bound);
+        let ref values = bit_values();
+
+        // encrypt the bits and commit to their proofs, all sharing a single challenge
+        let mut rho = r.as_ref().clone();
+        let mut nonces = vec![];
+        let mut bits = vec![];
+        for (bit, w) in decompose(m, &weights).into_iter().zip(weights.iter()) {
+            let ri = Randomness::sample(n).as_ref().clone();
+            let c = (ek.gm(&I::from(bit as u64)) * &modpow_secret(&ri, n, nn)) % nn;
+            rho = (rho * &I::modinv(&modpow_secret(&ri, w, n), n)) % n;
+            let (membership, nonce) = MembershipProof::commit(ek, &c, values, bit as usize);
+            nonces.push((ri, nonce));
+            bits.push(BitProof {
+                c: c,
+                membership: membership,
+            });
+        }
+
+        let ref e = bit_challenge(ek, &c.as_ref().0, &bits);
+        for (bit, (ri, nonce)) in bits.iter_mut().zip(nonces) {
+            bit.membership.respond(n, nonce, &ri, e);
+        }
+
+        Ok(BitDecompositionProof {
+            bits: bits,
+            rho: rho,
+        })
+    }
+
+    fn verify<K, C>(
+        &self,
+        ek: &K,
+        c: &C,
+        bound: &I,
+        proof: &BitDecompositionProof<I>,
+    ) -> Result<(), Error>
+    where
+        K: ProofKey<I> + ?Sized,
+        C: AsRef<Ciphertext<I>>,
+    {
+        let (n, nn) = (ek.n(), ek.nn());
+        let c = &c.as_ref().0;
+        check_bound(bound, n)?;
+        check_ciphertext(c, n, nn).map_err(|_| Error::InvalidProof)?;
+        Randomness::new(proof.rho.clone(), n).map_err(|_| Error::InvalidProof)?;
+        let weights = weights(bound);
+        if proof.bits.len() != weights.len() {
+            return Err(Error::InvalidProof);
+        }
+
+        let ref values = bit_values();
+        let ref e = bit_challenge(ek, c, &proof.bits);
+        let mut acc = I::modpow(&proof.rho, n, nn);
+        for (bit, w) in proof.bits.iter().zip(weights.iter()) {
+            check_ciphertext(&bit.c, n, nn).map_err(|_| Error::InvalidProof)?;
+            bit.membership.check(ek, &bit.c, values, e)?;
+            acc = (acc * &I::modpow(&bit.c, w, nn)) % nn;
+        }
+        if &acc != c {
+            return Err(Error::InvalidProof);
+        }
+        Ok(())
+    }
+}
+
+impl<I> Proof for BitDecompositionProof<I>
+where
+    I: Clone,
+    I: PartialOrd,
+    I: ToBytes + FromBytes,
+    I: From<u64>,
+    I: Zero + One + NumberTests,
+    I: Samplable,
+    I: EGCD,
+    I: ModInv,
+    I: ModPow,
+    I: ModPowSec,
+    I: Neg<Output = I>,
+    I: Add<I, Output = I>,
+    I: Shl<usize, Output = I>,
+    I: Shr<usize, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Sub<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    type Statement<'s>
+        = (&'s dyn ProofKey<I>, &'s Ciphertext<I>, &'s I)
+    where
+        Self: 's;
+
+    fn verify(&self, statement: &Self::Statement<'_>) -> Result<(), Error> {
+        let (ek, c, bound) = *statement;
+        BitDecomposition.verify(ek, c, bound, self)
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_u64(self.bits.len() as u64);
+        for bit in &self.bits {
+            encoder.write_integer(&bit.c);
+            bit.membership.encode(encoder);
+        }
+        encoder.write_integer(&self.rho);
+    }
+
+    fn decode(decoder: &mut Decoder) -> Result<Self, Error> {
+        let len = decoder.read_u64()?;
+        let mut bits = vec![];
+        for _ in 0..len {
+            bits.push(BitProof {
+                c: decoder.read_integer()?,
+                membership: MembershipProof::decode(decoder)?,
+            });
+        }
+        Ok(BitDecompositionProof {
+            bits: bits,
+            rho: decoder.read_integer()?,
+        })
+    }
+}
+
+fn bit_challenge<I, K>(ek: &K, c: &I, bits: &[BitProof<I>]) -> I
+where
+    K: ProofKey<I> + ?Sized,
+    I: One,
+    I: ToBytes + FromBytes + NumberTests,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+{
+    let mut transcript = Transcript::new(BIT_DOMAIN);
+    transcript.append_key("ek", ek);
+    transcript.append_integer("c", c);
+    for bit in bits {
+        transcript.append_integer("bit", &bit.c);
+        for a in bit.membership.commitments() {
+            transcript.append_integer("commitment", a);
+        }
+    }
+    transcript.challenge_below("e", ek.n())
+}
+
+/// Range proofs using a ring-Pedersen commitment to the value under an auxiliary RSA modulus.
+///
+/// The setup must be generated by the verifier, or by someone the verifier trusts, since its
+/// factorisation allows forging proofs. Unlike `BitDecomposition` the proven range has slack:
+/// an honest prover shows that the value lies in `[0, B)`, but a proof only guarantees that it
+/// lies strictly within `2^(209+k)` of zero for `k` the bit length of `B`. This is enough to
+/// rule out wrap-around when summing values as long as `n` is sufficiently large.
+///
+/// Bounds for which this slack reaches the encryption modulus, that is with `k + 209` at least
+/// the bit length of `n`, are rejected by both proving and verification.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RingPedersen<I> {
+    pub n: I, // the auxiliary modulus
+    pub s: I, // power of t
+    pub t: I, // quadratic residue
+}
+
+/// Proof produced by `RingPedersen`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RingPedersenProof<I> {
+    commitment: I,        // s^m t^mu mod N
+    masked_ciphertext: I, // encryption of alpha
+    masked_commitment: I, // s^alpha t^gamma mod N
+    z1: I,                // alpha + e*m
+    z2: I,                // randomness of the masked ciphertext times r^e
+    z3: I,                // gamma + e*mu
+}
+
+impl<'kp, I> From<&'kp Keypair<I>> for RingPedersen<I>
+where
+    I: Clone,
+    I: One,
+    I: PartialOrd,
+    I: NumberTests,
+    I: Samplable,
+    I: EGCD,
+    I: ModPow,
+    I: ModPowSec,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+{
+    /// Generate a setup from a keypair whose primes are kept secret by the verifier.
+    fn from(keypair: &'kp Keypair<I>) -> RingPedersen<I> {
+        let ref one = I::one();
+        let n = &keypair.p * &keypair.q;
+        let ref phi = &(&keypair.p - one) * &(&keypair.q - one);
+        let ref r = Randomness::sample(&n).as_ref().clone();
+        let t = &(r * r) % &n;
+        let ref lambda = I::sample_below(phi);
+        let s = modpow_secret(&t, lambda, &n);
+        RingPedersen { n: n, s: s, t: t }
+    }
+}
+
+impl<I> RangeProofSystem<I> for RingPedersen<I>
+where
+    I: Clone,
+    I: PartialOrd,
+    I: ToBytes + FromBytes,
+    I: From<u64>,
+    I: Zero + One + NumberTests,
+    I: Samplable,
+    I: EGCD,
+    I: ModPow,
+    I: ModPowSec,
+    I: Add<I, Output = I>,
+    I: Shl<usize, Output = I>,
+    I: Shr<usize, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    type Proof = RingPedersenProof<I>;
+
+    fn prove<K, C, P>(
+        &self,
+        ek: &K,
+        c: &C,
+        m: &P,
+        r: &Randomness<I>,
+        bound: &I,
+    ) -> Result<RingPedersenProof<I>, Error>
+    where
+        K: ProofKey<I> + ?Sized,
+        C: AsRef<Ciphertext<I>>,
+        P: AsRef<Plaintext<I>>,
+    {
+        let (n, nn) = (ek.n(), ek.nn());
+        let ref nhat = self.n;
+        let m = &m.as_ref().0;
+        check_bound(bound, n).map_err(|_| Error::PlaintextOutOfRange)?;
+        check_slack(bound, n).map_err(|_| Error::PlaintextOutOfRange)?;
+        if NumberTests::is_negative(m) || m >= bound {
+            return Err(Error::PlaintextOutOfRange);
+        }
+        let l = bit_length(bound);
+        let eps = CHALLENGE_BITS + STATISTICAL_BITS;
+
+        let ref alpha = I::sample(l + eps);
+        let ref mu = I::sample_below(&(pow2::<I>(l) * nhat));
+        let ref gamma = I::sample_below(&(pow2::<I>(l + eps) * nhat));
+        let ref rr = Randomness::sample(n).as_ref().clone();
+
+        let commitment = pedersen(&self.s, m, &self.t, mu, nhat);
+        let masked_ciphertext = (ek.gm(alpha) * &modpow_secret(rr, n, nn)) % nn;
+        let masked_commitment = pedersen(&self.s, alpha, &self.t, gamma, nhat);
+
+        let ref e = self.challenge(
+            ek,
+            &c.as_ref().0,
+            bound,
+            &[&commitment, &masked_ciphertext, &masked_commitment],
+        );
+        Ok(RingPedersenProof {
+            commitment: commitment,
+            masked_ciphertext: masked_ciphertext,
+            masked_commitment: masked_commitment,
+            z1: alpha + &(e * m),
+            z2: &(rr * &modpow_secret(r.as_ref(), e, n)) % n,
+            z3: gamma + &(e * mu),
+        })
+    }
+
+    fn verify<K, C>(
+        &self,
+        ek: &K,
+        c: &C,
+        bound: &I,
+        proof: &RingPedersenProof<I>,
+    ) -> Result<(), Error>
+    where
+        K: ProofKey<I> + ?Sized,
+        C: AsRef<Ciphertext<I>>,
+    {
+        let (n, nn) = (ek.n(), ek.nn());
+        let ref nhat = self.n;
+        let c = &c.as_ref().0;
+        check_bound(bound, n)?;
+        check_slack(bound, n)?;
+        check_ciphertext(c, n, nn).map_err(|_| Error::InvalidProof)?;
+        check_ciphertext(&proof.masked_ciphertext, n, nn).map_err(|_| Error::InvalidProof)?;
+        for x in &[&proof.commitment, &proof.masked_commitment] {
+            if NumberTests::is_negative(*x) || NumberTests::is_zero(*x) || *x >= nhat {
+                return Err(Error::InvalidProof);
+            }
+        }
+        Randomness::new(proof.z2.clone(), n).map_err(|_| Error::InvalidProof)?;
+        let l = bit_length(bound);
+        let eps = CHALLENGE_BITS + STATISTICAL_BITS;
+        if NumberTests::is_negative(&proof.z1)
+            || NumberTests::is_negative(&proof.z3)
+            || proof.z1 >= pow2(l + eps + 1)
+        {
+            return Err(Error::InvalidProof);
+        }
+
+        let ref e = self.challenge(
+            ek,
+            c,
+            bound,
+            &[
+                &proof.commitment,
+                &proof.masked_ciphertext,
+                &proof.masked_commitment,
+            ],
+        );
+        let lhs = (ek.gm(&proof.z1) * &I::modpow(&proof.z2, n, nn)) % nn;
+        let rhs = (&proof.masked_ciphertext * &I::modpow(c, e, nn)) % nn;
+        if lhs != rhs {
+            return Err(Error::InvalidProof);
+        }
+        let lhs = pedersen_public(&self.s, &proof.z1, &self.t, &proof.z3, nhat);
+        let rhs = (&proof.masked_commitment * &I::modpow(&proof.commitment, e, nhat)) % nhat;
+        if lhs != rhs {
+            return Err(Error::InvalidProof);
+        }
+        Ok(())
+    }
+}
+
+impl<I> Proof for RingPedersenProof<I>
+where
+    I: Clone,
+    I: PartialOrd,
+    I: ToBytes + FromBytes,
+    I: From<u64>,
+    I: Zero + One + NumberTests,
+    I: Samplable,
+    I: EGCD,
+    I: ModPow,
+    I: ModPowSec,
+    I: Neg<Output = I>,
+    I: Add<I, Output = I>,
+    I: Shl<usize, Output = I>,
+    I: Shr<usize, Output = I>,
+    for<'a, 'b> &'a I: Add<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    for<'b> I: Mul<&'b I, Output = I>,
+    for<'b> I: Rem<&'b I, Output = I>,
+{
+    type Statement<'s>
+        = (
+        &'s RingPedersen<I>,
+        &'s dyn ProofKey<I>,
+        &'s Ciphertext<I>,
+        &'s I,
+    )
+    where
+        Self: 's;
+
+    fn verify(&self, statement: &Self::Statement<'_>) -> Result<(), Error> {
+        let (setup, ek, c, bound) = *statement;
+        setup.verify(ek, c, bound, self)
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_integer(&self.commitment);
+        encoder.write_integer(&self.masked_ciphertext);
+        encoder.write_integer(&self.masked_commitment);
+        encoder.write_integer(&self.z1);
+        encoder.write_integer(&self.z2);
+        encoder.write_integer(&self.z3);
+    }
+
+    fn decode(decoder: &mut Decoder) -> Result<Self, Error> {
+        Ok(RingPedersenProof {
+            commitment: decoder.read_integer()?,
+            masked_ciphertext: decoder.read_integer()?,
+            masked_commitment: decoder.read_integer()?,
+            z1: decoder.read_integer()?,
+            z2: decoder.read_integer()?,
+            z3: decoder.read_integer()?,
+        })
+    }
+}
+
+impl<I> RingPedersen<I> {
+    fn challenge<K>(&self, ek: &K, c: &I, bound: &I, commitments: &[&I]) -> I
+    where
+        K: ProofKey<I> + ?Sized,
+        I: One,
+        I: ToBytes + FromBytes + NumberTests,
+        I: Shl<usize, Output = I>,
+        for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    {
+        let mut transcript = Transcript::new(PEDERSEN_DOMAIN);
+        transcript.append_key("ek", ek);
+        transcript.append_integer("c", c);
+        transcript.append_integer("bound", bound);
+        transcript.append_integer("setup.n", &self.n);
+        transcript.append_integer("setup.s", &self.s);
+        transcript.append_integer("setup.t", &self.t);
+        for a in commitments {
+            transcript.append_integer("commitment", *a);
+        }
+        transcript.challenge_below("e", &pow2(CHALLENGE_BITS))
+    }
+}
+
+// Compute s^x t^y mod n for secret x and y, as done by provers.
+pub(super) fn pedersen<I>(s: &I, x: &I, t: &I, y: &I, n: &I) -> I
+where
+    I: ModPow,
+    I: ModPowSec,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+{
+    &(&modpow_secret(s, x, n) * &modpow_secret(t, y, n)) % n
+}
+
+// Compute s^x t^y mod n for public x and y, such as the responses checked by verifiers.
+pub(super) fn pedersen_public<I>(s: &I, x: &I, t: &I, y: &I, n: &I) -> I
+where
+    I: ModPow,
+    for<'a, 'b> &'a I: Mul<&'b I, Output = I>,
+    for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+{
+    &(&I::modpow(s, x, n) * &I::modpow(t, y, n)) % n
+}
+
+fn bit_values<I>() -> [Plaintext<I>; 2]
+where
+    I: Zero + One,
+{
+    [Plaintext(I::zero()), Plaintext(I::one())]
+}
+
+// Check that `0 < bound <= n`.
+fn check_bound<I>(bound: &I, n: &I) -> Result<(), Error>
+where
+    I: PartialOrd,
+    I: NumberTests,
+{
+    if NumberTests::is_negative(bound) || NumberTests::is_zero(bound) || bound > n {
+        return Err(Error::InvalidProof);
+    }
+    Ok(())
+}
+
+// Check that the slack of `RingPedersen` for `bound` stays below `n`.
+fn check_slack<I>(bound: &I, n: &I) -> Result<(), Error>
+where
+    I: Clone,
+    I: NumberTests,
+    I: Shr<usize, Output = I>,
+{
+    if bit_length(bound) + CHALLENGE_BITS + STATISTICAL_BITS + 1 >= bit_length(n) {
+        return Err(Error::InvalidProof);
+    }
+    Ok(())
+}
+
+// Weights such that the values `sum(b_i * w_i)` for bits `b_i` are exactly those in
+// `[0, bound)`: the powers of two below `2^(k-1)` followed by `bound - 2^(k-1)`, for `k` the
+// bit length of `bound - 1`.
+fn weights<I>(bound: &I) -> Vec<I>
+where
+    I: Clone,
+    I: One,
+    I: NumberTests,
+    I: Shl<usize, Output = I>,
+    I: Shr<usize, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+{
+    let k = bit_length(&(bound - &I::one()));
+    if k == 0 {
+        return vec![];
+    }
+    let mut weights: Vec<I> = (0..k - 1).map(pow2).collect();
+    weights.push(bound - &pow2(k - 1));
+    weights
+}
+
+// Write `m` in `[0, bound)` as a sum of `weights(bound)`, returning the bits.
+fn decompose<I>(m: &I, weights: &[I]) -> Vec<bool>
+where
+    I: Clone,
+    I: One,
+    I: PartialOrd,
+    I: NumberTests,
+    I: Shl<usize, Output = I>,
+    I: Shr<usize, Output = I>,
+    for<'a, 'b> &'a I: Sub<&'b I, Output = I>,
+{
+    let k = weights.len();
+    if k == 0 {
+        return vec![];
+    }
+    let top = m >= &pow2(k - 1);
+    let rest = if top { m - &weights[k - 1] } else { m.clone() };
+    let mut bits: Vec<bool> = (0..k - 1)
+        .map(|i| !NumberTests::is_even(&(rest.clone() >> i)))
+        .collect();
+    bits.push(top);
+    bits
+}
+
+pub(super) fn bit_length<I>(x: &I) -> usize
+where
+    I: Clone,
+    I: NumberTests,
+    I: Shr<usize, Output = I>,
+{
+    let mut x = x.clone();
+    let mut bits = 0;
+    while !NumberTests::is_zero(&x) {
+        x = x >> 1;
+        bits += 1;
+    }
+    bits
+}
+
+pub(super) fn pow2<I>(bits: usize) -> I
+where
+    I: One,
+    I: Shl<usize, Output = I>,
+{
+    I::one() << bits
+}
+
+bigint!(
+    I,
+    #[cfg(test)]
+    mod tests {
+
+        use super::I;
+        use crate::core::*;
+        use crate::integral::scalar;
+        use crate::proof::range::*;
+        use crate::proof::{Proof, ProofKey};
+        use crate::*;
+        use std::marker::PhantomData;
+
+        fn test_keypair() -> Keypair<I> {
+            let p = str::parse("148677972634832330983979593310074301486537017973460461278300587514468301043894574906886127642530475786889672304776052879927627556769456140664043088700743909632312483413393134504352834240399191134336344285483935856491230340093391784574980688823380828143810804684752914935441384845195613674104960646037368551517").unwrap();
+            let q = str::parse("158741574437007245654463598139927898730476924736461654463975966787719309357536545869203069369466212089132653564188443272208127277664424448947476335413293018778018615899291704693105620242763173357203898195318179150836424196645745308205164116144020613415407736216097185962171301808761138424668335445923774195463").unwrap();
+            Keypair { p: p, q: q }
+        }
+
+        fn test_setup() -> RingPedersen<I> {
+            // 512 bit primes known only to the verifier
+            let p = str::parse("10770908117087230285325789289897283940317801756288494110056130677249695557468535949664735832776330761204390292104453881822657254099741970459746021226115819").unwrap();
+            let q = str::parse("10142634210367665828052173247239184241335177139128114095655063569598821985124380111473658401798150425781798354151698455060921515828077107368408566127327187").unwrap();
+            RingPedersen::from(&Keypair { p: p, q: q })
+        }
+
+        fn check_system<R: RangeProofSystem<I>>(system: &R) {
+            let ek = standard::EncryptionKey::from(&test_keypair());
+            let ref bound = I::from(100);
+
+            for &x in &[0, 1, 37, 63, 64, 99] {
+                let m = core::Plaintext::from(x);
+                let (c, r) = AbstractPaillier::encrypt_returning_randomness(&ek, &m);
+                let proof = system.prove(&ek, &c, &m, &r, bound).unwrap();
+                assert_eq!(system.verify(&ek, &c, bound, &proof), Ok(()));
+
+                // the proof is bound to the ciphertext
+                let d: core::Ciphertext<I> = AbstractPaillier::rerandomise(&ek, &c);
+                assert_eq!(
+                    system.verify(&ek, &d, bound, &proof),
+                    Err(Error::InvalidProof)
+                );
+            }
+
+            let m = core::Plaintext::from(100);
+            let (c, r) = AbstractPaillier::encrypt_returning_randomness(&ek, &m);
+            assert!(system.prove(&ek, &c, &m, &r, bound).is_err());
+        }
+
+        #[test]
+        fn test_bit_decomposition() {
+            check_system(&BitDecomposition);
+        }
+
+        #[test]
+        fn test_bit_decomposition_wrong_plaintext() {
+            // claiming a small value for a ciphertext of a large one does not help
+            let ek = standard::EncryptionKey::from(&test_keypair());
+            let ref bound = I::from(100);
+            let m = core::Plaintext(&ek.n - &I::from(1));
+            let (c, r) = AbstractPaillier::encrypt_returning_randomness(&ek, &m);
+            let proof = BitDecomposition
+                .prove(&ek, &c, &core::Plaintext::from(1), &r, bound)
+                .unwrap();
+            assert_eq!(
+                BitDecomposition.verify(&ek, &c, bound, &proof),
+                Err(Error::InvalidProof)
+            );
+        }
+
+        #[test]
+        fn test_ring_pedersen() {
+            check_system(&test_setup());
+        }
+
+        #[test]
+        fn test_ring_pedersen_wrong_plaintext() {
+            let ek = standard::EncryptionKey::from(&test_keypair());
+            let setup = test_setup();
+            let ref bound = I::from(100);
+            let m = core::Plaintext(&ek.n - &I::from(1));
+            let (c, r) = AbstractPaillier::encrypt_returning_randomness(&ek, &m);
+            let proof = setup
+                .prove(&ek, &c, &core::Plaintext::from(1), &r, bound)
+                .unwrap();
+            assert_eq!(
+                setup.verify(&ek, &c, bound, &proof),
+                Err(Error::InvalidProof)
+            );
+        }
+
+        #[test]
+        fn test_ring_pedersen_large_bound() {
+            // the slack of a bound just below n would allow values wrapping around n
+            let ek = standard::EncryptionKey::from(&test_keypair());
+            let setup = test_setup();
+            let ref bound = &ek.n >> 100;
+            let m = core::Plaintext::from(1);
+            let (c, r) = AbstractPaillier::encrypt_returning_randomness(&ek, &m);
+            assert_eq!(
+                setup.prove(&ek, &c, &m, &r, bound),
+                Err(Error::PlaintextOutOfRange)
+            );
+
+            let proof = setup.prove(&ek, &c, &m, &r, &I::from(100)).unwrap();
+            assert_eq!(
+                setup.verify(&ek, &c, bound, &proof),
+                Err(Error::InvalidProof)
+            );
+        }
+
+        #[test]
+        fn test_bytes() {
+            let ek = standard::EncryptionKey::from(&test_keypair());
+            let ref bound = I::from(100);
+            let m = core::Plaintext::from(37);
+            let (c, r) = AbstractPaillier::encrypt_returning_randomness(&ek, &m);
+
+            let proof = BitDecomposition.prove(&ek, &c, &m, &r, bound).unwrap();
+            let decoded = BitDecompositionProof::<I>::from_bytes(&proof.to_bytes()).unwrap();
+            assert_eq!(decoded, proof);
+            let statement = (&ek as &dyn ProofKey<I>, &c, bound);
+            assert_eq!(Proof::verify(&decoded, &statement), Ok(()));
+
+            let setup = test_setup();
+            let proof = setup.prove(&ek, &c, &m, &r, bound).unwrap();
+            let decoded = RingPedersenProof::<I>::from_bytes(&proof.to_bytes()).unwrap();
+            assert_eq!(decoded, proof);
+            let statement = (&setup, &ek as &dyn ProofKey<I>, &c, bound);
+            assert_eq!(Proof::verify(&decoded, &statement), Ok(()));
+        }
+
+        #[test]
+        fn test_scalar() {
+            let ek = standard::EncryptionKey::from(&test_keypair());
+            let ref bound = I::from(1000);
+            let m: scalar::Plaintext<I, u64> = scalar::Plaintext::from(123);
+            let (c, r) = AbstractPaillier::encrypt_returning_randomness(&ek, &m.data);
+            let c: scalar::Ciphertext<I, u64> = scalar::Ciphertext {
+                data: c,
+                _phantom: PhantomData,
+            };
+            let proof = BitDecomposition.prove(&ek, &c, &m, &r, bound).unwrap();
+            assert_eq!(BitDecomposition.verify(&ek, &c, bound, &proof), Ok(()));
+        }
+    }
+);
@@ -0,0 +1,379 @@
+//! Fiat-Shamir transcripts, and canonical encodings of proofs and the values they are made over.
+//!
+//! An integer is encoded as a sign byte followed by the length-prefixed big-endian bytes of its
+//! absolute value. Since this does not depend on the arithmetic library, challenges derived from
+//! the same values and proofs serialized from the same values agree across ramp, gmp, and num.
+
+use super::ProofKey;
+use crate::arithimpl::traits::*;
+use crate::core::{Ciphertext, Plaintext};
+use crate::Error;
+use num_traits::One;
+use sha2::{Digest, Sha256};
+use std::ops::{Neg, Rem};
+
+/// Length in bytes of challenges returned by `Transcript::challenge`.
+pub const CHALLENGE_BYTES: usize = 32;
+
+// Extra bytes sampled by `Transcript::challenge_below` to make the reduction statistically close
+// to uniform.
+const STATISTICAL_BYTES: usize = 16;
+
+/// Canonical byte encoding of values.
+#[derive(Debug, Clone, Default)]
+pub struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new() -> Encoder {
+        Encoder::default()
+    }
+
+    pub fn write_u64(&mut self, x: u64) {
+        self.bytes.extend_from_slice(&x.to_be_bytes());
+    }
+
+    pub fn write_bool(&mut self, x: bool) {
+        self.bytes.push(x as u8);
+    }
+
+    /// Write `bytes` prefixed by their length.
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write_u64(bytes.len() as u64);
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    pub fn write_integer<I>(&mut self, x: &I)
+    where
+        I: ToBytes + NumberTests,
+    {
+        self.write_bool(NumberTests::is_negative(x));
+        self.write_bytes(&x.to_bytes());
+    }
+
+    /// Write `xs` prefixed by their number.
+    pub fn write_integers<I>(&mut self, xs: &[I])
+    where
+        I: ToBytes + NumberTests,
+    {
+        self.write_u64(xs.len() as u64);
+        for x in xs {
+            self.write_integer(x);
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Reads values written by an `Encoder`, rejecting any encoding that is not canonical.
+///
+/// All failures are reported as `Error::InvalidProof`.
+#[derive(Debug, Clone)]
+pub struct Decoder<'b> {
+    bytes: &'b [u8],
+}
+
+impl<'b> Decoder<'b> {
+    pub fn new(bytes: &'b [u8]) -> Decoder<'b> {
+        Decoder { bytes: bytes }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'b [u8], Error> {
+        if self.bytes.len() < len {
+            return Err(Error::InvalidProof);
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, Error> {
+        let mut buf = [0; 8];
+        buf.copy_from_slice(self.take(8)?);
+        Ok(u64::from_be_bytes(buf))
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, Error> {
+        match self.take(1)?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Error::InvalidProof),
+        }
+    }
+
+    pub fn read_bytes(&mut self) -> Result<&'b [u8], Error> {
+        let len = self.read_u64()?;
+        if len > self.bytes.len() as u64 {
+            return Err(Error::InvalidProof);
+        }
+        self.take(len as usize)
+    }
+
+    pub fn read_integer<I>(&mut self) -> Result<I, Error>
+    where
+        I: FromBytes,
+        I: Neg<Output = I>,
+    {
+        let negative = self.read_bool()?;
+        let bytes = self.read_bytes()?;
+        // leading zeros and negative zero would give a second encoding of the same number
+        match bytes.first() {
+            None if negative => Err(Error::InvalidProof),
+            Some(0) => Err(Error::InvalidProof),
+            _ if negative => Ok(-I::from_bytes(bytes)),
+            _ => Ok(I::from_bytes(bytes)),
+        }
+    }
+
+    pub fn read_integers<I>(&mut self) -> Result<Vec<I>, Error>
+    where
+        I: FromBytes,
+        I: Neg<Output = I>,
+    {
+        let len = self.read_u64()?;
+        let mut xs = vec![];
+        for _ in 0..len {
+            xs.push(self.read_integer()?);
+        }
+        Ok(xs)
+    }
+
+    /// Check that all bytes have been read.
+    pub fn finish(self) -> Result<(), Error> {
+        if !self.bytes.is_empty() {
+            return Err(Error::InvalidProof);
+        }
+        Ok(())
+    }
+}
+
+/// Running hash of the messages of a proof, from which the challenges of the verifier are derived.
+///
+/// Every message is absorbed under a label, and the domain given on creation separates the
+/// challenges of different proofs even when made over the same values.
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    hasher: Sha256,
+}
+
+impl Transcript {
+    pub fn new(domain: &str) -> Transcript {
+        let mut transcript = Transcript {
+            hasher: Sha256::new(),
+        };
+        transcript.append_bytes("domain", domain.as_bytes());
+        transcript
+    }
+
+    pub fn append_bytes(&mut self, label: &str, bytes: &[u8]) {
+        absorb(&mut self.hasher, label.as_bytes());
+        absorb(&mut self.hasher, bytes);
+    }
+
+    pub fn append_integer<I>(&mut self, label: &str, x: &I)
+    where
+        I: ToBytes + NumberTests,
+    {
+        let mut encoder = Encoder::new();
+        encoder.write_integer(x);
+        self.append_bytes(label, &encoder.into_bytes());
+    }
+
+    /// Append the modulus and generator of an encryption key.
+    pub fn append_key<I, K>(&mut self, label: &str, ek: &K)
+    where
+        K: ProofKey<I> + ?Sized,
+        I: One,
+        I: ToBytes + NumberTests,
+    {
+        let mut encoder = Encoder::new();
+        encoder.write_integer(ek.n());
+        encoder.write_integer(&ek.gm(&I::one()));
+        self.append_bytes(label, &encoder.into_bytes());
+    }
+
+    pub fn append_ciphertext<I>(&mut self, label: &str, c: &Ciphertext<I>)
+    where
+        I: ToBytes + NumberTests,
+    {
+        self.append_integer(label, &c.0);
+    }
+
+    pub fn append_plaintext<I>(&mut self, label: &str, m: &Plaintext<I>)
+    where
+        I: ToBytes + NumberTests,
+    {
+        self.append_integer(label, &m.0);
+    }
+
+    /// Derive `len` bytes from everything appended so far.
+    ///
+    /// The output is appended to the transcript, so that later challenges depend on it.
+    pub fn challenge_bytes(&mut self, label: &str, len: usize) -> Vec<u8> {
+        let mut output = Vec::with_capacity(len);
+        let mut block: u64 = 0;
+        while output.len() < len {
+            let mut hasher = self.hasher.clone();
+            absorb(&mut hasher, label.as_bytes());
+            hasher.update(block.to_be_bytes());
+            output.extend_from_slice(&hasher.finalize());
+            block += 1;
+        }
+        output.truncate(len);
+        self.append_bytes(label, &output);
+        output
+    }
+
+    /// Derive a challenge of `8 * CHALLENGE_BYTES` bits.
+    pub fn challenge<I>(&mut self, label: &str) -> I
+    where
+        I: FromBytes,
+    {
+        I::from_bytes(&self.challenge_bytes(label, CHALLENGE_BYTES))
+    }
+
+    /// Derive a challenge statistically close to uniform in `[0, bound)`.
+    pub fn challenge_below<I>(&mut self, label: &str, bound: &I) -> I
+    where
+        I: ToBytes + FromBytes,
+        for<'a, 'b> &'a I: Rem<&'b I, Output = I>,
+    {
+        let len = bound.to_bytes().len() + STATISTICAL_BYTES;
+        let ref x = I::from_bytes(&self.challenge_bytes(label, len));
+        x % bound
+    }
+}
+
+fn absorb(hasher: &mut Sha256, bytes: &[u8]) {
+    // prefixing the length keeps the encoding of a sequence of messages unambiguous
+    hasher.update((bytes.len() as u64).to_be_bytes());
+    hasher.update(bytes);
+}
+
+/// Non-interactive proofs that can be serialized and verified against public values.
+pub trait Proof: Sized {
+    /// The public values the proof is about.
+    type Statement<'s>
+    where
+        Self: 's;
+
+    fn verify(&self, statement: &Self::Statement<'_>) -> Result<(), Error>;
+
+    fn encode(&self, encoder: &mut Encoder);
+
+    fn decode(decoder: &mut Decoder) -> Result<Self, Error>;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        self.encode(&mut encoder);
+        encoder.into_bytes()
+    }
+
+    /// Decode a proof, rejecting trailing bytes.
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let mut decoder = Decoder::new(bytes);
+        let proof = Self::decode(&mut decoder)?;
+        decoder.finish()?;
+        Ok(proof)
+    }
+}
+
+bigint!(
+    I,
+    #[cfg(test)]
+    mod tests {
+
+        use super::I;
+        use crate::arithimpl::traits::*;
+        use crate::proof::transcript::*;
+        use crate::Error;
+
+        #[test]
+        fn test_integer_encoding() {
+            let values: Vec<I> = vec![
+                I::from(0),
+                I::from(1),
+                I::from(255),
+                I::from(256),
+                -I::from(1),
+                -I::from(65536),
+                str::parse("123456789012345678901234567890123456789").unwrap(),
+            ];
+            assert_eq!(I::from(256).to_bytes(), vec![1, 0]);
+            assert_eq!(I::from(0).to_bytes(), Vec::<u8>::new());
+
+            let mut encoder = Encoder::new();
+            encoder.write_integers(&values);
+            let bytes = encoder.into_bytes();
+            let mut decoder = Decoder::new(&bytes);
+            assert_eq!(decoder.read_integers::<I>(), Ok(values));
+            assert_eq!(decoder.finish(), Ok(()));
+        }
+
+        #[test]
+        fn test_non_canonical() {
+            // leading zero
+            let bytes = [0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 1];
+            assert_eq!(
+                Decoder::new(&bytes).read_integer::<I>(),
+                Err(Error::InvalidProof)
+            );
+            // negative zero
+            let bytes = [1, 0, 0, 0, 0, 0, 0, 0, 0];
+            assert_eq!(
+                Decoder::new(&bytes).read_integer::<I>(),
+                Err(Error::InvalidProof)
+            );
+            // truncated
+            let bytes = [0, 0, 0, 0, 0, 0, 0, 0, 2, 1];
+            assert_eq!(
+                Decoder::new(&bytes).read_integer::<I>(),
+                Err(Error::InvalidProof)
+            );
+        }
+
+        #[test]
+        fn test_challenge() {
+            let ref x: I = str::parse("98765432109876543210").unwrap();
+            let mut transcript = Transcript::new("test");
+            transcript.append_integer("x", x);
+            let e: I = transcript.clone().challenge("e");
+
+            // fixed across arithmetic libraries
+            let expected: I = str::parse(
+                "15507059665106943398846999702716644735744578306871334971089386104270265325974",
+            )
+            .unwrap();
+            assert_eq!(e, expected);
+
+            // separated by domain, label, and value
+            let mut other = Transcript::new("other");
+            other.append_integer("x", x);
+            assert_ne!(other.challenge::<I>("e"), e);
+            let mut other = Transcript::new("test");
+            other.append_integer("y", x);
+            assert_ne!(other.challenge::<I>("e"), e);
+            let mut other = Transcript::new("test");
+            other.append_integer("x", &-x.clone());
+            assert_ne!(other.challenge::<I>("e"), e);
+
+            // later challenges depend on earlier ones
+            let f: I = transcript.challenge("e");
+            assert_eq!(f, e);
+            assert_ne!(transcript.challenge::<I>("e"), e);
+        }
+
+        #[test]
+        fn test_challenge_below() {
+            let ref bound = I::from(1000);
+            let mut transcript = Transcript::new("test");
+            for _ in 0..100 {
+                let e: I = transcript.challenge_below("e", bound);
+                assert!(e < *bound);
+            }
+        }
+    }
+);
@@ -0,0 +1,184 @@
+//! Serialization support for keys, plaintexts, and ciphertexts (requires the `serde` feature).
+//!
+//! Big integers are represented by their decimal string, making the format independent of the
+//! underlying arithmetic library. Only the essential key material is stored; all derived values
+//! (such as `nn` or the CRT constants) are recomputed when deserializing.
+
+use serde::de::Error;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Representation of a big integer in serialized form.
+pub fn to_repr<I>(x: &I) -> String
+where
+    I: Display,
+{
+    x.to_string()
+}
+
+/// Recover a big integer from its serialized form.
+pub fn from_repr<I, E>(repr: &str) -> Result<I, E>
+where
+    I: FromStr,
+    E: Error,
+{
+    I::from_str(repr).map_err(|_| E::custom(format!("invalid integer `{}`", repr)))
+}
+
+/// Serialized form of a keypair, also used for decryption keys.
+#[derive(Serialize, Deserialize)]
+pub struct KeypairRepr {
+    pub p: String,
+    pub q: String,
+}
+
+/// Serialized form of an encryption key.
+#[derive(Serialize, Deserialize)]
+pub struct EncryptionKeyRepr {
+    pub n: String,
+}
+
+/// Serialized form of an integral vector, plaintext or ciphertext.
+#[derive(Serialize, Deserialize)]
+pub struct VectorRepr {
+    pub data: String,
+    pub component_count: usize,
+    pub component_size: usize,
+}
+
+/// Serialized form of a floating-point number, plaintext or ciphertext.
+#[derive(Serialize, Deserialize)]
+pub struct EncodedNumberRepr {
+    pub data: String,
+    pub exponent: i32,
+    pub mantissa_bits: usize,
+}
+
+/// Serialized form of a proof of plaintext knowledge.
+#[derive(Serialize, Deserialize)]
+pub struct PlaintextKnowledgeProofRepr {
+    pub commitment: String,
+    pub z1: String,
+    pub z2: String,
+}
+
+/// Serialized form of a proof of correct decryption.
+#[derive(Serialize, Deserialize)]
+pub struct DecryptionProofRepr {
+    pub commitment: String,
+    pub response: String,
+}
+
+bigint!(
+    I,
+    #[cfg(test)]
+    mod tests {
+
+        use super::I;
+        use crate::core::*;
+        use crate::integral::{scalar, vector};
+        use crate::*;
+
+        fn test_keypair() -> Keypair<I> {
+            let p = str::parse("148677972634832330983979593310074301486537017973460461278300587514468301043894574906886127642530475786889672304776052879927627556769456140664043088700743909632312483413393134504352834240399191134336344285483935856491230340093391784574980688823380828143810804684752914935441384845195613674104960646037368551517").unwrap();
+            let q = str::parse("158741574437007245654463598139927898730476924736461654463975966787719309357536545869203069369466212089132653564188443272208127277664424448947476335413293018778018615899291704693105620242763173357203898195318179150836424196645745308205164116144020613415407736216097185962171301808761138424668335445923774195463").unwrap();
+            Keypair { p: p, q: q }
+        }
+
+        #[test]
+        fn test_keys_roundtrip() {
+            let keypair = test_keypair();
+            let json = serde_json::to_string(&keypair).unwrap();
+            let keypair: Keypair<I> = serde_json::from_str(&json).unwrap();
+            assert_eq!(keypair.p, test_keypair().p);
+            assert_eq!(keypair.q, test_keypair().q);
+
+            let (ek, dk) = keypair.keys();
+            let ek: EncryptionKey<I> =
+                serde_json::from_str(&serde_json::to_string(&ek).unwrap()).unwrap();
+            let dk: DecryptionKey<I> =
+                serde_json::from_str(&serde_json::to_string(&dk).unwrap()).unwrap();
+
+            let m = Plaintext::from(10);
+            let c = AbstractPaillier::encrypt(&ek, &m);
+            assert_eq!(AbstractPaillier::decrypt(&dk, &c), m);
+        }
+
+        #[test]
+        fn test_core_roundtrip() {
+            let (ek, dk) = test_keypair().keys();
+
+            let m = Plaintext::from(10);
+            let m_json = serde_json::to_string(&m).unwrap();
+            assert_eq!(m_json, "\"10\"");
+            assert_eq!(serde_json::from_str::<Plaintext<I>>(&m_json).unwrap(), m);
+
+            let c = AbstractPaillier::encrypt(&ek, &m);
+            let c: Ciphertext<I> =
+                serde_json::from_str(&serde_json::to_string(&c).unwrap()).unwrap();
+            assert_eq!(AbstractPaillier::decrypt(&dk, &c), m);
+        }
+
+        #[test]
+        fn test_integral_roundtrip() {
+            let (ek, dk) = test_keypair().keys();
+
+            let code = integral::Code::new(3, 16);
+
+            let m: scalar::Plaintext<I, u64> = code.encode(&10_u64);
+            let c = AbstractPaillier::encrypt(&ek, &m);
+            let c: scalar::Ciphertext<I, u64> =
+                serde_json::from_str(&serde_json::to_string(&c).unwrap()).unwrap();
+            let recovered_m: scalar::Plaintext<I, u64> = AbstractPaillier::decrypt(&dk, &c);
+            assert_eq!(recovered_m, m);
+
+            let m: vector::Plaintext<I, u64> = code.encode(&vec![1, 2, 3]);
+            let m: vector::Plaintext<I, u64> =
+                serde_json::from_str(&serde_json::to_string(&m).unwrap()).unwrap();
+            let c = AbstractPaillier::encrypt(&ek, &m);
+            let c: vector::Ciphertext<I, u64> =
+                serde_json::from_str(&serde_json::to_string(&c).unwrap()).unwrap();
+            let recovered_m: Vec<u64> = code.decode(&AbstractPaillier::decrypt(&dk, &c));
+            assert_eq!(recovered_m, vec![1, 2, 3]);
+        }
+
+        #[test]
+        fn test_invalid_keys_rejected() {
+            let p = test_keypair().p;
+            let json = format!("{{\"p\":\"{}\",\"q\":\"{}\"}}", p, p);
+            assert!(serde_json::from_str::<Keypair<I>>(&json).is_err());
+            assert!(serde_json::from_str::<DecryptionKey<I>>(&json).is_err());
+
+            assert!(serde_json::from_str::<Keypair<I>>("{\"p\":\"1\",\"q\":\"7\"}").is_err());
+            assert!(serde_json::from_str::<EncryptionKey<I>>("{\"n\":\"0\"}").is_err());
+            assert!(serde_json::from_str::<EncryptionKey<I>>("{\"n\":\"abc\"}").is_err());
+
+            // coprime but with 3 dividing both n = 21 and (p-1)(q-1) = 12, so that n has no
+            // inverse modulo p-1
+            let json = "{\"p\":\"7\",\"q\":\"3\"}";
+            assert!(serde_json::from_str::<Keypair<I>>(json).is_err());
+            assert!(serde_json::from_str::<DecryptionKey<I>>(json).is_err());
+        }
+
+        #[test]
+        fn test_invalid_vectors_rejected() {
+            let json = "{\"data\":\"1\",\"component_count\":0,\"component_size\":16}";
+            assert!(serde_json::from_str::<vector::Plaintext<I, u64>>(json).is_err());
+            assert!(serde_json::from_str::<vector::Ciphertext<I, u64>>(json).is_err());
+
+            let json = format!(
+                "{{\"data\":\"1\",\"component_count\":{},\"component_size\":16}}",
+                usize::MAX
+            );
+            assert!(serde_json::from_str::<vector::Plaintext<I, u64>>(&json).is_err());
+            assert!(serde_json::from_str::<vector::Ciphertext<I, u64>>(&json).is_err());
+
+            // 2^32 needs more than the two 16-bit components
+            let json = "{\"data\":\"4294967296\",\"component_count\":2,\"component_size\":16}";
+            assert!(serde_json::from_str::<vector::Plaintext<I, u64>>(json).is_err());
+            let json = "{\"data\":\"4294967295\",\"component_count\":2,\"component_size\":16}";
+            assert!(serde_json::from_str::<vector::Plaintext<I, u64>>(json).is_ok());
+        }
+    }
+);
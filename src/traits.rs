@@ -1,5 +1,7 @@
 //! Abstract operations exposed by the library.
 
+use crate::Error;
+
 /// Marker trait for the Paillier scheme.
 pub trait AbstractScheme {
     /// Underlying arbitrary precision arithmetic type.
@@ -51,6 +53,15 @@ pub trait Encryption<EK, PT, CT> {
     fn encrypt(ek: &EK, m: &PT) -> CT;
 }
 
+/// Encryption of plaintext with explicit randomness.
+pub trait EncryptionWithRandomness<EK, PT, R, CT> {
+    /// Encrypt plaintext `m` under key `ek` using randomness `r`.
+    fn encrypt_with_randomness(ek: &EK, m: &PT, r: &R) -> CT;
+
+    /// Encrypt plaintext `m` under key `ek`, returning the freshly sampled randomness as well.
+    fn encrypt_returning_randomness(ek: &EK, m: &PT) -> (CT, R);
+}
+
 /// Decryption of ciphertext.
 pub trait Decryption<DK, CT, PT> {
     /// Decrypt ciphertext `c` using key `dk` into a plaintext.
@@ -64,10 +75,28 @@ pub trait Addition<EK, CT1, CT2, CT> {
     fn add(ek: &EK, c1: &CT1, c2: &CT2) -> CT;
 }
 
+/// Negation of ciphertext.
+pub trait Negation<EK, CT1, CT> {
+    /// Homomorphically negate ciphertext `c` to obtain a ciphertext containing the additive
+    /// inverse of the underlying plaintext, reduced modulus `n` from `ek`.
+    ///
+    /// May panic if `c` is malformed; see `TryNegation` for untrusted ciphertexts.
+    fn neg(ek: &EK, c: &CT1) -> CT;
+}
+
+/// Subtraction of two ciphertexts.
+pub trait Subtraction<EK, CT1, CT2, CT> {
+    /// Homomorphically combine ciphertexts `c1` and `c2` to obtain a ciphertext containing
+    /// the difference of the two underlying plaintexts, reduced modulus `n` from `ek`.
+    fn sub(ek: &EK, c1: &CT1, c2: &CT2) -> CT;
+}
+
 /// Multiplication of ciphertext with plaintext.
 pub trait Multiplication<EK, CT1, PT2, CT> {
     /// Homomorphically combine ciphertext `c1` and plaintext `m2` to obtain a ciphertext
     /// containing the multiplication of the (underlying) plaintexts, reduced modulus `n` from `ek`.
+    ///
+    /// Negative plaintexts `-k` are treated as `n - k`.
     fn mul(ek: &EK, c1: &CT1, m2: &PT2) -> CT;
 }
 
@@ -78,6 +107,73 @@ pub trait Rerandomisation<EK, CT> {
     fn rerandomise(ek: &EK, c: &CT) -> CT;
 }
 
+/// Fallible encryption of plaintext.
+pub trait TryEncryption<EK, PT, CT> {
+    /// Encrypt plaintext `m` under key `ek` into a ciphertext, failing if `m` is out of range.
+    fn try_encrypt(ek: &EK, m: &PT) -> Result<CT, Error>;
+}
+
+/// Fallible decryption of ciphertext.
+pub trait TryDecryption<DK, CT, PT> {
+    /// Decrypt ciphertext `c` using key `dk` into a plaintext, failing if `c` is malformed.
+    fn try_decrypt(dk: &DK, c: &CT) -> Result<PT, Error>;
+}
+
+/// Fallible addition of two ciphertexts.
+pub trait TryAddition<EK, CT1, CT2, CT> {
+    /// As `Addition::add`, but failing if either `c1` or `c2` is malformed.
+    fn try_add(ek: &EK, c1: &CT1, c2: &CT2) -> Result<CT, Error>;
+}
+
+/// Fallible subtraction of two ciphertexts.
+pub trait TrySubtraction<EK, CT1, CT2, CT> {
+    /// As `Subtraction::sub`, but failing if either `c1` or `c2` is malformed.
+    fn try_sub(ek: &EK, c1: &CT1, c2: &CT2) -> Result<CT, Error>;
+}
+
+/// Fallible negation of ciphertext.
+pub trait TryNegation<EK, CT1, CT> {
+    /// As `Negation::neg`, but failing if `c` is malformed.
+    fn try_neg(ek: &EK, c: &CT1) -> Result<CT, Error>;
+}
+
+/// Fallible multiplication of ciphertext with plaintext.
+pub trait TryMultiplication<EK, CT1, PT2, CT> {
+    /// As `Multiplication::mul`, but failing if `c1` is malformed or `m2` is out of range.
+    fn try_mul(ek: &EK, c1: &CT1, m2: &PT2) -> Result<CT, Error>;
+}
+
+/// Validation of untrusted ciphertexts.
+pub trait Validation<EK, CT, VCT> {
+    /// Check that ciphertext `c` is well-formed under key `ek`, returning a validated ciphertext
+    /// that can safely be used in homomorphic operations and decryption.
+    fn validate(ek: &EK, c: &CT) -> Result<VCT, Error>;
+}
+
+/// Encryption of several plaintexts in parallel (requires the `parallel` feature).
+#[cfg(feature = "parallel")]
+pub trait BatchEncryption<EK, PT, CT> {
+    /// Encrypt all plaintexts in `ms` under key `ek`, preserving their order.
+    fn encrypt_batch(ek: &EK, ms: &[PT]) -> Vec<CT>;
+}
+
+/// Decryption of several ciphertexts in parallel (requires the `parallel` feature).
+#[cfg(feature = "parallel")]
+pub trait BatchDecryption<DK, CT, PT> {
+    /// Decrypt all ciphertexts in `cs` using key `dk`, preserving their order.
+    fn decrypt_batch(dk: &DK, cs: &[CT]) -> Vec<PT>;
+}
+
+/// Addition of several ciphertexts in parallel (requires the `parallel` feature).
+#[cfg(feature = "parallel")]
+pub trait BatchAddition<EK, CT> {
+    /// Pairwise add the ciphertexts in `c1s` and `c2s`, which must be of equal length.
+    fn add_batch(ek: &EK, c1s: &[CT], c2s: &[CT]) -> Vec<CT>;
+
+    /// Add together all ciphertexts in `cs`, returning `None` if there are none.
+    fn sum_batch(ek: &EK, cs: &[CT]) -> Option<CT>;
+}
+
 /// Marker trait to avoid conflicting implementations.
 // Future support for negative traits could void this.
 pub trait EncodableType {}
@@ -89,6 +185,10 @@ impl<T: Into<u64>> EncodableType for Vec<T> {}
 // impl EncodableType for u16 {}
 // impl EncodableType for u32 {}
 impl EncodableType for u64 {}
+impl EncodableType for i32 {}
+impl EncodableType for i64 {}
+impl EncodableType for i128 {}
+impl EncodableType for f64 {}
 
 /// Encoding into plaintexts.
 pub trait Encoder<T> {
@@ -98,6 +198,12 @@ pub trait Encoder<T> {
     fn encode(&self, x: &T) -> Self::Target;
 }
 
+/// Fallible encoding into plaintexts.
+pub trait TryEncoder<T>: Encoder<T> {
+    /// Encode `T` types into `Target` types, failing if `x` cannot be represented.
+    fn try_encode(&self, x: &T) -> Result<Self::Target, Error>;
+}
+
 /// Decoding from plaintexts.
 pub trait Decoder<T> {
     type Source;
@@ -105,3 +211,9 @@ pub trait Decoder<T> {
     /// Decode `Source` types into `T` types.
     fn decode(&self, y: &Self::Source) -> T;
 }
+
+/// Fallible decoding from plaintexts.
+pub trait TryDecoder<T>: Decoder<T> {
+    /// Decode `Source` types into `T` types, failing if the value does not fit in `T`.
+    fn try_decode(&self, y: &Self::Source) -> Result<T, Error>;
+}